@@ -6,9 +6,17 @@ use std::{
     cell::UnsafeCell,
     ffi::c_void,
     fmt::Debug,
+    future::Future,
     hint::spin_loop,
+    pin::Pin,
     ptr::NonNull,
-    sync::{atomic::AtomicBool, Arc, Mutex},
+    sync::{
+        atomic::AtomicBool,
+        mpsc::{channel, Sender},
+        Arc, Mutex,
+    },
+    task::{Context, Wake, Waker},
+    thread,
 };
 
 use atomic::Ordering;
@@ -74,6 +82,16 @@ unsafe fn init_pool() -> &'static Mutex<ThreadPool> {
     })
 }
 
+// The executor that polls futures returned by exported async functions. If no executor has been
+// set when the first future is spawned, a `DefaultExecutor` is used.
+static EXECUTOR: OnceCell<Box<dyn FutureExecutor>> = OnceCell::new();
+
+fn executor() -> &'static dyn FutureExecutor {
+    EXECUTOR
+        .get_or_init(|| Box::new(DefaultExecutor::new()))
+        .as_ref()
+}
+
 unsafe extern "C" fn set_pool_size(size: usize) {
     init_pool().lock().unwrap().set_num_threads(size);
 }
@@ -251,6 +269,31 @@ impl<'context> CCall<'context> {
         handle
     }
 
+    /// Set the executor that is used to poll futures returned by exported async functions.
+    ///
+    /// The executor can only be set once, and must be set before the first future is spawned.
+    /// Returns `false` if an executor had already been set. When the `julia_module` macro is used
+    /// the executor can be configured with `become init_fn with executor_fn;`.
+    pub fn set_executor<E: FutureExecutor>(executor: E) -> bool {
+        EXECUTOR.set(Box::new(executor)).is_ok()
+    }
+
+    /// Spawn the future returned by `func` on the executor.
+    ///
+    /// The executor must have been set with [`CCall::set_executor`], otherwise a
+    /// [`DefaultExecutor`] is used.
+    pub fn spawn_future<F, Fut, T>(func: F) -> Arc<DispatchHandle<T>>
+    where
+        F: FnOnce(Arc<DispatchHandle<T>>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+        T: IntoJulia + Send + Sync + ConstructType,
+    {
+        let handle = DispatchHandle::new();
+        let future = func(handle.clone());
+        executor().spawn(Box::pin(future));
+        handle
+    }
+
     /// This function must be called before jlrs can be used. When the `julia_module` macro is
     /// used this function is called automatically.
     ///
@@ -299,6 +342,36 @@ where
     rewrapped
 }
 
+// The version of jlrs, it's part of the ABI fingerprint of a module defined with `julia_module`.
+#[doc(hidden)]
+pub const JLRS_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// The initial value of the FNV-1a hash that `julia_module` computes as the ABI fingerprint of a
+// module.
+#[doc(hidden)]
+pub const ABI_FINGERPRINT_SEED: u64 = 0xcbf29ce484222325;
+
+// Used by `julia_module` to compute the ABI fingerprint of a module, continues the FNV-1a hash
+// `hash` with `bytes`.
+#[doc(hidden)]
+pub const fn abi_fingerprint(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = (hash ^ bytes[i] as u64).wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+
+    hash
+}
+
+// Used by `julia_module` to add the size and alignment of an exported type to the ABI
+// fingerprint of a module.
+#[doc(hidden)]
+pub fn layout_fingerprint<T>(hash: u64) -> u64 {
+    let hash = abi_fingerprint(hash, &std::mem::size_of::<T>().to_le_bytes());
+    abi_fingerprint(hash, &std::mem::align_of::<T>().to_le_bytes())
+}
+
 #[doc(hidden)]
 #[repr(transparent)]
 pub struct AsyncConditionHandle(pub *mut c_void);
@@ -365,6 +438,107 @@ where
 {
 }
 
+/// Trait implemented by futures that can be returned by exported async functions.
+pub trait RustFuture<T: IntoJulia + Send + ConstructType>:
+    'static + Send + Future<Output = JlrsResult<T>>
+{
+}
+
+impl<T, U> RustFuture<T> for U
+where
+    T: IntoJulia + Send + ConstructType,
+    U: 'static + Send + Future<Output = JlrsResult<T>>,
+{
+}
+
+/// An executor that can poll the futures returned by exported async functions.
+///
+/// The futures are spawned from the thread that calls the exported function, the executor must
+/// poll them on another thread. They must not call into Julia.
+pub trait FutureExecutor: 'static + Send + Sync {
+    /// Spawn `future` on this executor.
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>);
+}
+
+#[cfg(feature = "tokio")]
+impl FutureExecutor for tokio::runtime::Runtime {
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        tokio::runtime::Runtime::spawn(self, future);
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl FutureExecutor for tokio::runtime::Handle {
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        tokio::runtime::Handle::spawn(self, future);
+    }
+}
+
+/// A minimal executor that polls all futures on a single background thread.
+///
+/// This executor is used if no other executor has been set with [`CCall::set_executor`].
+pub struct DefaultExecutor {
+    sender: Mutex<Sender<Arc<ExecutorTask>>>,
+}
+
+impl DefaultExecutor {
+    /// Create a new `DefaultExecutor` and start its background thread.
+    pub fn new() -> Self {
+        let (sender, receiver) = channel::<Arc<ExecutorTask>>();
+
+        thread::Builder::new()
+            .name("jlrs-executor".into())
+            .spawn(move || {
+                while let Ok(task) = receiver.recv() {
+                    let mut slot = task.future.lock().unwrap();
+                    if let Some(mut future) = slot.take() {
+                        let waker = Waker::from(task.clone());
+                        let mut context = Context::from_waker(&waker);
+                        if future.as_mut().poll(&mut context).is_pending() {
+                            *slot = Some(future);
+                        }
+                    }
+                }
+            })
+            .expect("Cannot spawn executor thread");
+
+        DefaultExecutor {
+            sender: Mutex::new(sender),
+        }
+    }
+}
+
+impl Default for DefaultExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FutureExecutor for DefaultExecutor {
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        let sender = self.sender.lock().unwrap().clone();
+        let task = Arc::new(ExecutorTask {
+            future: Mutex::new(Some(future)),
+            sender: Mutex::new(sender.clone()),
+        });
+
+        sender.send(task).ok();
+    }
+}
+
+#[doc(hidden)]
+pub struct ExecutorTask {
+    future: Mutex<Option<Pin<Box<dyn Future<Output = ()> + Send>>>>,
+    sender: Mutex<Sender<Arc<ExecutorTask>>>,
+}
+
+impl Wake for ExecutorTask {
+    fn wake(self: Arc<Self>) {
+        let sender = self.sender.lock().unwrap().clone();
+        sender.send(self).ok();
+    }
+}
+
 /// A handle to a function call that has been dispatched to a thread pool.
 pub struct DispatchHandle<T> {
     result: UnsafeCell<Option<JlrsResult<T>>>,
@@ -419,3 +593,57 @@ impl<T: IntoJulia> DispatchHandle<T> {
 
 unsafe impl<T> Sync for DispatchHandle<T> {}
 unsafe impl<T> Send for DispatchHandle<T> {}
+
+/// Start Julia, generate the bindings of a module defined with `julia_module` and write them to
+/// `path`.
+///
+/// This is a convenience wrapper around [`Module::generate_rust_module_bindings`] that can be
+/// called from a build script or a small binary that depends on the crate that defines the
+/// module. The generated module is named `module_name`, `library_path` is the path of the
+/// library that the generated module loads, `init_fn` and `fingerprint` must be the
+/// initialization function and the value returned by `<init_fn_name>_abi_fingerprint`.
+///
+/// The exported types, constants and globals aren't defined in the generated file, they're still
+/// created by calling the initialization function when the generated module is loaded. Only the
+/// functions, their documentation and the generated wrappers are defined statically.
+///
+/// Julia can only be initialized once, so this function must not be called if Julia has already
+/// been initialized by this process.
+///
+/// Safety: `init_fn` and `init_fn_name` must refer to the same initialization function.
+#[cfg(feature = "sync-rt")]
+pub unsafe fn write_rust_module_bindings<P: AsRef<std::path::Path>>(
+    path: P,
+    module_name: &str,
+    library_path: &str,
+    init_fn_name: &str,
+    init_fn: unsafe extern "C" fn(Module, u8) -> crate::data::managed::value::ValueRet,
+    fingerprint: u64,
+) -> JlrsResult<()> {
+    use crate::{
+        convert::into_jlrs_result::IntoJlrsResult, data::managed::string::JuliaString,
+        runtime::builder::RuntimeBuilder,
+    };
+
+    let mut julia = RuntimeBuilder::new().start()?;
+    let mut frame = StackFrame::new();
+    let bindings = julia.instance(&mut frame).scope(|mut frame| {
+        let name = JuliaString::new(&mut frame, module_name).as_value();
+        let module = Value::eval_string(&mut frame, "name -> Module(Symbol(name))")
+            .into_jlrs_result()?
+            .call1(&mut frame, name)
+            .into_jlrs_result()?
+            .cast::<Module>()?;
+
+        module.generate_rust_module_bindings(
+            &mut frame,
+            library_path,
+            init_fn_name,
+            init_fn,
+            fingerprint,
+        )
+    })?;
+
+    std::fs::write(path, bindings).map_err(JlrsError::other)?;
+    Ok(())
+}
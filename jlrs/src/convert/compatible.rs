@@ -30,20 +30,24 @@ unsafe impl<T: ValidLayout> Compatible<T> for T {}
 
 /// Cast data to a compatible type.
 ///
-/// If `T` implements [`ValidLayout`] this trait is automatically implemented for `T`, `[T]`, and
-/// `[T; N]`. The methods of this traits substitute `T` with `U` and can only be called if
+/// If `T` implements [`ValidLayout`] this trait is automatically implemented for `T` and `[T]`.
+/// The methods of this traits substitute `T` with `U` and can only be called if
 /// `T: Compatible<U>` which guarantees this conversion is valid.
+///
+/// Arrays `[T; N]` are layouts of `NTuple{N, T}` if `T` is a valid field type, in which case the
+/// array as a whole is cast and `[T; N]` must be compatible with `U`. To cast the elements of an
+/// array, cast it as a slice instead and convert the result back to an array if necessary:
+/// `<&[U; N]>::try_from(array[..].compatible_cast::<U>()).unwrap()`.
 pub trait CompatibleCast: CompatibleCastPriv {
     type Inner;
     type Output<U: Sized>: ?Sized;
 
-    /// Converts `&Self` to `&U`, `&[Self]` to `&[U]`, and `&[Self; N]` to `&[U; N]`.
+    /// Converts `&Self` to `&U` and `&[Self]` to `&[U]`.
     fn compatible_cast<U>(&self) -> &Self::Output<U>
     where
         Self::Inner: Compatible<U>;
 
-    /// Converts `&mut Self` to `&mut U`, `&mut [Self]` to `&mut [U]`, and `&mut [Self; N]` to
-    /// `&mut [U; N]`.
+    /// Converts `&mut Self` to `&mut U` and `&mut [Self]` to `&mut [U]`.
     fn compatible_cast_mut<U>(&mut self) -> &mut Self::Output<U>
     where
         Self::Inner: Compatible<U>;
@@ -99,31 +103,6 @@ impl<T: ValidLayout> CompatibleCast for [T] {
     }
 }
 
-impl<T: ValidLayout, const N: usize> CompatibleCast for [T; N] {
-    type Inner = T;
-    type Output<U: Sized> = [U; N];
-
-    #[inline(always)]
-    fn compatible_cast<U>(&self) -> &Self::Output<U>
-    where
-        T: Compatible<U>,
-    {
-        debug_assert_eq!(std::mem::size_of::<T>(), std::mem::size_of::<U>());
-        debug_assert_eq!(std::mem::align_of::<T>(), std::mem::align_of::<U>());
-        unsafe { std::mem::transmute(self) }
-    }
-
-    #[inline(always)]
-    fn compatible_cast_mut<U>(&mut self) -> &mut Self::Output<U>
-    where
-        T: Compatible<U>,
-    {
-        debug_assert_eq!(std::mem::size_of::<T>(), std::mem::size_of::<U>());
-        debug_assert_eq!(std::mem::align_of::<T>(), std::mem::align_of::<U>());
-        unsafe { std::mem::transmute(self) }
-    }
-}
-
 mod private {
     use crate::data::layout::valid_layout::ValidLayout;
 
@@ -132,14 +111,15 @@ mod private {
     impl<T: ValidLayout> CompatibleCastPriv for T {}
 
     impl<T: ValidLayout> CompatibleCastPriv for [T] {}
-
-    impl<T: ValidLayout, const N: usize> CompatibleCastPriv for [T; N] {}
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Compatible, CompatibleCast};
-    use crate::data::{layout::valid_layout::ValidLayout, managed::value::Value};
+    use crate::data::{
+        layout::valid_layout::{ValidField, ValidLayout},
+        managed::value::Value,
+    };
 
     #[repr(C)]
     struct A {
@@ -161,6 +141,31 @@ mod tests {
 
     unsafe impl Compatible<B> for A {}
 
+    #[repr(C)]
+    #[derive(Clone, Debug)]
+    struct C {
+        a: f64,
+    }
+
+    #[repr(C)]
+    struct D {
+        b: f64,
+    }
+
+    unsafe impl ValidLayout for C {
+        fn valid_layout(_: Value) -> bool {
+            unimplemented!()
+        }
+    }
+
+    unsafe impl ValidField for C {
+        fn valid_field(_: Value) -> bool {
+            unimplemented!()
+        }
+    }
+
+    unsafe impl Compatible<D> for C {}
+
     #[test]
     fn compatible_cast_ref() {
         let a = &A { a: 1.0, b: 2.0 };
@@ -235,4 +240,22 @@ mod tests {
         assert_eq!(a[0].a, 2.0);
         assert_eq!(a[0].b, 3.0);
     }
+
+    #[test]
+    fn compatible_cast_field_array_elements() {
+        let a = &mut [C { a: 1.0 }, C { a: 2.0 }];
+
+        {
+            let b = <&[D; 2]>::try_from(a[..].compatible_cast::<D>()).unwrap();
+            assert_eq!(b[0].b, a[0].a);
+            assert_eq!(b[1].b, a[1].a);
+        }
+
+        {
+            let b = <&mut [D; 2]>::try_from(a[..].compatible_cast_mut::<D>()).unwrap();
+            b[1].b = 3.0;
+        }
+
+        assert_eq!(a[1].a, 3.0);
+    }
 }
@@ -6,6 +6,8 @@ pub mod into_jlrs_result;
 pub mod into_julia;
 #[cfg(feature = "async-rt")]
 pub mod into_result;
+#[cfg(feature = "jlrs-nalgebra")]
+pub mod nalgebra;
 #[cfg(feature = "jlrs-ndarray")]
 pub mod ndarray;
 pub mod to_symbol;
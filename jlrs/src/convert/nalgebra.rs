@@ -0,0 +1,285 @@
+//! Borrow data from Julia arrays as `nalgebra`'s `DMatrixView` and `DMatrixViewMut`, and move
+//! `nalgebra`'s `DMatrix` and `DVector` into Julia.
+//!
+//! Julia and `nalgebra` both store matrices in column-major order, so a matrix can be borrowed
+//! from and moved into Julia without copying its elements. Only arrays with a rank of at most 2
+//! can be borrowed as a matrix, vectors are borrowed as a matrix with a single column.
+
+use nalgebra::{DMatrix, DMatrixView, DMatrixViewMut, DVector, Scalar};
+
+use super::{
+    compatible::{Compatible, CompatibleCast},
+    into_julia::IntoJulia,
+};
+use crate::{
+    data::{
+        layout::valid_layout::ValidField,
+        managed::array::{
+            data::{
+                accessor::{BitsArrayAccessor, InlinePtrArrayAccessor, Mutability, Mutable},
+                copied::CopiedArray,
+            },
+            TypedArray, TypedArrayResult,
+        },
+    },
+    error::{ArrayLayoutError, JlrsResult},
+    memory::target::{ExtendedTarget, Target},
+};
+
+/// Trait to borrow Julia arrays with inline data as `nalgebra`'s `DMatrixView`.
+pub trait NalgebraView<'view, T: Scalar>: private::NalgebraPriv {
+    /// Borrow the data in the array as a `DMatrixView`.
+    ///
+    /// Returns an error if the rank of the array is larger than 2.
+    fn matrix_view(&'view self) -> JlrsResult<DMatrixView<'view, T>>;
+
+    /// Borrow the data in the array as a `DMatrixView` of a compatible type `U`.
+    ///
+    /// Returns an error if the rank of the array is larger than 2.
+    fn compatible_matrix_view<U>(&'view self) -> JlrsResult<DMatrixView<'view, U>>
+    where
+        T: Compatible<U>,
+        U: Scalar;
+}
+
+/// Trait to borrow Julia arrays with inline data as `nalgebra`'s `DMatrixViewMut`.
+pub trait NalgebraViewMut<'view, T: Scalar>: NalgebraView<'view, T> {
+    /// Mutably borrow the data in the array as a `DMatrixViewMut`.
+    ///
+    /// Returns an error if the rank of the array is larger than 2.
+    fn matrix_view_mut(&'view mut self) -> JlrsResult<DMatrixViewMut<'view, T>>;
+
+    /// Mutably borrow the data in the array as a `DMatrixViewMut` of a compatible type `U`.
+    ///
+    /// Returns an error if the rank of the array is larger than 2.
+    fn compatible_matrix_view_mut<U>(&'view mut self) -> JlrsResult<DMatrixViewMut<'view, U>>
+    where
+        T: Compatible<U>,
+        U: Scalar;
+}
+
+/// Trait to move `nalgebra`'s `DMatrix` and `DVector` into Julia arrays.
+pub trait NalgebraIntoJulia<T: ValidField>: private::NalgebraPriv {
+    /// Move the data of this matrix or vector to a new Julia array with the same shape.
+    ///
+    /// The Julia array takes ownership of the data without copying it like
+    /// [`TypedArray::from_vec`] does.
+    ///
+    /// If the array size is too large, Julia will throw an error. This error is caught and
+    /// returned.
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayResult<'target, 'static, S, T>>
+    where
+        S: Target<'target>;
+}
+
+impl<T> NalgebraIntoJulia<T> for DMatrix<T>
+where
+    T: IntoJulia + ValidField + Scalar,
+{
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayResult<'target, 'static, S, T>>
+    where
+        S: Target<'target>,
+    {
+        let dims = self.shape();
+        let data: Vec<T> = self.data.into();
+        TypedArray::from_vec(target, data, dims)
+    }
+}
+
+impl<T> NalgebraIntoJulia<T> for DVector<T>
+where
+    T: IntoJulia + ValidField + Scalar,
+{
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayResult<'target, 'static, S, T>>
+    where
+        S: Target<'target>,
+    {
+        let n = self.nrows();
+        let data: Vec<T> = self.data.into();
+        TypedArray::from_vec(target, data, n)
+    }
+}
+
+impl<'borrow: 'view, 'view, 'array, 'data, T, M> NalgebraView<'view, T>
+    for BitsArrayAccessor<'borrow, 'array, 'data, T, M>
+where
+    T: Scalar,
+    M: Mutability,
+{
+    fn matrix_view(&'view self) -> JlrsResult<DMatrixView<'view, T>> {
+        // Safety: while the array is borrowed nothing can be pushed or popped from it.
+        let (nrows, ncols) = matrix_shape(unsafe { self.dimensions().as_slice() })?;
+        Ok(DMatrixView::from_slice(self.as_slice(), nrows, ncols))
+    }
+
+    fn compatible_matrix_view<U>(&'view self) -> JlrsResult<DMatrixView<'view, U>>
+    where
+        T: Compatible<U>,
+        U: Scalar,
+    {
+        // Safety: while the array is borrowed nothing can be pushed or popped from it.
+        let (nrows, ncols) = matrix_shape(unsafe { self.dimensions().as_slice() })?;
+        Ok(DMatrixView::from_slice(
+            self.as_slice().compatible_cast(),
+            nrows,
+            ncols,
+        ))
+    }
+}
+
+impl<'borrow: 'view, 'view, 'array, 'data, T, M> NalgebraView<'view, T>
+    for InlinePtrArrayAccessor<'borrow, 'array, 'data, T, M>
+where
+    T: Scalar,
+    M: Mutability,
+{
+    fn matrix_view(&'view self) -> JlrsResult<DMatrixView<'view, T>> {
+        // Safety: while the array is borrowed nothing can be pushed or popped from it.
+        let (nrows, ncols) = matrix_shape(unsafe { self.dimensions().as_slice() })?;
+        Ok(DMatrixView::from_slice(self.as_slice(), nrows, ncols))
+    }
+
+    fn compatible_matrix_view<U>(&'view self) -> JlrsResult<DMatrixView<'view, U>>
+    where
+        T: Compatible<U>,
+        U: Scalar,
+    {
+        // Safety: while the array is borrowed nothing can be pushed or popped from it.
+        let (nrows, ncols) = matrix_shape(unsafe { self.dimensions().as_slice() })?;
+        Ok(DMatrixView::from_slice(
+            self.as_slice().compatible_cast(),
+            nrows,
+            ncols,
+        ))
+    }
+}
+
+impl<'borrow: 'view, 'view, 'array, 'data, T> NalgebraViewMut<'view, T>
+    for BitsArrayAccessor<'borrow, 'array, 'data, T, Mutable<'borrow, T>>
+where
+    T: Scalar,
+{
+    fn matrix_view_mut(&'view mut self) -> JlrsResult<DMatrixViewMut<'view, T>> {
+        // Safety: while the array is borrowed nothing can be pushed or popped from it.
+        let (nrows, ncols) = matrix_shape(unsafe { self.dimensions().as_slice() })?;
+        Ok(DMatrixViewMut::from_slice(
+            self.as_mut_slice(),
+            nrows,
+            ncols,
+        ))
+    }
+
+    fn compatible_matrix_view_mut<U>(&'view mut self) -> JlrsResult<DMatrixViewMut<'view, U>>
+    where
+        T: Compatible<U>,
+        U: Scalar,
+    {
+        // Safety: while the array is borrowed nothing can be pushed or popped from it.
+        let (nrows, ncols) = matrix_shape(unsafe { self.dimensions().as_slice() })?;
+        Ok(DMatrixViewMut::from_slice(
+            self.as_mut_slice().compatible_cast_mut(),
+            nrows,
+            ncols,
+        ))
+    }
+}
+
+impl<'view, T> NalgebraView<'view, T> for CopiedArray<T>
+where
+    T: Scalar,
+{
+    fn matrix_view(&'view self) -> JlrsResult<DMatrixView<'view, T>> {
+        let (nrows, ncols) = matrix_shape(self.dimensions().as_slice())?;
+        Ok(DMatrixView::from_slice(self.as_slice(), nrows, ncols))
+    }
+
+    fn compatible_matrix_view<U>(&'view self) -> JlrsResult<DMatrixView<'view, U>>
+    where
+        T: Compatible<U>,
+        U: Scalar,
+    {
+        let (nrows, ncols) = matrix_shape(self.dimensions().as_slice())?;
+        Ok(DMatrixView::from_slice(
+            self.as_slice().compatible_cast(),
+            nrows,
+            ncols,
+        ))
+    }
+}
+
+impl<'view, T> NalgebraViewMut<'view, T> for CopiedArray<T>
+where
+    T: Scalar,
+{
+    fn matrix_view_mut(&'view mut self) -> JlrsResult<DMatrixViewMut<'view, T>> {
+        let (nrows, ncols) = matrix_shape(self.dimensions().as_slice())?;
+        Ok(DMatrixViewMut::from_slice(
+            self.as_mut_slice(),
+            nrows,
+            ncols,
+        ))
+    }
+
+    fn compatible_matrix_view_mut<U>(&'view mut self) -> JlrsResult<DMatrixViewMut<'view, U>>
+    where
+        T: Compatible<U>,
+        U: Scalar,
+    {
+        let (nrows, ncols) = matrix_shape(self.dimensions().as_slice())?;
+        Ok(DMatrixViewMut::from_slice(
+            self.as_mut_slice().compatible_cast_mut(),
+            nrows,
+            ncols,
+        ))
+    }
+}
+
+// Returns the number of rows and columns of an array with dimensions `dims`.
+fn matrix_shape(dims: &[usize]) -> JlrsResult<(usize, usize)> {
+    match *dims {
+        [] => Ok((1, 1)),
+        [nrows] => Ok((nrows, 1)),
+        [nrows, ncols] => Ok((nrows, ncols)),
+        _ => Err(ArrayLayoutError::RankMismatch {
+            found: dims.len() as isize,
+            provided: 2,
+        })?,
+    }
+}
+
+mod private {
+    use nalgebra::{DMatrix, DVector, Scalar};
+
+    use crate::data::managed::array::data::{
+        accessor::{BitsArrayAccessor, InlinePtrArrayAccessor, Mutability},
+        copied::CopiedArray,
+    };
+
+    pub trait NalgebraPriv {}
+    impl<'borrow, 'array, 'data, T, M> NalgebraPriv
+        for InlinePtrArrayAccessor<'borrow, 'array, 'data, T, M>
+    where
+        M: Mutability,
+    {
+    }
+
+    impl<'borrow, 'array, 'data, T, M> NalgebraPriv for BitsArrayAccessor<'borrow, 'array, 'data, T, M> where
+        M: Mutability
+    {
+    }
+
+    impl<T> NalgebraPriv for CopiedArray<T> {}
+
+    impl<T: Scalar> NalgebraPriv for DMatrix<T> {}
+
+    impl<T: Scalar> NalgebraPriv for DVector<T> {}
+}
@@ -1,11 +1,33 @@
-//! Borrow data from Julia arrays as `ndarray`'s `ArrayView` and `ArrayViewMut`.
+//! Borrow data from Julia arrays as `ndarray`'s `ArrayView` and `ArrayViewMut`, and move
+//! `ndarray`'s `Array`s into Julia.
 
-use ndarray::{ArrayView, ArrayViewMut, Dim, IntoDimension, IxDynImpl, ShapeBuilder};
+use std::mem;
 
-use super::compatible::{Compatible, CompatibleCast};
-use crate::data::managed::array::data::{
-    accessor::{BitsArrayAccessor, InlinePtrArrayAccessor, Mutability, Mutable},
-    copied::CopiedArray,
+use ndarray::{
+    Array as NdArray, ArrayView, ArrayViewMut, Axis, Dim, Dimension, IntoDimension, IxDynImpl,
+    ShapeBuilder, StrideShape,
+};
+
+use super::{
+    compatible::{Compatible, CompatibleCast},
+    into_julia::IntoJulia,
+};
+use crate::{
+    data::{
+        layout::valid_layout::ValidField,
+        managed::array::{
+            data::{
+                accessor::{
+                    BitsArrayAccessor, InlinePtrArrayAccessor, Mutability, Mutable,
+                    StridedArrayAccessor,
+                },
+                copied::CopiedArray,
+            },
+            TypedArray, TypedArrayResult,
+        },
+    },
+    error::JlrsResult,
+    memory::target::{ExtendedTarget, Target},
 };
 
 /// Trait to borrow Julia arrays with inline data as `ndarray`'s `ArrayView`.
@@ -30,6 +52,76 @@ pub trait NdArrayViewMut<'view, T>: NdArrayView<'view, T> {
         T: Compatible<U>;
 }
 
+/// Trait to move `ndarray`'s `Array`s into Julia arrays.
+pub trait NdArrayIntoJulia<T: ValidField>: private::NdArrayPriv {
+    /// Move the data of this array to a new Julia array with the same shape.
+    ///
+    /// If the elements are stored contiguously in column-major order, the Julia array takes
+    /// ownership of the data without copying it like [`TypedArray::from_vec`] does. Otherwise,
+    /// e.g. because the elements are stored in row-major order, they are copied to a new
+    /// column-major buffer first.
+    ///
+    /// If the array size is too large, Julia will throw an error. This error is caught and
+    /// returned.
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayResult<'target, 'static, S, T>>
+    where
+        S: Target<'target>;
+}
+
+impl<T, D> NdArrayIntoJulia<T> for NdArray<T, D>
+where
+    T: IntoJulia + ValidField + Clone,
+    D: Dimension,
+{
+    fn into_julia_array<'target, S>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, S>,
+    ) -> JlrsResult<TypedArrayResult<'target, 'static, S, T>>
+    where
+        S: Target<'target>,
+    {
+        let dims = self.shape().to_vec();
+        let data = into_column_major_vec(self);
+        TypedArray::from_vec(target, data, dims.as_slice())
+    }
+}
+
+// Returns the elements of `array` in column-major order. The buffer of `array` is reused if its
+// elements are stored contiguously in column-major order.
+fn into_column_major_vec<T, D>(array: NdArray<T, D>) -> Vec<T>
+where
+    T: Clone,
+    D: Dimension,
+{
+    // The reversed axes of a column-major array are in row-major order.
+    if !array.t().is_standard_layout() {
+        return array.t().iter().cloned().collect();
+    }
+
+    let len = array.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let ptr = array.as_ptr();
+    let mut data = array.into_raw_vec();
+
+    // The array can start at an offset in its buffer, e.g. if it has been sliced in place.
+    let offset = if mem::size_of::<T>() == 0 {
+        0
+    } else {
+        // Safety: both pointers point into the same allocation.
+        unsafe { ptr.offset_from(data.as_ptr()) as usize }
+    };
+
+    data.truncate(offset + len);
+    data.drain(..offset);
+    data
+}
+
 impl<'borrow: 'view, 'view, 'array, 'data, T, M> NdArrayView<'view, T>
     for BitsArrayAccessor<'borrow, 'array, 'data, T, M>
 where
@@ -119,9 +211,102 @@ impl<'view, T> NdArrayViewMut<'view, T> for CopiedArray<T> {
     }
 }
 
+impl<'borrow: 'view, 'view, 'array, 'data, T, M> NdArrayView<'view, T>
+    for StridedArrayAccessor<'borrow, 'array, 'data, T, M>
+where
+    M: Mutability,
+{
+    fn array_view(&'view self) -> ArrayView<'view, T, Dim<IxDynImpl>> {
+        let (shape, ptr, inverted) = strided_parts(self.dimensions().as_slice(), self.strides());
+        // Safety: the shape and strides describe the data of the view, which is borrowed.
+        let mut view = unsafe { ArrayView::from_shape_ptr(shape, self.as_ptr().offset(ptr)) };
+        for axis in inverted {
+            view.invert_axis(Axis(axis));
+        }
+
+        view
+    }
+
+    fn compatible_array_view<U>(&'view self) -> ArrayView<'view, U, Dim<IxDynImpl>>
+    where
+        T: Compatible<U>,
+    {
+        let (shape, ptr, inverted) = strided_parts(self.dimensions().as_slice(), self.strides());
+        // Safety: the shape and strides describe the data of the view, which is borrowed, and
+        // `T` is compatible with `U`.
+        let mut view =
+            unsafe { ArrayView::from_shape_ptr(shape, self.as_ptr().offset(ptr).cast::<U>()) };
+        for axis in inverted {
+            view.invert_axis(Axis(axis));
+        }
+
+        view
+    }
+}
+
+impl<'borrow: 'view, 'view, 'array, 'data, T> NdArrayViewMut<'view, T>
+    for StridedArrayAccessor<'borrow, 'array, 'data, T, Mutable<'borrow, T>>
+{
+    fn array_view_mut(&'view mut self) -> ArrayViewMut<'view, T, Dim<IxDynImpl>> {
+        let (shape, ptr, inverted) = strided_parts(self.dimensions().as_slice(), self.strides());
+        // Safety: the shape and strides describe the data of the view, which is mutably
+        // borrowed.
+        let mut view =
+            unsafe { ArrayViewMut::from_shape_ptr(shape, self.as_mut_ptr().offset(ptr)) };
+        for axis in inverted {
+            view.invert_axis(Axis(axis));
+        }
+
+        view
+    }
+
+    fn compatible_array_view_mut<U>(&'view mut self) -> ArrayViewMut<'view, U, Dim<IxDynImpl>>
+    where
+        T: Compatible<U>,
+    {
+        let (shape, ptr, inverted) = strided_parts(self.dimensions().as_slice(), self.strides());
+        // Safety: the shape and strides describe the data of the view, which is mutably
+        // borrowed, and `T` is compatible with `U`.
+        let mut view = unsafe {
+            ArrayViewMut::from_shape_ptr(shape, self.as_mut_ptr().offset(ptr).cast::<U>())
+        };
+        for axis in inverted {
+            view.invert_axis(Axis(axis));
+        }
+
+        view
+    }
+}
+
+// ndarray only accepts non-negative strides. Returns the shape with the absolute strides, the
+// offset of the element with the lowest address relative to the first element of the view, and
+// the axes that must be inverted to restore the original order.
+fn strided_parts(
+    dims: &[usize],
+    strides: &[isize],
+) -> (StrideShape<Dim<IxDynImpl>>, isize, Vec<usize>) {
+    let mut offset = 0;
+    let mut inverted = Vec::new();
+    let mut abs_strides = Vec::with_capacity(strides.len());
+
+    for (axis, (&n, &stride)) in dims.iter().zip(strides).enumerate() {
+        if stride < 0 && n > 0 {
+            offset += (n as isize - 1) * stride;
+            inverted.push(axis);
+        }
+
+        abs_strides.push(stride.unsigned_abs());
+    }
+
+    let shape = dims.into_dimension().strides(abs_strides.into_dimension());
+    (shape, offset, inverted)
+}
+
 mod private {
+    use ndarray::{Array, Dimension};
+
     use crate::data::managed::array::data::{
-        accessor::{BitsArrayAccessor, InlinePtrArrayAccessor, Mutability},
+        accessor::{BitsArrayAccessor, InlinePtrArrayAccessor, Mutability, StridedArrayAccessor},
         copied::CopiedArray,
     };
 
@@ -138,5 +323,14 @@ mod private {
     {
     }
 
+    impl<'borrow, 'array, 'data, T, M> NdArrayPriv
+        for StridedArrayAccessor<'borrow, 'array, 'data, T, M>
+    where
+        M: Mutability,
+    {
+    }
+
     impl<T> NdArrayPriv for CopiedArray<T> {}
+
+    impl<T, D: Dimension> NdArrayPriv for Array<T, D> {}
 }
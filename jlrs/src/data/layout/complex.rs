@@ -0,0 +1,158 @@
+//! Layout type for `Complex`.
+//!
+//! `Complex{T}` is an immutable type with two fields of type `T`, `re` and `im`. The layout
+//! [`Complex<T>`] can be used for `Complex{T}` if `T` is a valid layout for the Julia type `T`,
+//! e.g. `Complex<f64>` for `ComplexF64`.
+
+use std::{fmt::Debug, ptr::NonNull};
+
+use jl_sys::jl_apply_type;
+
+use crate::{
+    convert::{ccall_types::CCallArg, into_julia::IntoJulia, unbox::Unbox},
+    data::{
+        layout::valid_layout::{ValidField, ValidLayout},
+        managed::{
+            datatype::{DataType, DataTypeData},
+            private::ManagedPriv as _,
+            union_all::UnionAll,
+            value::{Value, ValueData},
+            Managed as _,
+        },
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    inline_static_global,
+    memory::target::{ExtendedTarget, Target},
+    private::Private,
+};
+
+/// Layout of `Complex{T}`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    /// Create a new complex number.
+    pub const fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+}
+
+// Returns `true` if `ty` is `Complex{U}` and `T` is a valid layout for `U`.
+fn valid_complex<T: ValidField>(ty: Value) -> bool {
+    if let Ok(dt) = ty.cast::<DataType>() {
+        unsafe {
+            let unrooted = ty.unrooted_target();
+            let ua = inline_static_global!(COMPLEX_TYPE, UnionAll, "Base.Complex", unrooted);
+            if dt.type_name() != ua.base_type().type_name() {
+                return false;
+            }
+
+            let params = dt.parameters().data().as_slice();
+            return T::valid_field(params[0].unwrap().as_value());
+        }
+    }
+
+    false
+}
+
+unsafe impl<T> IntoJulia for Complex<T>
+where
+    T: IntoJulia + Debug + Clone,
+{
+    fn julia_type<'scope, Tgt>(target: Tgt) -> DataTypeData<'scope, Tgt>
+    where
+        Tgt: Target<'scope>,
+    {
+        unsafe {
+            let ua = inline_static_global!(COMPLEX_TYPE, UnionAll, "Base.Complex", target);
+            let ty = T::julia_type(&target)
+                .as_managed()
+                .as_value()
+                .unwrap(Private);
+            let mut params = [ty];
+            let complex_ty = jl_apply_type(ua.as_value().unwrap(Private), params.as_mut_ptr(), 1);
+            target.data_from_ptr(NonNull::new_unchecked(complex_ty.cast()), Private)
+        }
+    }
+}
+
+unsafe impl<T> ValidLayout for Complex<T>
+where
+    T: ValidField + Clone + Debug,
+{
+    fn valid_layout(ty: Value) -> bool {
+        valid_complex::<T>(ty)
+    }
+
+    const IS_REF: bool = false;
+}
+
+unsafe impl<T> ValidField for Complex<T>
+where
+    T: ValidField + Clone + Debug,
+{
+    fn valid_field(ty: Value) -> bool {
+        valid_complex::<T>(ty)
+    }
+}
+
+unsafe impl<T> Unbox for Complex<T>
+where
+    T: ValidField + Clone + Debug,
+{
+    type Output = Self;
+}
+
+unsafe impl<T> Typecheck for Complex<T>
+where
+    T: ValidField + Clone + Debug,
+{
+    fn typecheck(t: DataType) -> bool {
+        <Self as ValidLayout>::valid_layout(t.as_value())
+    }
+}
+
+unsafe impl<T> ConstructType for Complex<T>
+where
+    T: ConstructType,
+{
+    fn construct_type<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+    ) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        let (target, frame) = target.split();
+
+        frame
+            .scope(|mut frame| {
+                let ty = T::construct_type(frame.as_extended_target());
+
+                unsafe {
+                    let ua = inline_static_global!(COMPLEX_TYPE, UnionAll, "Base.Complex", frame);
+                    Ok(ua.as_value().apply_type_unchecked(target, [ty]))
+                }
+            })
+            .unwrap()
+    }
+
+    fn base_type<'target, Tgt>(target: &Tgt) -> Option<Value<'target, 'static>>
+    where
+        Tgt: Target<'target>,
+    {
+        let ua = inline_static_global!(COMPLEX_TYPE, UnionAll, "Base.Complex", target);
+        Some(ua.as_value())
+    }
+}
+
+unsafe impl<T> CCallArg for Complex<T>
+where
+    T: ValidField + ConstructType + Clone + Debug,
+{
+    type CCallArgType = Self;
+    type FunctionArgType = Self;
+}
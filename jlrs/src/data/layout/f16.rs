@@ -2,7 +2,7 @@
 //!
 //! This module is only available if the `f16` feature has been enabled.
 
-use half::f16;
+pub use half::f16;
 use jl_sys::jl_float16_type;
 
 use crate::{
@@ -0,0 +1,213 @@
+//! Layouts for enums defined with Julia's `@enum` macro.
+//!
+//! An enum defined with `@enum Color::Int32 Red=0 Green=1 Blue=2` is a primitive type whose
+//! instances are stored as 32-bits integers. A C-like Rust enum with the same representation,
+//! `#[repr(i32)]`, has a compatible layout. Such enums can implement the [`Enum`] trait, which
+//! should be derived:
+//!
+//! ```ignore
+//! #[repr(i32)]
+//! #[derive(Copy, Clone, Debug, PartialEq, Enum)]
+//! enum Color {
+//!     Red = 0,
+//!     Green = 1,
+//!     Blue = 2,
+//! }
+//! ```
+//!
+//! Deriving `Enum` also implements `IntoJulia`, `Unbox`, `ValidLayout`, `ValidField`,
+//! `Typecheck`, `ConstructType`, `CCallArg` and `CCallReturn`, so the enum can be used as an
+//! argument or return type of functions exported with the `julia_module` macro and as a field
+//! type. The Julia type can be created from Rust with `Enum::create_type`, or by exporting it
+//! with `julia_module`:
+//!
+//! ```ignore
+//! julia_module! {
+//!     become module_jl_init;
+//!
+//!     enum Color;
+//! }
+//! ```
+//!
+//! The Julia type is registered when it's created, its name and module are only determined by
+//! the call to `Enum::create_type` or the `julia_module` macro. An enum that has been defined in
+//! Julia must be registered with `Enum::register_type` before it can be used. Until the type has
+//! been registered, the enum isn't a valid layout for any type and `IntoJulia::julia_type` and
+//! `ConstructType::construct_type` panic.
+
+use std::{any::TypeId, sync::RwLock};
+
+use crate::{
+    call::Call,
+    convert::{into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia},
+    data::{
+        managed::{
+            array::Array,
+            datatype::{DataType, DataTypeData},
+            module::Module,
+            private::ManagedPriv,
+            symbol::Symbol,
+            value::Value,
+            Managed,
+        },
+        types::construct_type::ConstructType,
+    },
+    error::{JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
+    memory::target::{ExtendedTarget, Target},
+    private::Private,
+};
+
+static ENUM_TYPE_REGISTRY: EnumTypes = EnumTypes::new();
+
+struct EnumTypes {
+    data: RwLock<Vec<(TypeId, DataType<'static>)>>,
+}
+
+impl EnumTypes {
+    const fn new() -> Self {
+        EnumTypes {
+            data: RwLock::new(Vec::new()),
+        }
+    }
+
+    fn find<T: 'static>(&self) -> Option<DataType> {
+        let tid = TypeId::of::<T>();
+        self.data
+            .read()
+            .expect("Lock poisoned")
+            .iter()
+            .find_map(|s| match s {
+                &(type_id, ty) if type_id == tid => Some(ty),
+                _ => None,
+            })
+    }
+
+    // Registers `ty` as the Julia type of `T`, replaces the previously registered type if the
+    // type has been created again.
+    unsafe fn insert<T: 'static>(&self, ty: DataType) {
+        let tid = TypeId::of::<T>();
+        let ty = DataType::wrap_non_null(ty.unwrap_non_null(Private), Private);
+        let mut data = self.data.write().expect("Enum type lock was poisoned");
+
+        match data.iter_mut().find(|(type_id, _)| *type_id == tid) {
+            Some(entry) => entry.1 = ty,
+            None => data.push((tid, ty)),
+        }
+    }
+}
+
+unsafe impl Sync for EnumTypes {}
+unsafe impl Send for EnumTypes {}
+
+// Defines an enum by expanding `@enum name::T names[1]=values[1] ...` in `mod`.
+const CREATE_ENUM: &str = "(mod, name, T, names, values) -> Core.eval(mod, Expr(
+    :macrocall,
+    GlobalRef(Base, Symbol(\"@enum\")),
+    LineNumberNode(0),
+    Expr(:(::), name, T),
+    [Expr(:(=), n, v) for (n, v) in zip(names, values)]...
+))";
+
+/// Trait implemented by C-like Rust enums that are compatible with an enum defined with Julia's
+/// `@enum` macro.
+///
+/// Safety:
+///
+/// The enum must have a `repr` attribute with an integer type, which must be `Self::Super`.
+/// `VARIANTS` must contain all variants of the enum.
+pub unsafe trait Enum: Sized + Copy + 'static {
+    /// The integer type used to represent this enum.
+    type Super: IntoJulia + ConstructType + Copy;
+
+    /// The names and values of all variants.
+    const VARIANTS: &'static [(&'static str, Self)];
+
+    /// Convert this variant to its integer representation.
+    fn as_super(self) -> Self::Super;
+
+    /// Returns the registered Julia type of this enum, or `None` if the type hasn't been created
+    /// or registered yet.
+    fn registered_type<'target, Tgt>(_: &Tgt) -> Option<DataType<'target>>
+    where
+        Tgt: Target<'target>,
+    {
+        ENUM_TYPE_REGISTRY.find::<Self>()
+    }
+
+    /// Register `ty` as the Julia type of this enum. This is only necessary if the enum has been
+    /// defined in Julia, types created with `Enum::create_type` are registered automatically.
+    ///
+    /// Returns an error if `ty` isn't a primitive type with the same size as `Self`.
+    ///
+    /// Safety: `ty` must be an enum whose instances have the same names and values as the
+    /// variants of `Self`, and it must never be redefined.
+    unsafe fn register_type(ty: DataType) -> JlrsResult<()> {
+        if !ty.is_primitive_type() || ty.size() != Some(std::mem::size_of::<Self>() as u32) {
+            let base_type = ty.display_string_or(CANNOT_DISPLAY_TYPE);
+            Err(TypeError::IncompatibleBaseType { base_type })?;
+        }
+
+        ENUM_TYPE_REGISTRY.insert::<Self>(ty);
+        Ok(())
+    }
+
+    /// Create the Julia enum `name` in `module` by expanding the `@enum` macro, the name and
+    /// value of each of its instances is taken from `VARIANTS`. The new type is registered as
+    /// the Julia type of this enum.
+    ///
+    /// Safety: the type must not exist yet. This method is called automatically by init
+    /// functions generated with the `julia_module` macro.
+    unsafe fn create_type<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+        name: Symbol,
+        module: Module,
+    ) -> JlrsResult<DataTypeData<'target, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        let (target, frame) = target.split();
+
+        frame.scope(|mut frame| {
+            let n_variants = Self::VARIANTS.len();
+            let symbol_ty = DataType::symbol_type(&frame).as_value();
+            let any_ty = DataType::any_type(&frame).as_value();
+            let mut names = Array::new_for(frame.as_extended_target(), n_variants, symbol_ty)
+                .into_jlrs_result()?;
+            let mut values = Array::new_for(frame.as_extended_target(), n_variants, any_ty)
+                .into_jlrs_result()?;
+
+            {
+                let mut names = names.value_data_mut()?;
+                let mut values_ref = values.value_data_mut()?;
+
+                for (i, (name, variant)) in Self::VARIANTS.iter().copied().enumerate() {
+                    let name = Symbol::new(&frame, name);
+                    names.set(i, Some(name.as_value()))?;
+
+                    let value = Value::new(&mut frame, variant.as_super());
+                    values_ref.set(i, Some(value))?;
+                }
+            }
+
+            let super_ty =
+                <Self::Super as ConstructType>::construct_type(frame.as_extended_target());
+            Value::eval_string(&mut frame, CREATE_ENUM)
+                .into_jlrs_result()?
+                .call(
+                    &mut frame,
+                    [
+                        module.as_value(),
+                        name.as_value(),
+                        super_ty,
+                        names.as_value(),
+                        values.as_value(),
+                    ],
+                )
+                .into_jlrs_result()?;
+
+            let ty = module.global(&frame, name)?.as_value().cast::<DataType>()?;
+            ENUM_TYPE_REGISTRY.insert::<Self>(ty);
+            Ok(ty.root(target))
+        })
+    }
+}
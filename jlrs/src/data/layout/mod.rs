@@ -59,9 +59,13 @@ macro_rules! impl_construct_julia_type {
 
 pub mod bool;
 pub mod char;
+pub mod complex;
 #[cfg(feature = "f16")]
 pub mod f16;
+pub mod julia_enum;
 pub mod nothing;
+#[cfg(feature = "layout-registry")]
+pub mod registry;
 #[cfg(feature = "internal-types")]
 pub mod ssa_value;
 pub mod tuple;
@@ -0,0 +1,111 @@
+//! Registry of derived layouts.
+//!
+//! When the `layout-registry` feature is enabled, every layout that derives `ValidLayout` and
+//! whose Julia type is set with `#[jlrs(julia_type = "...")]` is registered automatically. Layouts
+//! with type parameters are not registered. All registered layouts can be checked against their
+//! Julia types with [`verify_all_layouts`], for example at startup or in a test. The modules and
+//! packages that define these types must have been loaded before this function is called.
+
+#[doc(hidden)]
+pub use inventory;
+use thiserror::Error;
+
+use super::valid_layout::LayoutMismatch;
+use crate::{
+    data::managed::{
+        datatype::DataType, module::Module, union_all::UnionAll, value::Value, Managed,
+    },
+    error::{JlrsError, JlrsResult},
+    memory::target::{unrooted::Unrooted, Target},
+};
+
+/// A layout that has been registered by the `ValidLayout` derive macro.
+pub struct RegisteredLayout {
+    /// The path of the Rust type.
+    pub rust_type: &'static str,
+    /// The path of the Julia type, e.g. `Main.MyModule.MyType`.
+    pub julia_type: &'static str,
+    /// Checks the layout of the Rust type against a Julia type.
+    pub layout_mismatch: fn(Value) -> Option<LayoutMismatch>,
+}
+
+inventory::collect!(RegisteredLayout);
+
+/// Error returned by [`verify_all_layouts`] for a layout that can't be verified or is invalid.
+#[derive(Debug, Error)]
+pub enum LayoutVerificationError {
+    #[error("cannot find {julia_type}, the Julia type of {rust_type}")]
+    TypeNotFound {
+        rust_type: &'static str,
+        julia_type: &'static str,
+    },
+    #[error(transparent)]
+    Mismatch(#[from] LayoutMismatch),
+}
+
+/// Returns an iterator over all registered layouts.
+pub fn registered_layouts() -> impl Iterator<Item = &'static RegisteredLayout> {
+    inventory::iter::<RegisteredLayout>.into_iter()
+}
+
+/// Checks all registered layouts against their Julia types.
+///
+/// If the Julia type of a layout is a `UnionAll`, the layout is checked against the type at the
+/// bottom of that `UnionAll`. Returns an error for each layout whose Julia type can't be found
+/// or whose layout is incompatible with that type.
+pub fn verify_all_layouts<'target, T>(target: &T) -> Result<(), Vec<LayoutVerificationError>>
+where
+    T: Target<'target>,
+{
+    let unrooted = target.unrooted();
+    let errors = registered_layouts()
+        .filter_map(|layout| match find_type(unrooted, layout.julia_type) {
+            Ok(ty) => (layout.layout_mismatch)(ty.as_value()).map(LayoutVerificationError::from),
+            Err(_) => Some(LayoutVerificationError::TypeNotFound {
+                rust_type: layout.rust_type,
+                julia_type: layout.julia_type,
+            }),
+        })
+        .collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// Finds the type at `path`, the first component must be `Main`, `Base`, `Core` or the name of a
+// package that has been loaded.
+fn find_type<'target>(target: Unrooted<'target>, path: &str) -> JlrsResult<DataType<'target>> {
+    let mut parts = path.split('.');
+    let mut module = match parts.next() {
+        Some("Main") | None => Module::main(&target),
+        Some("Base") => Module::base(&target),
+        Some("Core") => Module::core(&target),
+        Some(pkg) => match Module::package_root_module(&target, pkg) {
+            Some(module) => module,
+            None => Err(JlrsError::exception(format!(
+                "package {} has not been loaded",
+                pkg
+            )))?,
+        },
+    };
+
+    let mut parts = parts.collect::<Vec<_>>();
+    let name = parts.pop().unwrap_or_default();
+
+    // Safety: modules and global types are rooted by their parent modules.
+    unsafe {
+        for part in parts {
+            module = module.submodule(target, part)?.as_managed();
+        }
+
+        let ty = module.global(target, name)?.as_managed();
+        if let Ok(ua) = ty.cast::<UnionAll>() {
+            return Ok(ua.base_type());
+        }
+
+        ty.cast::<DataType>()
+    }
+}
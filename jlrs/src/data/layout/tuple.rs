@@ -26,18 +26,26 @@
 //! ```
 //!
 //! Additionally, [`Tuple` ] can be used to create a tuple from an arbitrary number of `Value`s.
+//!
+//! A tuple whose elements all have the same type, an `NTuple{N, T}`, has the same layout as the
+//! Rust array `[T; N]`. Arrays can be used as a layout and field type for such tuples for any
+//! `N`.
+
+use std::{fmt::Debug, ptr::NonNull};
 
-use jl_sys::jl_tuple_typename;
+use jl_sys::{jl_apply_tuple_type_v, jl_tuple_typename};
 
 use crate::{
+    convert::{into_julia::IntoJulia, unbox::Unbox},
     data::{
+        layout::valid_layout::{ValidField, ValidLayout},
         managed::{
-            datatype::DataType,
+            datatype::{DataType, DataTypeData},
             private::ManagedPriv as _,
             value::{Value, ValueData, ValueResult, MAX_SIZE},
             Managed as _,
         },
-        types::typecheck::Typecheck,
+        types::{construct_type::ConstructType, typecheck::Typecheck},
     },
     memory::target::{ExtendedTarget, Target},
     private::Private,
@@ -459,3 +467,118 @@ impl_tuple!(
     Tuple32, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19,
     T20, T21, T22, T23, T24, T25, T26, T27, T28, T29, T30, T31, T32
 );
+
+// Returns `true` if `ty` is a tuple type with `N` fields and `T` is a valid layout for each of
+// them.
+fn valid_ntuple<T: ValidField, const N: usize>(ty: Value) -> bool {
+    if let Ok(dt) = ty.cast::<DataType>() {
+        if !Tuple::typecheck(dt) {
+            return false;
+        }
+
+        unsafe {
+            let global = ty.unrooted_target();
+            let field_types = dt.field_types(global).as_managed();
+            if field_types.len() != N {
+                return false;
+            }
+
+            return field_types
+                .data()
+                .as_slice()
+                .iter()
+                .all(|ty| T::valid_field(ty.unwrap().as_managed()));
+        }
+    }
+
+    false
+}
+
+unsafe impl<T, const N: usize> IntoJulia for [T; N]
+where
+    T: IntoJulia + Debug + Clone,
+{
+    fn julia_type<'scope, Tgt>(target: Tgt) -> DataTypeData<'scope, Tgt>
+    where
+        Tgt: Target<'scope>,
+    {
+        unsafe {
+            let ty = T::julia_type(&target)
+                .as_managed()
+                .as_value()
+                .unwrap(Private);
+            let mut types = vec![ty; N];
+            let tuple_ty = jl_apply_tuple_type_v(types.as_mut_ptr(), N);
+            target.data_from_ptr(NonNull::new_unchecked(tuple_ty.cast()), Private)
+        }
+    }
+}
+
+unsafe impl<T, const N: usize> ValidLayout for [T; N]
+where
+    T: ValidField + Clone + Debug,
+{
+    fn valid_layout(ty: Value) -> bool {
+        valid_ntuple::<T, N>(ty)
+    }
+
+    const IS_REF: bool = false;
+}
+
+unsafe impl<T, const N: usize> ValidField for [T; N]
+where
+    T: ValidField + Clone + Debug,
+{
+    fn valid_field(ty: Value) -> bool {
+        valid_ntuple::<T, N>(ty)
+    }
+}
+
+unsafe impl<T, const N: usize> Unbox for [T; N]
+where
+    T: ValidField + Clone + Debug,
+{
+    type Output = Self;
+}
+
+unsafe impl<T, const N: usize> Typecheck for [T; N]
+where
+    T: ValidField + Clone + Debug,
+{
+    fn typecheck(t: DataType) -> bool {
+        <Self as ValidLayout>::valid_layout(t.as_value())
+    }
+}
+
+unsafe impl<T, const N: usize> ConstructType for [T; N]
+where
+    T: ConstructType,
+{
+    fn construct_type<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+    ) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        let (target, frame) = target.split();
+
+        frame
+            .scope(|mut frame| {
+                let ty = T::construct_type(frame.as_extended_target());
+
+                unsafe {
+                    let mut types = vec![ty.unwrap(Private); N];
+                    let tuple_ty = jl_apply_tuple_type_v(types.as_mut_ptr(), N);
+                    Ok(target.data_from_ptr(NonNull::new_unchecked(tuple_ty.cast()), Private))
+                }
+            })
+            .unwrap()
+    }
+
+    fn base_type<'target, Tgt>(target: &Tgt) -> Option<Value<'target, 'static>>
+    where
+        Tgt: Target<'target>,
+    {
+        Some(DataType::tuple_type(target).as_value())
+    }
+}
@@ -15,6 +15,10 @@
 //! field, which contains the data of the bits-union, is aligned correctly. [`BitsUnionContainer`]
 //! and [`Flag`] are marker traits that are used by jlrs-derive to implement `ValidLayout`
 //! correctly.
+//!
+//! The active variant of a bits-union can be converted to a Rust enum that implements
+//! [`UnionEnum`]. This trait can be derived for enums whose variants each correspond to one of
+//! the types of the union.
 
 use std::{
     ffi::c_void,
@@ -28,10 +32,16 @@ use super::valid_layout::{ValidField, ValidLayout};
 use crate::{
     data::{
         managed::{
-            datatype::DataType, private::ManagedPriv as _, union::Union, value::Value, Managed,
+            datatype::DataType,
+            private::ManagedPriv as _,
+            union::{find_union_component, nth_union_component, Union},
+            value::Value,
+            Managed,
         },
         types::typecheck::Typecheck,
     },
+    error::{AccessError, JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
+    memory::target::Target,
     private::Private,
 };
 
@@ -148,6 +158,235 @@ impl<const N: usize> Debug for BitsUnion<N> {
 
 unsafe impl<const N: usize> BitsUnionContainer for BitsUnion<N> {}
 
+impl<const N: usize> BitsUnion<N> {
+    /// Convert the active variant of this bits-union to `E`.
+    ///
+    /// `union` must be the type of the field this bits-union has been read from, `flag` the
+    /// value of the flag field that follows it.
+    pub fn as_enum<E: UnionEnum>(&self, union: Union, flag: u8) -> JlrsResult<E> {
+        check_union_enum::<E, N>(union)?;
+
+        let mut tag = flag as i32;
+
+        // Safety: the union is compatible with E and fits in this container, the active variant
+        // has type ty.
+        unsafe {
+            if let Some(ty) = nth_union_component(union.as_value(), &mut tag) {
+                let ty = ty.cast_unchecked::<DataType>();
+                if let Some(value) = E::read_variant(ty, self.0.as_ptr().cast()) {
+                    return Ok(value);
+                }
+
+                Err(AccessError::InvalidLayout {
+                    value_type: ty.display_string_or(CANNOT_DISPLAY_TYPE),
+                })?
+            }
+        }
+
+        Err(AccessError::IllegalUnionTag {
+            union_type: union.display_string_or(CANNOT_DISPLAY_TYPE),
+            tag: flag as usize,
+        })?
+    }
+
+    /// Store `value` in this bits-union and update `flag` accordingly.
+    ///
+    /// `union` must be the type of the field this bits-union belongs to, `flag` the flag field
+    /// that follows it.
+    pub fn set_enum<E: UnionEnum>(
+        &mut self,
+        union: Union,
+        flag: &mut u8,
+        value: E,
+    ) -> JlrsResult<()> {
+        check_union_enum::<E, N>(union)?;
+
+        let ty = value.variant_type(&union.unrooted_target());
+        let mut tag = 0;
+        if !find_union_component(union.as_value(), ty.as_value(), &mut tag) {
+            Err(TypeError::IncompatibleType {
+                element_type: union.display_string_or(CANNOT_DISPLAY_TYPE),
+                value_type: ty.display_string_or(CANNOT_DISPLAY_TYPE),
+            })?;
+        }
+
+        // Safety: the union is compatible with E and fits in this container.
+        unsafe { value.write_variant(self.0.as_mut_ptr().cast()) };
+        *flag = tag as u8;
+        Ok(())
+    }
+}
+
+// Checks that union is a bits-union of the variants of E whose data fits in N bytes.
+fn check_union_enum<E: UnionEnum, const N: usize>(union: Union) -> JlrsResult<()> {
+    let mut size = 0;
+    if !union.isbits_size_align(&mut size, &mut 0) || size != N || !E::is_compatible(union) {
+        Err(AccessError::InvalidLayout {
+            value_type: union.display_string_or(CANNOT_DISPLAY_TYPE),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Trait implemented by Rust enums that represent a union of bits types.
+///
+/// Every variant of the enum corresponds to one of the types of the union. A tuple variant with
+/// a single field of type `T` corresponds to the Julia type of `T`, a unit variant corresponds
+/// to `Nothing`. This trait should be derived:
+///
+/// ```ignore
+/// #[derive(Copy, Clone, Debug, PartialEq, UnionEnum)]
+/// enum IntOrFloat {
+///     Int(i64),
+///     Float(f64),
+///     Nothing,
+/// }
+/// ```
+///
+/// This enum corresponds to `Union{Int64, Float64, Nothing}`. It can be used to access the
+/// elements of an array with that element type with [`UnionArrayAccessor::get_enum`] and
+/// [`UnionArrayAccessor::set_enum`]. A field of that type can be accessed with
+/// [`FieldAccessor::access_enum`], or with [`BitsUnion::as_enum`] if the field is part of a
+/// layout generated by JlrsReflect.jl.
+///
+/// The enum can also be used as the type of a field of a struct that derives `Unbox` and
+/// `Typecheck` if that field is annotated with `#[jlrs(union_enum)]`:
+///
+/// ```ignore
+/// #[repr(C)]
+/// #[derive(Clone, Debug, Unbox, Typecheck)]
+/// struct WithUnion {
+///     a: i8,
+///     #[jlrs(union_enum)]
+///     b: IntOrFloat,
+/// }
+/// ```
+///
+/// Julia stores a bits-union field inline, followed by a flag whose meaning depends on the order
+/// of the types in the union, so the layout of this struct doesn't match the layout of the
+/// Julia type. Deriving `Unbox` generates a layout that stores the field as a [`BitsUnion`] and
+/// its flag, and converts the field to the enum when the data is unboxed. `Typecheck` checks
+/// this generated layout and that the union is compatible with the enum. Such a struct can't
+/// derive `ValidLayout`, `ValidField` or `IntoJulia`.
+///
+/// The variants of the enum must have different types, a unit variant has the same type as a
+/// variant that contains `Nothing`. The derive macro only rejects variants whose types are
+/// written identically. If two variants correspond to the same Julia type, e.g. `i64` and `isize`
+/// on a 64-bit system, that type isn't considered to be a variant type and a union that contains
+/// it is incompatible with the enum.
+///
+/// Safety:
+///
+/// `is_variant_type` must only return `true` if `ty` is the type of the data of exactly one of
+/// the variants, which must be an isbits type. `variant_type` must return the type of the data of
+/// the variant, `read_variant` and `write_variant` must read and write data of that type.
+///
+/// [`UnionArrayAccessor::get_enum`]: crate::data::managed::array::data::accessor::UnionArrayAccessor::get_enum
+/// [`UnionArrayAccessor::set_enum`]: crate::data::managed::array::data::accessor::UnionArrayAccessor::set_enum
+/// [`FieldAccessor::access_enum`]: crate::data::managed::value::field_accessor::FieldAccessor::access_enum
+pub unsafe trait UnionEnum: Sized + Copy + 'static {
+    /// The size of the largest variant in bytes.
+    const SIZE: usize;
+
+    /// The alignment of a bits-union of the variants, one of the `AlignN` types.
+    type Alignment: Align;
+
+    /// Returns `true` if `ty` is the type of the data of exactly one of the variants.
+    fn is_variant_type(ty: Value) -> bool;
+
+    /// Returns the Julia type of the data of this variant.
+    fn variant_type<'target, Tgt>(&self, target: &Tgt) -> DataType<'target>
+    where
+        Tgt: Target<'target>;
+
+    /// Read the variant whose data has type `ty` from `ptr`. Returns `None` if `ty` isn't the
+    /// type of the data of one of the variants.
+    ///
+    /// Safety: if `ty` is the type of one of the variants, `ptr` must point to an instance of
+    /// that type.
+    unsafe fn read_variant(ty: DataType, ptr: *const u8) -> Option<Self>;
+
+    /// Write the data of this variant to `ptr`.
+    ///
+    /// Safety: `ptr` must be valid for writes of an instance of the type of this variant.
+    unsafe fn write_variant(self, ptr: *mut u8);
+
+    /// Returns `true` if every type in `union` is the type of the data of one of the variants.
+    fn is_compatible(union: Union) -> bool {
+        union
+            .variants()
+            .into_iter()
+            .all(|ty| Self::is_variant_type(ty))
+    }
+}
+
+// Used by the UnionEnum derive macro to select the alignment of a bits-union.
+#[doc(hidden)]
+pub struct AlignTo<const N: usize>;
+
+#[doc(hidden)]
+pub trait SelectAlign {
+    type Align: Align;
+}
+
+impl SelectAlign for AlignTo<1> {
+    type Align = Align1;
+}
+
+impl SelectAlign for AlignTo<2> {
+    type Align = Align2;
+}
+
+impl SelectAlign for AlignTo<4> {
+    type Align = Align4;
+}
+
+impl SelectAlign for AlignTo<8> {
+    type Align = Align8;
+}
+
+impl SelectAlign for AlignTo<16> {
+    type Align = Align16;
+}
+
+// Used by the Typecheck derive macro to check if the field of ty at position index is a
+// bits-union that is compatible with E.
+#[doc(hidden)]
+pub fn union_enum_field_compatible<E: UnionEnum>(ty: DataType, index: usize) -> bool {
+    // Safety: the field type is only checked.
+    unsafe {
+        match ty.field_type(ty.unrooted_target(), index) {
+            Some(field_type) => match field_type.as_managed().cast::<Union>() {
+                Ok(union) => E::is_compatible(union),
+                Err(_) => false,
+            },
+            None => false,
+        }
+    }
+}
+
+// Used by the Unbox derive macro to convert a bits-union field to E.
+//
+// Safety: the field of ty at position index must be a bits-union that is compatible with E,
+// data and flag must have been read from that field.
+#[doc(hidden)]
+pub unsafe fn union_enum_field<E: UnionEnum, const N: usize>(
+    ty: DataType,
+    index: usize,
+    data: &BitsUnion<N>,
+    flag: u8,
+) -> E {
+    let union = ty
+        .field_type(ty.unrooted_target(), index)
+        .expect("field index out of bounds")
+        .as_managed()
+        .cast_unchecked::<Union>();
+
+    data.as_enum(union, flag)
+        .expect("bits-union is incompatible with the enum")
+}
+
 #[doc(hidden)]
 pub unsafe fn correct_layout_for<A: Align, B: BitsUnionContainer, F: Flag>(u: Union) -> bool {
     let mut bu_sz = 0;
@@ -8,11 +8,14 @@
 //!
 //! [`DataType`]: crate::data::managed::datatype::DataType
 
-use std::ffi::c_void;
+use std::{any::type_name, ffi::c_void};
+
+use thiserror::Error;
 
 use crate::{
     convert::into_julia::IntoJulia,
-    data::managed::{datatype::DataType, value::Value},
+    data::managed::{datatype::DataType, value::Value, Managed},
+    error::CANNOT_DISPLAY_TYPE,
 };
 
 /// Trait used to check if a Rust type and Julia type have matching layouts.
@@ -28,6 +31,45 @@ pub unsafe trait ValidLayout {
     /// argument is a `Value` to account for the fact that a field type can be a `Union`,
     /// `UnionAll` or `Union{}`.
     fn valid_layout(ty: Value) -> bool;
+
+    /// Returns why the layout of the implementor is incompatible with the layout of `ty`, or
+    /// `None` if they're compatible.
+    ///
+    /// Derived implementations report the first field whose layout is incompatible.
+    fn layout_mismatch(ty: Value) -> Option<LayoutMismatch> {
+        if Self::valid_layout(ty) {
+            None
+        } else {
+            Some(LayoutMismatch::new::<Self>(
+                ty,
+                MismatchReason::Incompatible,
+            ))
+        }
+    }
+}
+
+// Registers a derived layout if the `layout-registry` feature is enabled, the derive macros
+// can't check which features of jlrs are enabled.
+#[cfg(feature = "layout-registry")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_layout {
+    ($ty:ty, $name:ident, $julia_type:literal) => {
+        $crate::data::layout::registry::inventory::submit! {
+            $crate::data::layout::registry::RegisteredLayout {
+                rust_type: concat!(module_path!(), "::", stringify!($name)),
+                julia_type: $julia_type,
+                layout_mismatch: <$ty as $crate::data::layout::valid_layout::ValidLayout>::layout_mismatch,
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "layout-registry"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_layout {
+    ($ty:ty, $name:ident, $julia_type:literal) => {};
 }
 
 #[doc(hidden)]
@@ -123,3 +165,90 @@ pub unsafe trait ValidField {
     /// when it's used as a field type.
     fn valid_field(ty: Value) -> bool;
 }
+
+/// Describes why the layout of a Rust type is incompatible with a Julia type.
+#[derive(Clone, Debug, Error)]
+#[error("layout of {rust_type} is incompatible with {julia_type}: {reason}")]
+pub struct LayoutMismatch {
+    /// The name of the Rust type.
+    pub rust_type: &'static str,
+    /// The Julia type the layout has been checked against.
+    pub julia_type: String,
+    /// Why the layouts are incompatible.
+    pub reason: MismatchReason,
+}
+
+impl LayoutMismatch {
+    /// Create a new `LayoutMismatch` for the Rust type `T` and the Julia type `ty`.
+    pub fn new<T: ?Sized>(ty: Value, reason: MismatchReason) -> Self {
+        LayoutMismatch {
+            rust_type: type_name::<T>(),
+            julia_type: ty.display_string_or(CANNOT_DISPLAY_TYPE),
+            reason,
+        }
+    }
+}
+
+/// The reason the layout of a Rust type is incompatible with a Julia type.
+#[derive(Clone, Debug, Error)]
+pub enum MismatchReason {
+    #[error("the Julia type is not a concrete DataType")]
+    NotADataType,
+    #[error("expected {expected} fields, found {found}")]
+    FieldCount { expected: usize, found: usize },
+    #[error("{0}")]
+    Field(FieldMismatch),
+    #[error("the layouts don't match")]
+    Incompatible,
+}
+
+/// A field whose layout is incompatible with the Rust type of that field.
+#[derive(Clone, Debug, Error)]
+#[error(
+    "field {name} (index {index}) has type {julia_type} with size {size} at offset {offset}, \
+    expected {rust_type}"
+)]
+pub struct FieldMismatch {
+    /// The index of the field.
+    pub index: usize,
+    /// The name of the field.
+    pub name: String,
+    /// The type of the field in Julia.
+    pub julia_type: String,
+    /// The size of the field in bytes.
+    pub size: u32,
+    /// The offset of the field in bytes.
+    pub offset: u32,
+    /// The name of the Rust type of the field.
+    pub rust_type: &'static str,
+}
+
+impl FieldMismatch {
+    /// Create a new `FieldMismatch` for the field of `ty` at position `index`, whose expected
+    /// Rust type is `T`.
+    ///
+    /// Panics if `ty` doesn't have a field at position `index`.
+    pub fn new<T: ?Sized>(ty: DataType, index: usize) -> Self {
+        let name = ty
+            .field_name(index)
+            .and_then(|name| name.as_string().ok())
+            .unwrap_or_else(|| index.to_string());
+
+        // Safety: the field type is only used to create its display string.
+        let julia_type = ty
+            .field_type(ty.unrooted_target(), index)
+            .map(|field_type| {
+                unsafe { field_type.as_managed() }.display_string_or(CANNOT_DISPLAY_TYPE)
+            })
+            .unwrap_or_else(|| CANNOT_DISPLAY_TYPE.into());
+
+        FieldMismatch {
+            index,
+            name,
+            julia_type,
+            size: ty.field_size(index).expect("field index out of bounds"),
+            offset: ty.field_offset(index).expect("field index out of bounds"),
+            rust_type: type_name::<T>(),
+        }
+    }
+}
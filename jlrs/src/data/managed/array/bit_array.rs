@@ -0,0 +1,394 @@
+//! Managed type for `BitArray`.
+//!
+//! Julia's `BitArray{N}` is an `AbstractArray{Bool, N}` that stores its elements as individual
+//! bits. It's not an `Array`, but it's returned by many common operations like broadcasted
+//! comparisons, e.g. `A .> 0`. The elements are stored in a `Vector{UInt64}`, element `i` is
+//! stored in bit `i % 64` of chunk `i / 64`, the unused bits of the last chunk are always 0.
+//!
+//! ```ignore
+//! let arr = Value::eval_string(&mut frame, "[1, -2, 3] .> 0")
+//!     .into_jlrs_result()?
+//!     .cast::<BitArray>()?;
+//!
+//! assert_eq!(arr.len(), 3);
+//! assert_eq!(arr.get(1), Some(false));
+//! assert_eq!(arr.to_vec(), vec![true, false, true]);
+//! ```
+//!
+//! If the `jlrs-bitvec` feature is enabled, a `BitArray` can be converted to and from a `BitVec`
+//! from the bitvec crate.
+
+use std::{marker::PhantomData, ptr::NonNull, slice};
+
+use jl_sys::{jl_array_t, jl_value_t};
+
+use crate::{
+    call::Call,
+    data::{
+        managed::{
+            array::{
+                dimensions::{Dimensions, Dims},
+                Array, TypedArray,
+            },
+            datatype::DataType,
+            private::ManagedPriv,
+            union_all::UnionAll,
+            value::Value,
+            Managed, Ref,
+        },
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    error::{InstantiationError, JlrsResult},
+    inline_static_global,
+    memory::target::{target_type::TargetType, unrooted::Unrooted, ExtendedTarget, Target},
+    private::Private,
+};
+
+// The layout of a `BitArray`, the dimensions are stored after the length.
+#[repr(C)]
+struct BitArrayLayout {
+    chunks: NonNull<jl_array_t>,
+    len: usize,
+}
+
+/// A Julia `BitArray`.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct BitArray<'scope, 'data>(
+    NonNull<jl_value_t>,
+    PhantomData<&'scope ()>,
+    PhantomData<&'data mut ()>,
+);
+
+impl<'scope, 'data> BitArray<'scope, 'data> {
+    /// Create a new `BitArray` with dimensions `dims`, all elements are `false`.
+    ///
+    /// If the array size is too large, Julia will throw an error. This error is caught and
+    /// returned.
+    pub fn new<'target, D, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+        dims: D,
+    ) -> JlrsResult<BitArrayResult<'target, T>>
+    where
+        D: Dims,
+        T: Target<'target>,
+    {
+        let (output, scope) = target.split();
+        scope.scope(|mut frame| {
+            let falses = inline_static_global!(FALSES, "Base.falses", frame);
+
+            let mut args = Vec::with_capacity(dims.rank());
+            for dim in 0..dims.rank() {
+                args.push(Value::new(&mut frame, dims.n_elements(dim) as isize));
+            }
+
+            // Safety: falses is called with Int arguments, the result is a BitArray.
+            unsafe {
+                let res = match falses.call(&mut frame, args) {
+                    Ok(arr) => Ok(arr.unwrap_non_null(Private)),
+                    Err(e) => Err(e.unwrap_non_null(Private)),
+                };
+
+                Ok(output.result_from_ptr(res, Private))
+            }
+        })
+    }
+
+    /// Create a new `BitArray` with dimensions `dims` that contains the elements of `data` in
+    /// column-major order.
+    ///
+    /// Returns `InstantiationError::ArraySizeMismatch` if the size of `dims` doesn't match the
+    /// length of `data`.
+    pub fn from_bools<'target, D, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+        data: &[bool],
+        dims: D,
+    ) -> JlrsResult<BitArrayResult<'target, T>>
+    where
+        D: Dims,
+        T: Target<'target>,
+    {
+        if dims.size() != data.len() {
+            Err(InstantiationError::ArraySizeMismatch {
+                vec_size: data.len(),
+                dim_size: dims.size(),
+            })?;
+        }
+
+        let (output, scope) = target.split();
+        scope.scope(|mut frame| {
+            let unrooted = frame.unrooted();
+            let target = frame.extended_target(unrooted);
+
+            // Safety: the array is not rooted, but it's not used after the chunks have been
+            // written. It has just been allocated, so no references to its data exist.
+            unsafe {
+                let res = match BitArray::new(target, dims)? {
+                    Ok(arr) => {
+                        let arr = arr.as_managed();
+                        for (word, bits) in arr.chunks_mut().iter_mut().zip(data.chunks(64)) {
+                            *word = bits
+                                .iter()
+                                .enumerate()
+                                .fold(0, |word, (i, &bit)| word | ((bit as u64) << i));
+                        }
+                        Ok(arr.unwrap_non_null(Private))
+                    }
+                    Err(e) => Err(e.as_managed().unwrap_non_null(Private)),
+                };
+
+                Ok(output.result_from_ptr(res, Private))
+            }
+        })
+    }
+
+    /// Returns the number of elements.
+    pub fn len(self) -> usize {
+        self.layout().len
+    }
+
+    /// Returns `true` if the array contains no elements.
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the rank of this array.
+    pub fn rank(self) -> usize {
+        // Safety: the only type parameter of a BitArray is its rank.
+        unsafe {
+            let ty = self.as_value().datatype();
+            ty.parameter(ty.unrooted_target(), 0)
+                .unwrap()
+                .as_value()
+                .unbox_unchecked::<isize>() as usize
+        }
+    }
+
+    /// Returns the dimensions of this array.
+    pub fn dimensions(self) -> Dimensions {
+        let rank = self.rank();
+        if rank == 1 {
+            // The dims field of a BitVector is not initialized.
+            return Dimensions::from_dims(&self.len());
+        }
+
+        // Safety: the dimensions are stored inline as an NTuple{N, Int} after the length.
+        unsafe {
+            let dims = (self.0.as_ptr() as *const BitArrayLayout).add(1) as *const usize;
+            Dimensions::from_dims(&slice::from_raw_parts(dims, rank))
+        }
+    }
+
+    /// Returns the `Vector{UInt64}` that stores the elements of this array.
+    pub fn chunks(self) -> TypedArray<'scope, 'static, u64> {
+        // Safety: the chunks are a Vector{UInt64} that is reachable from this array.
+        unsafe { Array::wrap_non_null(self.layout().chunks, Private).as_typed_unchecked() }
+    }
+
+    /// Returns the chunks that store the elements of this array as a slice.
+    ///
+    /// Safety: no mutable references to the chunks can exist while this slice is in use.
+    pub unsafe fn chunks_slice(&self) -> &[u64] {
+        let (ptr, len) = self.chunks_raw();
+        slice::from_raw_parts(ptr, len)
+    }
+
+    /// Returns the chunks that store the elements of this array as a mutable slice.
+    ///
+    /// The unused bits of the last chunk must remain 0.
+    ///
+    /// Safety: no other references to the chunks can exist while this slice is in use.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn chunks_mut(&self) -> &mut [u64] {
+        let (ptr, len) = self.chunks_raw();
+        slice::from_raw_parts_mut(ptr, len)
+    }
+
+    /// Returns the element at `index`, or `None` if the index is out of bounds.
+    pub fn get<D>(self, index: D) -> Option<bool>
+    where
+        D: Dims,
+    {
+        let idx = self.dimensions().index_of(&index).ok()?;
+        // Safety: the index is in bounds.
+        let word = unsafe { self.chunks_slice()[idx / 64] };
+        Some((word >> (idx % 64)) & 1 == 1)
+    }
+
+    /// Set the element at `index` to `value`.
+    ///
+    /// Safety: no other references to the chunks can exist.
+    pub unsafe fn set<D>(self, index: D, value: bool) -> JlrsResult<()>
+    where
+        D: Dims,
+    {
+        let idx = self.dimensions().index_of(&index)?;
+        let word = &mut self.chunks_mut()[idx / 64];
+        let mask = 1u64 << (idx % 64);
+
+        if value {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+
+        Ok(())
+    }
+
+    /// Copy the elements of this array to a `Vec` in column-major order.
+    pub fn to_vec(self) -> Vec<bool> {
+        let len = self.len();
+        // Safety: the chunks are only read.
+        let chunks = unsafe { self.chunks_slice() };
+
+        (0..len)
+            .map(|idx| (chunks[idx / 64] >> (idx % 64)) & 1 == 1)
+            .collect()
+    }
+
+    // Returns a pointer to the first chunk and the number of chunks.
+    fn chunks_raw(&self) -> (*mut u64, usize) {
+        // Safety: the chunks are a Vector{UInt64}.
+        unsafe {
+            let chunks = Array::wrap_non_null(self.layout().chunks, Private);
+            let len = chunks.unwrap_non_null(Private).as_ref().length;
+            (chunks.data_ptr().cast(), len)
+        }
+    }
+
+    fn layout(&self) -> &BitArrayLayout {
+        // Safety: a BitArray starts with its chunks and length.
+        unsafe { self.0.cast().as_ref() }
+    }
+}
+
+#[cfg(feature = "jlrs-bitvec")]
+impl<'scope, 'data> BitArray<'scope, 'data> {
+    /// Create a new `BitArray` with dimensions `dims` that contains the bits of `data` in
+    /// column-major order.
+    ///
+    /// Returns `InstantiationError::ArraySizeMismatch` if the size of `dims` doesn't match the
+    /// length of `data`.
+    pub fn from_bitslice<'target, D, T, S, O>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+        data: &bitvec::slice::BitSlice<S, O>,
+        dims: D,
+    ) -> JlrsResult<BitArrayResult<'target, T>>
+    where
+        D: Dims,
+        T: Target<'target>,
+        S: bitvec::store::BitStore,
+        O: bitvec::order::BitOrder,
+    {
+        if dims.size() != data.len() {
+            Err(InstantiationError::ArraySizeMismatch {
+                vec_size: data.len(),
+                dim_size: dims.size(),
+            })?;
+        }
+
+        let (output, scope) = target.split();
+        scope.scope(|mut frame| {
+            let unrooted = frame.unrooted();
+            let target = frame.extended_target(unrooted);
+
+            // Safety: the array is not rooted, but it's not used after the chunks have been
+            // written. It has just been allocated, so no references to its data exist.
+            unsafe {
+                let res = match BitArray::new(target, dims)? {
+                    Ok(arr) => {
+                        let arr = arr.as_managed();
+                        for (word, bits) in arr.chunks_mut().iter_mut().zip(data.chunks(64)) {
+                            *word = bits
+                                .iter()
+                                .by_vals()
+                                .enumerate()
+                                .fold(0, |word, (i, bit)| word | ((bit as u64) << i));
+                        }
+                        Ok(arr.unwrap_non_null(Private))
+                    }
+                    Err(e) => Err(e.as_managed().unwrap_non_null(Private)),
+                };
+
+                Ok(output.result_from_ptr(res, Private))
+            }
+        })
+    }
+
+    /// Copy the elements of this array to a `BitVec` in column-major order.
+    pub fn to_bitvec(self) -> bitvec::vec::BitVec<u64, bitvec::order::Lsb0> {
+        // Safety: the chunks are only read.
+        let chunks = unsafe { self.chunks_slice() };
+        let mut bits = bitvec::vec::BitVec::from_slice(chunks);
+        bits.truncate(self.len());
+        bits
+    }
+}
+
+unsafe impl Typecheck for BitArray<'_, '_> {
+    fn typecheck(t: DataType) -> bool {
+        // Safety: BitArray is a UnionAll, so check if the typenames match
+        unsafe {
+            let unrooted = Unrooted::new();
+            let ua = inline_static_global!(BIT_ARRAY_TYPE, UnionAll, "Base.BitArray", unrooted);
+            t.type_name() == ua.base_type().type_name()
+        }
+    }
+}
+
+impl_debug!(BitArray<'_, '_>);
+
+impl<'scope, 'data> ManagedPriv<'scope, 'data> for BitArray<'scope, 'data> {
+    type Wraps = jl_value_t;
+    type TypeConstructorPriv<'target, 'da> = BitArray<'target, 'da>;
+    const NAME: &'static str = "BitArray";
+
+    // Safety: `inner` must not have been freed yet, the result must never be
+    // used after the GC might have freed it.
+    unsafe fn wrap_non_null(inner: NonNull<Self::Wraps>, _: Private) -> Self {
+        Self(inner, PhantomData, PhantomData)
+    }
+
+    #[inline(always)]
+    fn unwrap_non_null(self, _: Private) -> NonNull<Self::Wraps> {
+        self.0
+    }
+}
+
+unsafe impl ConstructType for BitArray<'_, '_> {
+    fn construct_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> crate::data::managed::value::ValueData<'target, 'static, T>
+    where
+        T: Target<'target>,
+    {
+        let (target, _) = target.split();
+        let ua = inline_static_global!(BIT_ARRAY_TYPE, UnionAll, "Base.BitArray", target);
+        ua.as_value().root(target)
+    }
+
+    fn base_type<'target, Tgt>(target: &Tgt) -> Option<Value<'target, 'static>>
+    where
+        Tgt: Target<'target>,
+    {
+        let ua = inline_static_global!(BIT_ARRAY_TYPE, UnionAll, "Base.BitArray", target);
+        Some(ua.as_value())
+    }
+}
+
+impl_ccall_arg_managed!(BitArray, 2);
+
+/// A reference to a [`BitArray`] that has not been explicitly rooted.
+pub type BitArrayRef<'scope, 'data> = Ref<'scope, 'data, BitArray<'scope, 'data>>;
+
+/// A [`BitArrayRef`] with static lifetimes. This is a useful shorthand for signatures of
+/// `ccall`able functions that return a [`BitArray`].
+pub type BitArrayRet = Ref<'static, 'static, BitArray<'static, 'static>>;
+
+/// `BitArray` or `BitArrayRef`, depending on the target type `T`.
+pub type BitArrayData<'target, T> =
+    <T as TargetType<'target>>::Data<'static, BitArray<'target, 'static>>;
+
+/// `JuliaResult<BitArray>` or `JuliaResultRef<BitArrayRef>`, depending on the target type `T`.
+pub type BitArrayResult<'target, T> =
+    <T as TargetType<'target>>::Result<'static, BitArray<'target, 'static>>;
@@ -0,0 +1,135 @@
+//! Julia arrays backed by buffers owned by Rust.
+//!
+//! [`Array::from_vec`] moves a `Vec` to Julia, the `Vec` is dropped when the array is freed by
+//! the garbage collector. Data that isn't owned by a `Vec` can also be shared with Julia without
+//! copying it, as long as its owner implements [`ArrayBuffer`]. [`Array::from_buffer`] creates a
+//! new array that aliases the buffer and keeps its owner alive until the array is freed, at which
+//! point [`ArrayBuffer::release`] is called by a finalizer.
+//!
+//! An owner that shares its data with other owners, like an `Arc<[T]>`, can only implement this
+//! trait if Julia doesn't mutate the array:
+//!
+//! ```ignore
+//! struct SharedBuffer(Arc<[f64]>);
+//!
+//! // Safety: the data of the Arc is never moved. The array is only read by Julia.
+//! unsafe impl ArrayBuffer for SharedBuffer {
+//!     type Element = f64;
+//!
+//!     fn as_mut_ptr(&mut self) -> *mut f64 {
+//!         self.0.as_ptr() as *mut f64
+//!     }
+//!
+//!     fn len(&self) -> usize {
+//!         self.0.len()
+//!     }
+//! }
+//! ```
+//!
+//! [`Array::from_vec`]: crate::data::managed::array::Array::from_vec
+//! [`Array::from_buffer`]: crate::data::managed::array::Array::from_buffer
+
+use std::{any::Any, collections::HashMap, mem, ptr::null_mut, slice, sync::Mutex};
+
+use jl_sys::{jl_array_dims_ptr, jl_array_ndims};
+use once_cell::sync::Lazy;
+
+use crate::{
+    data::managed::{array::Array, private::ManagedPriv},
+    private::Private,
+};
+
+// The owners of the buffers that back Julia arrays, indexed by the address of the array.
+static BUFFERS: Lazy<Mutex<HashMap<usize, Box<dyn Any + Send>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A buffer that can back a Julia array.
+///
+/// Safety: `as_mut_ptr` must return a pointer to `len` initialized elements. This data must not
+/// be moved or freed until the buffer is released, and Julia can read and write the data while
+/// the array exists. If the data is shared with other owners, the array must not be mutated.
+pub unsafe trait ArrayBuffer: Send + 'static {
+    /// The type of the elements.
+    type Element;
+
+    /// Returns a pointer to the first element.
+    fn as_mut_ptr(&mut self) -> *mut Self::Element;
+
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the buffer contains no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Called by the finalizer of the array when it's freed by the garbage collector. By default
+    /// the buffer is dropped.
+    fn release(self)
+    where
+        Self: Sized,
+    {
+    }
+}
+
+unsafe impl<T: Send + 'static> ArrayBuffer for Vec<T> {
+    type Element = T;
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.as_mut_slice().as_mut_ptr()
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+unsafe impl<T: Send + 'static> ArrayBuffer for Box<[T]> {
+    type Element = T;
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        <[T]>::as_mut_ptr(self)
+    }
+
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+}
+
+// Store `buffer` until the array at `array` is finalized.
+pub(crate) fn register_buffer<B: ArrayBuffer>(array: Array, buffer: B) {
+    let key = array.unwrap(Private) as usize;
+    BUFFERS
+        .lock()
+        .unwrap()
+        .insert(key, Box::new(buffer) as Box<dyn Any + Send>);
+}
+
+// Release the buffer that backs `array`. This function is called by the finalizer of the array.
+pub(crate) unsafe extern "C" fn release_buffer<B: ArrayBuffer>(array: Array) {
+    let mut arr_nn_ptr = array.unwrap_non_null(Private);
+    let key = arr_nn_ptr.as_ptr() as usize;
+
+    // The lock must not be held while the buffer is released.
+    let buffer = BUFFERS.lock().unwrap().remove(&key);
+    let buffer = match buffer {
+        Some(buffer) => buffer,
+        None => return,
+    };
+
+    // The data is no longer valid, set it to a null pointer and all dims to 0.
+    let arr_ref = arr_nn_ptr.as_mut();
+    arr_ref.data = null_mut();
+
+    let arr_ptr = arr_nn_ptr.as_ptr();
+    let dims_ptr = jl_array_dims_ptr(arr_ptr);
+    let n_dims = jl_array_ndims(arr_ptr);
+    for dim in slice::from_raw_parts_mut(dims_ptr, n_dims as _) {
+        *dim = 0;
+    }
+
+    match buffer.downcast::<B>() {
+        Ok(buffer) => buffer.release(),
+        Err(buffer) => mem::drop(buffer),
+    }
+}
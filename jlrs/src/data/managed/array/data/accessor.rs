@@ -11,10 +11,16 @@ use jl_sys::{jl_array_ptr_set, jl_array_typetagdata, jl_arrayref, jl_arrayset};
 
 use crate::{
     data::{
-        layout::valid_layout::ValidField,
+        layout::{union::UnionEnum, valid_layout::ValidField},
         managed::{
             array::{
-                dimensions::{ArrayDimensions, Dims},
+                data::{
+                    chunks::AxisChunksMut,
+                    copied::CopiedArray,
+                    iter::{self, IndexedIter, IndexedIterMut, Indices, Lanes},
+                },
+                dimensions::{ArrayDimensions, Dimensions, Dims},
+                strided::StridedLayout,
                 Array,
             },
             datatype::DataType,
@@ -24,7 +30,7 @@ use crate::{
             Managed, ManagedRef, ManagedType,
         },
     },
-    error::{AccessError, JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
+    error::{AccessError, ArrayLayoutError, JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
     memory::target::Target,
     private::Private,
 };
@@ -452,6 +458,32 @@ impl<'borrow, 'array, 'data, T, M: Mutability> BitsArrayAccessor<'borrow, 'array
         // Safety: the layout is compatible and the lifetime is limited.
         unsafe { slice::from_raw_parts(data, len) }
     }
+
+    /// Returns an iterator over all indices of the array in column-major order.
+    pub fn indices(&self) -> Indices {
+        Indices::new(&self.dimensions())
+    }
+
+    /// Returns an iterator over the elements of the array and their indices in column-major
+    /// order.
+    pub fn indexed_iter(&self) -> IndexedIter<'_, T> {
+        IndexedIter::new(self.as_slice(), &self.dimensions())
+    }
+
+    /// Returns an iterator over the lanes of the array along `axis`.
+    ///
+    /// Returns `AccessError::OutOfBoundsAxis` if `axis` is not smaller than the rank of the array.
+    pub fn lanes(&self, axis: usize) -> JlrsResult<Lanes<'_, T>> {
+        Lanes::new(self.as_slice(), &self.dimensions(), axis)
+    }
+
+    /// Returns an iterator over the lanes of the array along the first axis as slices. For a
+    /// matrix, these are its columns.
+    ///
+    /// Returns `AccessError::OutOfBoundsAxis` if the rank of the array is 0.
+    pub fn columns(&self) -> JlrsResult<slice::ChunksExact<'_, T>> {
+        iter::columns(self.as_slice(), &self.dimensions())
+    }
 }
 
 impl<'borrow, 'array, 'data, T> BitsArrayAccessor<'borrow, 'array, 'data, T, Mutable<'borrow, T>> {
@@ -492,6 +524,117 @@ impl<'borrow, 'array, 'data, T> BitsArrayAccessor<'borrow, 'array, 'data, T, Mut
         // Safety: the layout is compatible and the lifetime is limited.
         unsafe { slice::from_raw_parts_mut(data, len) }
     }
+
+    /// Returns an iterator over the elements of the array and their indices in column-major
+    /// order that allows modifying each element.
+    pub fn indexed_iter_mut(&mut self) -> IndexedIterMut<'_, T> {
+        let dims = self.dimensions();
+        IndexedIterMut::new(self.as_mut_slice(), &dims)
+    }
+
+    /// Split the array into disjoint chunks along `axis`, each chunk contains at most
+    /// `chunk_size` elements along that axis.
+    ///
+    /// The chunks can be sent to other threads, but they must not call into Julia. Returns
+    /// `AccessError::OutOfBoundsAxis` if `axis` is not smaller than the rank of the array.
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn axis_chunks_mut(
+        &mut self,
+        axis: usize,
+        chunk_size: usize,
+    ) -> JlrsResult<AxisChunksMut<'_, T>> {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+
+        let dims = self.dimensions();
+        let rank = dims.rank();
+        if axis >= rank {
+            Err(AccessError::OutOfBoundsAxis { axis, rank })?;
+        }
+
+        // Safety: the data has the dimensions `dims`, the axis is in bounds and chunk_size is
+        // non-zero.
+        unsafe {
+            Ok(AxisChunksMut::new(
+                self.as_mut_slice(),
+                &dims,
+                axis,
+                chunk_size,
+            ))
+        }
+    }
+
+    /// Split a matrix into its columns.
+    ///
+    /// Returns `ArrayLayoutError::RankMismatch` if the array is not two-dimensional.
+    pub fn columns_mut(&mut self) -> JlrsResult<AxisChunksMut<'_, T>> {
+        let rank = self.dimensions().rank();
+        if rank != 2 {
+            Err(ArrayLayoutError::RankMismatch {
+                found: rank as isize,
+                provided: 2,
+            })?;
+        }
+
+        self.axis_chunks_mut(1, 1)
+    }
+
+    /// Split a matrix into its rows.
+    ///
+    /// Returns `ArrayLayoutError::RankMismatch` if the array is not two-dimensional.
+    pub fn rows_mut(&mut self) -> JlrsResult<AxisChunksMut<'_, T>> {
+        let rank = self.dimensions().rank();
+        if rank != 2 {
+            Err(ArrayLayoutError::RankMismatch {
+                found: rank as isize,
+                provided: 2,
+            })?;
+        }
+
+        self.axis_chunks_mut(0, 1)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'borrow, 'array, 'data, T, M: Mutability> BitsArrayAccessor<'borrow, 'array, 'data, T, M>
+where
+    T: Sync,
+{
+    /// Returns a parallel iterator over the array's data in column-major order.
+    pub fn par_iter(&self) -> rayon::slice::Iter<'_, T> {
+        use rayon::prelude::*;
+        self.as_slice().par_iter()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'borrow, 'array, 'data, T> BitsArrayAccessor<'borrow, 'array, 'data, T, Mutable<'borrow, T>>
+where
+    T: Send,
+{
+    /// Returns a parallel iterator over the array's data in column-major order that allows
+    /// modifying each element.
+    pub fn par_iter_mut(&mut self) -> rayon::slice::IterMut<'_, T> {
+        use rayon::prelude::*;
+        self.as_mut_slice().par_iter_mut()
+    }
+
+    /// Returns a parallel iterator over disjoint chunks of the array along `axis`, each chunk
+    /// contains at most `chunk_size` elements along that axis.
+    ///
+    /// Returns `AccessError::OutOfBoundsAxis` if `axis` is not smaller than the rank of the
+    /// array.
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn par_axis_chunks_mut(
+        &mut self,
+        axis: usize,
+        chunk_size: usize,
+    ) -> JlrsResult<rayon::vec::IntoIter<super::chunks::ArrayChunkMut<'_, T>>> {
+        use rayon::prelude::*;
+        let chunks: Vec<_> = self.axis_chunks_mut(axis, chunk_size)?.collect();
+        Ok(chunks.into_par_iter())
+    }
 }
 
 impl<'borrow, 'array, 'data, T, M, D> Index<D> for BitsArrayAccessor<'borrow, 'array, 'data, T, M>
@@ -561,6 +704,32 @@ impl<'borrow, 'array, 'data, T, M: Mutability>
         // Safety: the layout is compatible and the lifetime is limited.
         unsafe { slice::from_raw_parts(data, len) }
     }
+
+    /// Returns an iterator over all indices of the array in column-major order.
+    pub fn indices(&self) -> Indices {
+        Indices::new(&self.dimensions())
+    }
+
+    /// Returns an iterator over the elements of the array and their indices in column-major
+    /// order.
+    pub fn indexed_iter(&self) -> IndexedIter<'_, T> {
+        IndexedIter::new(self.as_slice(), &self.dimensions())
+    }
+
+    /// Returns an iterator over the lanes of the array along `axis`.
+    ///
+    /// Returns `AccessError::OutOfBoundsAxis` if `axis` is not smaller than the rank of the array.
+    pub fn lanes(&self, axis: usize) -> JlrsResult<Lanes<'_, T>> {
+        Lanes::new(self.as_slice(), &self.dimensions(), axis)
+    }
+
+    /// Returns an iterator over the lanes of the array along the first axis as slices. For a
+    /// matrix, these are its columns.
+    ///
+    /// Returns `AccessError::OutOfBoundsAxis` if the rank of the array is 0.
+    pub fn columns(&self) -> JlrsResult<slice::ChunksExact<'_, T>> {
+        iter::columns(self.as_slice(), &self.dimensions())
+    }
 }
 
 impl<'borrow, 'array, 'data, T, M, D> Index<D>
@@ -640,6 +809,41 @@ impl<'borrow, 'array, 'data, M: Mutability> UnionArrayAccessor<'borrow, 'array,
             })?
         }
     }
+
+    /// Get the element at index `idx` as the variant of `E` that corresponds to its type.
+    ///
+    /// Returns an error if the type of that element isn't the type of one of the variants of `E`.
+    pub fn get_enum<E, D>(&self, index: D) -> JlrsResult<E>
+    where
+        E: UnionEnum,
+        D: Dims,
+    {
+        let elty = self.array.element_type();
+        let idx = self.dimensions().index_of(&index)?;
+
+        // Safety: The index is in bounds and E checks the type of the element.
+        unsafe {
+            let tags = jl_array_typetagdata(self.array.unwrap(Private));
+            let mut tag = *tags.add(idx) as _;
+
+            if let Some(ty) = nth_union_component(elty, &mut tag) {
+                let offset = idx * self.array.unwrap_non_null(Private).as_ref().elsize as usize;
+                let ptr = self.array.data_ptr().cast::<u8>().add(offset);
+                if let Some(value) = E::read_variant(ty.cast_unchecked::<DataType>(), ptr) {
+                    return Ok(value);
+                }
+
+                Err(AccessError::InvalidLayout {
+                    value_type: ty.display_string_or(CANNOT_DISPLAY_TYPE),
+                })?
+            }
+
+            Err(AccessError::IllegalUnionTag {
+                union_type: elty.display_string_or(CANNOT_DISPLAY_TYPE),
+                tag: tag as usize,
+            })?
+        }
+    }
 }
 
 impl<'borrow, 'array, 'data> UnionArrayAccessor<'borrow, 'array, 'data, Mutable<'borrow, u8>> {
@@ -686,4 +890,205 @@ impl<'borrow, 'array, 'data> UnionArrayAccessor<'borrow, 'array, 'data, Mutable<
 
         Ok(())
     }
+
+    /// Set the element at index `idx` to `value`.
+    ///
+    /// The type of the data of `value` must be a member of the union of all possible element
+    /// types.
+    pub fn set_enum<E, D>(&mut self, index: D, value: E) -> JlrsResult<()>
+    where
+        E: UnionEnum,
+        D: Dims,
+    {
+        let ty = value.variant_type(&self.array.unrooted_target());
+        let mut tag = 0;
+        let elty = self.array.element_type();
+        if !find_union_component(elty, ty.as_value(), &mut tag) {
+            let element_type = elty.display_string_or(CANNOT_DISPLAY_TYPE);
+            let value_type = ty.display_string_or(CANNOT_DISPLAY_TYPE);
+            Err(TypeError::IncompatibleType {
+                element_type,
+                value_type,
+            })?;
+        }
+
+        let idx = self.dimensions().index_of(&index)?;
+        // Safety: The data can be stored in this array, the tag is updated accordingly.
+        unsafe {
+            let offset = idx * self.array.unwrap_non_null(Private).as_ref().elsize as usize;
+            value.write_variant(self.array.data_ptr().cast::<u8>().add(offset));
+
+            jl_array_typetagdata(self.array.unwrap(Private))
+                .add(idx)
+                .write(tag as _);
+        }
+
+        Ok(())
+    }
+}
+
+/// An accessor for strided views of Julia arrays whose elements have an `isbits` type.
+///
+/// Unlike the data of an [`Array`], the elements of a [`SubArray`], [`ReshapedArray`] or
+/// [`PermutedDimsArray`] are not necessarily stored contiguously in column-major order. The
+/// element at index `[i, j, ...]` is stored `i * strides[0] + j * strides[1] + ...` elements
+/// after the first element of the view, strides can be negative. The data is owned by the dense
+/// array at the root of the view, which is available as [`StridedArrayAccessor::array`].
+///
+/// [`SubArray`]: crate::data::managed::array::strided::SubArray
+/// [`ReshapedArray`]: crate::data::managed::array::strided::ReshapedArray
+/// [`PermutedDimsArray`]: crate::data::managed::array::strided::PermutedDimsArray
+pub struct StridedArrayAccessor<'borrow, 'array, 'data, T, M: Mutability> {
+    layout: StridedLayout<'array, 'data>,
+    _lt_marker: PhantomData<&'borrow ()>,
+    _ty_marker: PhantomData<*mut T>,
+    _mut_marker: PhantomData<M>,
+}
+
+/// A type alias for an immutable `StridedArrayAccessor`.
+pub type StridedArrayAccessorI<'borrow, 'array, 'data, T> =
+    StridedArrayAccessor<'borrow, 'array, 'data, T, Immutable<'borrow, T>>;
+
+/// A type alias for a mutable `StridedArrayAccessor`.
+pub type StridedArrayAccessorMut<'borrow, 'array, 'data, T> =
+    StridedArrayAccessor<'borrow, 'array, 'data, T, Mutable<'borrow, T>>;
+
+impl<'borrow, 'array, 'data, T, M: Mutability> StridedArrayAccessor<'borrow, 'array, 'data, T, M> {
+    // Safety: the element type of `layout.array()` must be an isbits type that's compatible with
+    // `T`.
+    pub(crate) unsafe fn new(layout: StridedLayout<'array, 'data>) -> Self {
+        StridedArrayAccessor {
+            layout,
+            _lt_marker: PhantomData,
+            _ty_marker: PhantomData,
+            _mut_marker: PhantomData,
+        }
+    }
+
+    /// Returns the array that owns the data of this view.
+    pub fn array(&self) -> Array<'array, 'data> {
+        self.layout.array()
+    }
+
+    /// Returns the dimensions of the view.
+    pub fn dimensions(&self) -> &Dimensions {
+        self.layout.dimensions()
+    }
+
+    /// Returns the strides of the view in number of elements.
+    pub fn strides(&self) -> &[isize] {
+        self.layout.strides()
+    }
+
+    /// Returns the offset of the first element of the view in the data of [`Self::array`].
+    pub fn offset(&self) -> usize {
+        self.layout.offset()
+    }
+
+    /// Returns a pointer to the first element of the view.
+    pub fn as_ptr(&self) -> *const T {
+        // Safety: the offset is in bounds.
+        unsafe { self.array().data_ptr().cast::<T>().add(self.offset()) }
+    }
+
+    /// Get a reference to the value at `index`, or `None` if the index is out of bounds.
+    pub fn get<D>(&self, index: D) -> Option<&T>
+    where
+        D: Dims,
+    {
+        let idx = self.layout.index_of(&index).ok()?;
+        // Safety: the index is in bounds
+        unsafe { self.as_ptr().offset(idx).as_ref() }
+    }
+
+    /// Copy the elements of the view to a new `CopiedArray`.
+    pub fn copy_data(&self) -> CopiedArray<T>
+    where
+        T: Clone,
+    {
+        let dims = self.dimensions().clone();
+        let rank = dims.rank();
+        let mut data = Vec::with_capacity(dims.size());
+        let mut index = vec![0; rank];
+
+        if rank == 0 {
+            data.push(self[index.as_slice()].clone());
+        } else if dims.size() != 0 {
+            'outer: loop {
+                data.push(self[index.as_slice()].clone());
+
+                for (dim, i) in index.iter_mut().enumerate() {
+                    *i += 1;
+                    if *i < dims.n_elements(dim) {
+                        continue 'outer;
+                    }
+
+                    *i = 0;
+                }
+
+                break;
+            }
+        }
+
+        // Safety: the elements have been copied in column-major order.
+        unsafe { CopiedArray::new(data.into_boxed_slice(), dims) }
+    }
+}
+
+impl<'borrow, 'array, 'data, T>
+    StridedArrayAccessor<'borrow, 'array, 'data, T, Mutable<'borrow, T>>
+{
+    /// Returns a mutable pointer to the first element of the view.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        // Safety: the offset is in bounds.
+        unsafe { self.array().data_ptr().cast::<T>().add(self.offset()) }
+    }
+
+    /// Set the value at `index` to `value`.
+    pub fn set<D>(&mut self, index: D, value: T) -> JlrsResult<()>
+    where
+        D: Dims,
+    {
+        let idx = self.layout.index_of(&index)?;
+        // Safety: the index is in bounds and layout is compatible.
+        unsafe { self.as_mut_ptr().offset(idx).write(value) };
+
+        Ok(())
+    }
+
+    /// Get a mutable reference to the element stored at `index`.
+    pub fn get_mut<D>(&mut self, index: D) -> Option<&mut T>
+    where
+        D: Dims,
+    {
+        let idx = self.layout.index_of(&index).ok()?;
+        // Safety: the index is in bounds and layout is compatible.
+        unsafe { self.as_mut_ptr().offset(idx).as_mut() }
+    }
+}
+
+impl<'borrow, 'array, 'data, T, M, D> Index<D>
+    for StridedArrayAccessor<'borrow, 'array, 'data, T, M>
+where
+    D: Dims,
+    M: Mutability,
+{
+    type Output = T;
+    fn index(&self, index: D) -> &Self::Output {
+        let idx = self.layout.index_of(&index).unwrap();
+        // Safety: the layout is compatible and the index is in bounds.
+        unsafe { self.as_ptr().offset(idx).as_ref().unwrap_unchecked() }
+    }
+}
+
+impl<'borrow, 'array, 'data, T, D> IndexMut<D>
+    for StridedArrayAccessor<'borrow, 'array, 'data, T, Mutable<'borrow, T>>
+where
+    D: Dims,
+{
+    fn index_mut(&mut self, index: D) -> &mut Self::Output {
+        let idx = self.layout.index_of(&index).unwrap();
+        // Safety: the layout is compatible and the index is in bounds.
+        unsafe { self.as_mut_ptr().offset(idx).as_mut().unwrap_unchecked() }
+    }
 }
@@ -0,0 +1,277 @@
+//! Split the data of a bits array into disjoint parts.
+//!
+//! A [`BitsArrayAccessorMut`] provides mutable access to the entire array. With
+//! [`BitsArrayAccessorMut::axis_chunks_mut`] its data can be split into disjoint chunks along
+//! some axis, e.g. the columns or rows of a matrix. These chunks don't depend on the array, they
+//! can be sent to and mutated on other threads without calling into Julia as long as the accessor
+//! is borrowed.
+//!
+//! [`BitsArrayAccessorMut`]: crate::data::managed::array::data::accessor::BitsArrayAccessorMut
+//! [`BitsArrayAccessorMut::axis_chunks_mut`]: crate::data::managed::array::data::accessor::ArrayAccessor::axis_chunks_mut
+
+use std::{
+    iter::FusedIterator,
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+    ptr::NonNull,
+    slice,
+};
+
+use crate::{
+    data::managed::array::dimensions::{Dimensions, Dims},
+    error::{AccessError, JlrsResult},
+};
+
+/// A disjoint, mutable part of a bits array.
+///
+/// A chunk contains all elements of the array whose index along the split axis is in
+/// `offset..offset + len`, where `len` is the number of elements of the chunk along that axis.
+/// Elements are indexed relative to the chunk, i.e. the first element of the chunk has index
+/// `[0, ..., 0]`.
+pub struct ArrayChunkMut<'borrow, T> {
+    ptr: NonNull<T>,
+    dims: Dimensions,
+    strides: Box<[usize]>,
+    axis: usize,
+    offset: usize,
+    contiguous: bool,
+    _marker: PhantomData<&'borrow mut [T]>,
+}
+
+// Safety: a chunk has exclusive access to its elements.
+unsafe impl<'borrow, T: Send> Send for ArrayChunkMut<'borrow, T> {}
+unsafe impl<'borrow, T: Sync> Sync for ArrayChunkMut<'borrow, T> {}
+
+impl<'borrow, T> ArrayChunkMut<'borrow, T> {
+    /// Returns the dimensions of the chunk.
+    pub fn dimensions(&self) -> &Dimensions {
+        &self.dims
+    }
+
+    /// Returns the axis along which the array has been split.
+    pub fn axis(&self) -> usize {
+        self.axis
+    }
+
+    /// Returns the index of the first element of the chunk along the split axis of the array.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns `true` if the elements of the chunk are stored contiguously.
+    pub fn is_contiguous(&self) -> bool {
+        self.contiguous
+    }
+
+    /// Returns the data of the chunk as a slice if it's stored contiguously, the data is in
+    /// column-major order.
+    pub fn as_slice(&self) -> Option<&[T]> {
+        if !self.contiguous {
+            return None;
+        }
+
+        // Safety: the data is contiguous, the chunk has exclusive access to it.
+        unsafe { Some(slice::from_raw_parts(self.ptr.as_ptr(), self.dims.size())) }
+    }
+
+    /// Returns the data of the chunk as a mutable slice if it's stored contiguously, the data is
+    /// in column-major order.
+    pub fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        if !self.contiguous {
+            return None;
+        }
+
+        // Safety: the data is contiguous, the chunk has exclusive access to it.
+        unsafe {
+            Some(slice::from_raw_parts_mut(
+                self.ptr.as_ptr(),
+                self.dims.size(),
+            ))
+        }
+    }
+
+    /// Get a reference to the value at `index`, or `None` if the index is out of bounds.
+    pub fn get<D>(&self, index: D) -> Option<&T>
+    where
+        D: Dims,
+    {
+        let idx = self.index_of(&index).ok()?;
+        // Safety: the index is in bounds
+        unsafe { self.ptr.as_ptr().add(idx).as_ref() }
+    }
+
+    /// Get a mutable reference to the value at `index`, or `None` if the index is out of bounds.
+    pub fn get_mut<D>(&mut self, index: D) -> Option<&mut T>
+    where
+        D: Dims,
+    {
+        let idx = self.index_of(&index).ok()?;
+        // Safety: the index is in bounds
+        unsafe { self.ptr.as_ptr().add(idx).as_mut() }
+    }
+
+    /// Set the value at `index` to `value`.
+    pub fn set<D>(&mut self, index: D, value: T) -> JlrsResult<()>
+    where
+        D: Dims,
+    {
+        let idx = self.index_of(&index)?;
+        // Safety: the index is in bounds
+        unsafe { self.ptr.as_ptr().add(idx).write(value) };
+        Ok(())
+    }
+
+    // Calculate the offset of the element at `index` relative to the first element of the chunk.
+    fn index_of<D: Dims>(&self, index: &D) -> JlrsResult<usize> {
+        let rank = self.dims.rank();
+        if index.rank() != rank {
+            Err(AccessError::InvalidIndex {
+                idx: index.into_dimensions(),
+                sz: self.dims.clone(),
+            })?;
+        }
+
+        let mut idx = 0;
+        for dim in 0..rank {
+            let i = index.n_elements(dim);
+            if i >= self.dims.n_elements(dim) {
+                Err(AccessError::InvalidIndex {
+                    idx: index.into_dimensions(),
+                    sz: self.dims.clone(),
+                })?;
+            }
+
+            idx += i * self.strides[dim];
+        }
+
+        Ok(idx)
+    }
+}
+
+impl<'borrow, T, D> Index<D> for ArrayChunkMut<'borrow, T>
+where
+    D: Dims,
+{
+    type Output = T;
+    fn index(&self, index: D) -> &Self::Output {
+        let idx = self.index_of(&index).unwrap();
+        // Safety: the index is in bounds.
+        unsafe { self.ptr.as_ptr().add(idx).as_ref().unwrap_unchecked() }
+    }
+}
+
+impl<'borrow, T, D> IndexMut<D> for ArrayChunkMut<'borrow, T>
+where
+    D: Dims,
+{
+    fn index_mut(&mut self, index: D) -> &mut Self::Output {
+        let idx = self.index_of(&index).unwrap();
+        // Safety: the index is in bounds.
+        unsafe { self.ptr.as_ptr().add(idx).as_mut().unwrap_unchecked() }
+    }
+}
+
+/// An iterator over disjoint chunks of a bits array along some axis.
+///
+/// This iterator is created with [`BitsArrayAccessorMut::axis_chunks_mut`].
+///
+/// [`BitsArrayAccessorMut::axis_chunks_mut`]: crate::data::managed::array::data::accessor::ArrayAccessor::axis_chunks_mut
+pub struct AxisChunksMut<'borrow, T> {
+    ptr: NonNull<T>,
+    dims: Box<[usize]>,
+    strides: Box<[usize]>,
+    axis: usize,
+    chunk_size: usize,
+    offset: usize,
+    _marker: PhantomData<&'borrow mut [T]>,
+}
+
+// Safety: the chunks are disjoint.
+unsafe impl<'borrow, T: Send> Send for AxisChunksMut<'borrow, T> {}
+unsafe impl<'borrow, T: Sync> Sync for AxisChunksMut<'borrow, T> {}
+
+impl<'borrow, T> AxisChunksMut<'borrow, T> {
+    // Safety: `data` must be the data of an array with dimensions `dims`, `axis` must be smaller
+    // than the rank of the array and `chunk_size` must be non-zero.
+    pub(crate) unsafe fn new<D: Dims>(
+        data: &'borrow mut [T],
+        dims: &D,
+        axis: usize,
+        chunk_size: usize,
+    ) -> Self {
+        let rank = dims.rank();
+        let dims: Box<[usize]> = (0..rank).map(|dim| dims.n_elements(dim)).collect();
+        let strides = dims
+            .iter()
+            .scan(1, |stride, &n| {
+                let current = *stride;
+                *stride *= n;
+                Some(current)
+            })
+            .collect();
+
+        AxisChunksMut {
+            ptr: NonNull::new_unchecked(data.as_mut_ptr()),
+            dims,
+            strides,
+            axis,
+            chunk_size,
+            offset: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the axis along which the array is split.
+    pub fn axis(&self) -> usize {
+        self.axis
+    }
+
+    /// Returns the maximum number of elements of each chunk along the split axis.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+}
+
+impl<'borrow, T> Iterator for AxisChunksMut<'borrow, T> {
+    type Item = ArrayChunkMut<'borrow, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.dims[self.axis];
+        if self.offset >= n {
+            return None;
+        }
+
+        let offset = self.offset;
+        let len = self.chunk_size.min(n - offset);
+        self.offset += len;
+
+        let mut dims = self.dims.clone();
+        dims[self.axis] = len;
+        let contiguous = len == n || dims[self.axis + 1..].iter().all(|&n| n == 1);
+
+        // Safety: the offset is in bounds, chunks never overlap.
+        let ptr = unsafe {
+            NonNull::new_unchecked(self.ptr.as_ptr().add(offset * self.strides[self.axis]))
+        };
+
+        Some(ArrayChunkMut {
+            ptr,
+            dims: Dimensions::from_dims(&dims.as_ref()),
+            strides: self.strides.clone(),
+            axis: self.axis,
+            offset,
+            contiguous,
+            _marker: PhantomData,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.dims[self.axis].saturating_sub(self.offset);
+        let n_chunks = (remaining + self.chunk_size - 1) / self.chunk_size;
+        (n_chunks, Some(n_chunks))
+    }
+}
+
+impl<'borrow, T> ExactSizeIterator for AxisChunksMut<'borrow, T> {}
+
+impl<'borrow, T> FusedIterator for AxisChunksMut<'borrow, T> {}
@@ -1,8 +1,17 @@
 //! Array data that has been copied from Julia to Rust.
 
-use std::ops::{Index, IndexMut};
+use std::{
+    ops::{Index, IndexMut},
+    slice,
+};
 
-use crate::data::managed::array::dimensions::{Dimensions, Dims};
+use crate::{
+    data::managed::array::{
+        data::iter::{self, IndexedIter, IndexedIterMut, Indices, Lanes},
+        dimensions::{Dimensions, Dims},
+    },
+    error::JlrsResult,
+};
 
 /// An n-dimensional array whose contents have been copied from Julia to Rust.
 ///
@@ -53,6 +62,38 @@ impl<T> CopiedArray<T> {
     pub fn dimensions(&self) -> &Dimensions {
         &self.dimensions
     }
+
+    /// Returns an iterator over all indices of the array in column-major order.
+    pub fn indices(&self) -> Indices {
+        Indices::new(&self.dimensions)
+    }
+
+    /// Returns an iterator over the elements of the array and their indices in column-major
+    /// order.
+    pub fn indexed_iter(&self) -> IndexedIter<'_, T> {
+        IndexedIter::new(&self.data, &self.dimensions)
+    }
+
+    /// Returns an iterator over the elements of the array and their indices in column-major
+    /// order that allows modifying each element.
+    pub fn indexed_iter_mut(&mut self) -> IndexedIterMut<'_, T> {
+        IndexedIterMut::new(&mut self.data, &self.dimensions)
+    }
+
+    /// Returns an iterator over the lanes of the array along `axis`.
+    ///
+    /// Returns `AccessError::OutOfBoundsAxis` if `axis` is not smaller than the rank of the array.
+    pub fn lanes(&self, axis: usize) -> JlrsResult<Lanes<'_, T>> {
+        Lanes::new(&self.data, &self.dimensions, axis)
+    }
+
+    /// Returns an iterator over the lanes of the array along the first axis as slices. For a
+    /// matrix, these are its columns.
+    ///
+    /// Returns `AccessError::OutOfBoundsAxis` if the rank of the array is 0.
+    pub fn columns(&self) -> JlrsResult<slice::ChunksExact<'_, T>> {
+        iter::columns(&self.data, &self.dimensions)
+    }
 }
 
 impl<T, D: Dims> Index<D> for CopiedArray<T> {
@@ -0,0 +1,332 @@
+//! Iterate over the indices, elements and lanes of n-dimensional arrays.
+//!
+//! The data of a Julia array is stored in column-major order, the iterators in this module visit
+//! elements in the same order. The indices they yield start at 0, they can be converted to and
+//! from one-based indices with [`CartesianIndex`].
+//!
+//! These iterators are returned by methods of [`BitsArrayAccessor`], [`InlinePtrArrayAccessor`]
+//! and [`CopiedArray`].
+//!
+//! [`CartesianIndex`]: crate::data::managed::array::dimensions::CartesianIndex
+//! [`BitsArrayAccessor`]: crate::data::managed::array::data::accessor::BitsArrayAccessor
+//! [`InlinePtrArrayAccessor`]: crate::data::managed::array::data::accessor::InlinePtrArrayAccessor
+//! [`CopiedArray`]: crate::data::managed::array::data::copied::CopiedArray
+
+use std::{
+    iter::{FusedIterator, StepBy, Take},
+    slice,
+};
+
+use crate::{
+    data::managed::array::dimensions::{Dimensions, Dims},
+    error::{AccessError, JlrsResult},
+};
+
+/// An iterator over all n-dimensional indices of an array in column-major order.
+#[derive(Clone, Debug)]
+pub struct Indices {
+    dims: Box<[usize]>,
+    current: Box<[usize]>,
+    remaining: usize,
+}
+
+impl Indices {
+    /// Create a new iterator over all indices of an array with dimensions `dims`.
+    pub fn new<D: Dims>(dims: &D) -> Self {
+        let rank = dims.rank();
+        let dims: Box<[usize]> = (0..rank).map(|dim| dims.n_elements(dim)).collect();
+        // A zero-dimensional array contains exactly one element
+        let remaining = dims.iter().product();
+
+        Indices {
+            current: vec![0; rank].into_boxed_slice(),
+            dims,
+            remaining,
+        }
+    }
+}
+
+impl Iterator for Indices {
+    type Item = Dimensions;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let index = Dimensions::from_dims(&self.current.as_ref());
+        self.remaining -= 1;
+
+        for (i, n) in self.current.iter_mut().zip(self.dims.iter()) {
+            *i += 1;
+            if *i < *n {
+                break;
+            }
+
+            *i = 0;
+        }
+
+        Some(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Indices {}
+
+impl FusedIterator for Indices {}
+
+/// An iterator over the elements of an array and their indices in column-major order.
+pub struct IndexedIter<'borrow, T> {
+    indices: Indices,
+    data: slice::Iter<'borrow, T>,
+}
+
+impl<'borrow, T> IndexedIter<'borrow, T> {
+    pub(crate) fn new<D: Dims>(data: &'borrow [T], dims: &D) -> Self {
+        IndexedIter {
+            indices: Indices::new(dims),
+            data: data.iter(),
+        }
+    }
+}
+
+impl<'borrow, T> Iterator for IndexedIter<'borrow, T> {
+    type Item = (Dimensions, &'borrow T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some((self.indices.next()?, self.data.next()?))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<'borrow, T> ExactSizeIterator for IndexedIter<'borrow, T> {}
+
+impl<'borrow, T> FusedIterator for IndexedIter<'borrow, T> {}
+
+/// An iterator over the elements of an array and their indices in column-major order that
+/// allows modifying each element.
+pub struct IndexedIterMut<'borrow, T> {
+    indices: Indices,
+    data: slice::IterMut<'borrow, T>,
+}
+
+impl<'borrow, T> IndexedIterMut<'borrow, T> {
+    pub(crate) fn new<D: Dims>(data: &'borrow mut [T], dims: &D) -> Self {
+        IndexedIterMut {
+            indices: Indices::new(dims),
+            data: data.iter_mut(),
+        }
+    }
+}
+
+impl<'borrow, T> Iterator for IndexedIterMut<'borrow, T> {
+    type Item = (Dimensions, &'borrow mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some((self.indices.next()?, self.data.next()?))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<'borrow, T> ExactSizeIterator for IndexedIterMut<'borrow, T> {}
+
+impl<'borrow, T> FusedIterator for IndexedIterMut<'borrow, T> {}
+
+/// An iterator over the elements of a single lane of an array.
+///
+/// A lane contains all elements whose indices only differ along one axis.
+pub type Lane<'borrow, T> = Take<StepBy<slice::Iter<'borrow, T>>>;
+
+/// An iterator over all lanes of an array along some axis.
+///
+/// Lanes are visited in column-major order of the indices of their first element. For a matrix,
+/// the lanes along axis 0 are its columns and the lanes along axis 1 are its rows.
+pub struct Lanes<'borrow, T> {
+    data: &'borrow [T],
+    stride: usize,
+    len: usize,
+    current: usize,
+    n_lanes: usize,
+}
+
+impl<'borrow, T> Lanes<'borrow, T> {
+    pub(crate) fn new<D: Dims>(data: &'borrow [T], dims: &D, axis: usize) -> JlrsResult<Self> {
+        let rank = dims.rank();
+        if axis >= rank {
+            Err(AccessError::OutOfBoundsAxis { axis, rank })?;
+        }
+
+        let stride = (0..axis).map(|dim| dims.n_elements(dim)).product();
+        let len = dims.n_elements(axis);
+        let n_lanes = (0..rank)
+            .filter(|&dim| dim != axis)
+            .map(|dim| dims.n_elements(dim))
+            .product();
+
+        Ok(Lanes {
+            data,
+            stride,
+            len,
+            current: 0,
+            n_lanes,
+        })
+    }
+
+    /// Returns the number of elements in each lane.
+    pub fn lane_len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'borrow, T> Iterator for Lanes<'borrow, T> {
+    type Item = Lane<'borrow, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current >= self.n_lanes {
+            return None;
+        }
+
+        let lane = self.current;
+        self.current += 1;
+
+        // The lanes that start in the same block of `stride * len` elements are adjacent.
+        let start = lane % self.stride + lane / self.stride * self.stride * self.len;
+        let data = self.data.get(start..).unwrap_or(&[]);
+        Some(data.iter().step_by(self.stride).take(self.len))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.n_lanes - self.current;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'borrow, T> ExactSizeIterator for Lanes<'borrow, T> {}
+
+impl<'borrow, T> FusedIterator for Lanes<'borrow, T> {}
+
+// Returns an iterator over the lanes along the first axis as slices.
+pub(crate) fn columns<'borrow, T, D: Dims>(
+    data: &'borrow [T],
+    dims: &D,
+) -> JlrsResult<slice::ChunksExact<'borrow, T>> {
+    let rank = dims.rank();
+    if rank == 0 {
+        Err(AccessError::OutOfBoundsAxis { axis: 0, rank })?;
+    }
+
+    // If the first dimension is empty, so is the data.
+    Ok(data.chunks_exact(dims.n_elements(0).max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{columns, IndexedIter, Indices, Lanes};
+    use crate::data::managed::array::dimensions::Dims;
+
+    #[test]
+    fn indices_column_major() {
+        let indices: Vec<_> = Indices::new(&(2, 3))
+            .map(|idx| idx.as_slice().to_vec())
+            .collect();
+
+        assert_eq!(
+            indices,
+            vec![
+                vec![0, 0],
+                vec![1, 0],
+                vec![0, 1],
+                vec![1, 1],
+                vec![0, 2],
+                vec![1, 2]
+            ]
+        );
+    }
+
+    #[test]
+    fn indices_match_linear_index() {
+        let dims = (3, 2, 4);
+        for (linear, idx) in Indices::new(&dims).enumerate() {
+            assert_eq!(dims.index_of(&idx).unwrap(), linear);
+        }
+    }
+
+    #[test]
+    fn indices_empty() {
+        assert_eq!(Indices::new(&(3, 0)).count(), 0);
+    }
+
+    #[test]
+    fn indices_zero_dimensional() {
+        let indices: Vec<_> = Indices::new(&()).collect();
+        assert_eq!(indices.len(), 1);
+        assert!(indices[0].as_slice().is_empty());
+    }
+
+    #[test]
+    fn indexed_iter() {
+        let data = [1, 2, 3, 4];
+        let mut iter = IndexedIter::new(&data, &(2, 2));
+        assert_eq!(iter.len(), 4);
+
+        let (idx, value) = iter.nth(2).unwrap();
+        assert_eq!(idx.as_slice(), &[0, 1]);
+        assert_eq!(*value, 3);
+    }
+
+    #[test]
+    fn lanes_of_matrix() {
+        let data = [1, 2, 3, 4, 5, 6];
+
+        let cols: Vec<Vec<_>> = Lanes::new(&data, &(2, 3), 0)
+            .unwrap()
+            .map(|lane| lane.copied().collect())
+            .collect();
+        assert_eq!(cols, vec![vec![1, 2], vec![3, 4], vec![5, 6]]);
+
+        let rows: Vec<Vec<_>> = Lanes::new(&data, &(2, 3), 1)
+            .unwrap()
+            .map(|lane| lane.copied().collect())
+            .collect();
+        assert_eq!(rows, vec![vec![1, 3, 5], vec![2, 4, 6]]);
+
+        assert!(Lanes::new(&data, &(2, 3), 2).is_err());
+    }
+
+    #[test]
+    fn lanes_of_3d_array() {
+        let data: Vec<usize> = (0..24).collect();
+        let dims = (2, 3, 4);
+
+        let lanes: Vec<Vec<_>> = Lanes::new(&data, &dims, 1)
+            .unwrap()
+            .map(|lane| lane.copied().collect())
+            .collect();
+
+        assert_eq!(lanes.len(), 8);
+        assert_eq!(lanes[0], vec![0, 2, 4]);
+        assert_eq!(lanes[1], vec![1, 3, 5]);
+        assert_eq!(lanes[2], vec![6, 8, 10]);
+        assert_eq!(lanes[7], vec![19, 21, 23]);
+    }
+
+    #[test]
+    fn columns_as_slices() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let cols: Vec<_> = columns(&data, &(3, 2)).unwrap().collect();
+        assert_eq!(cols, vec![&[1, 2, 3][..], &[4, 5, 6][..]]);
+
+        let empty: [u8; 0] = [];
+        assert_eq!(columns(&empty, &(0, 2)).unwrap().count(), 0);
+        assert!(columns(&data, &()).is_err());
+    }
+}
@@ -5,4 +5,6 @@
 //! submodules.
 
 pub mod accessor;
+pub mod chunks;
 pub mod copied;
+pub mod iter;
@@ -6,17 +6,33 @@
 //! and including four dimensions), and arrays and array slices of any number of dimensions. So,
 //! if you want to access the third column of the second row of an array, you can use both
 //! `[1, 2]` or `(1, 2)`. Note that unlike Julia, array indexing starts at 0.
+//!
+//! One-based indices, like the ones used by Julia's `CartesianIndex`, are represented by
+//! [`CartesianIndex`], which can be converted to and from zero-based indices.
 
 use std::{
     fmt::{Debug, Display, Formatter, Result as FmtResult},
     marker::PhantomData,
+    mem, slice,
 };
 
 use jl_sys::{jl_array_dims_ptr, jl_array_ndims};
 
 use crate::{
-    data::managed::{array::Array, private::ManagedPriv as _},
-    error::{AccessError, JlrsResult},
+    call::Call,
+    data::{
+        layout::valid_layout::ValidField,
+        managed::{
+            array::Array,
+            datatype::DataType,
+            private::ManagedPriv as _,
+            union_all::UnionAll,
+            value::{Value, ValueResult},
+        },
+    },
+    error::{AccessError, JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
+    inline_static_global,
+    memory::target::{unrooted::Unrooted, ExtendedTarget, Target},
     private::Private,
 };
 
@@ -376,9 +392,185 @@ impl Display for Dimensions {
     }
 }
 
+/// A one-based n-dimensional index, compatible with Julia's `CartesianIndex`.
+///
+/// Unlike the indices used by jlrs, indices in Julia start at 1. A `CartesianIndex` can't be used
+/// to index an array directly, it must be converted to a zero-based index with
+/// [`CartesianIndex::to_zero_based`] first.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CartesianIndex {
+    index: Box<[usize]>,
+}
+
+impl CartesianIndex {
+    /// Create a new `CartesianIndex` from a one-based index. Returns `None` if any of the
+    /// components of `index` is 0.
+    pub fn new(index: &[usize]) -> Option<Self> {
+        if index.contains(&0) {
+            return None;
+        }
+
+        Some(CartesianIndex {
+            index: index.into(),
+        })
+    }
+
+    /// Convert a zero-based index to a `CartesianIndex`.
+    pub fn from_zero_based<D: Dims>(index: &D) -> Self {
+        let index = (0..index.rank())
+            .map(|dim| index.n_elements(dim) + 1)
+            .collect();
+        CartesianIndex { index }
+    }
+
+    /// Convert a one-based linear index to the `CartesianIndex` of that element in an array with
+    /// dimensions `dims`, like `CartesianIndices(dims)[linear]` does in Julia. Returns `None` if
+    /// the linear index is out of bounds.
+    pub fn from_linear_index<D: Dims>(dims: &D, linear: usize) -> Option<Self> {
+        if linear == 0 || linear > dims.size() {
+            return None;
+        }
+
+        let mut rem = linear - 1;
+        let index = (0..dims.rank())
+            .map(|dim| {
+                let n = dims.n_elements(dim);
+                let i = rem % n;
+                rem /= n;
+                i + 1
+            })
+            .collect();
+
+        Some(CartesianIndex { index })
+    }
+
+    /// Convert this index to a zero-based index.
+    pub fn to_zero_based(&self) -> Dimensions {
+        let index: Box<[usize]> = self.index.iter().map(|i| i - 1).collect();
+        Dimensions::from_dims(&index.as_ref())
+    }
+
+    /// Returns the one-based linear index of this index in an array with dimensions `dims`, like
+    /// `LinearIndices(dims)[index]` does in Julia.
+    pub fn to_linear_index<D: Dims>(&self, dims: &D) -> JlrsResult<usize> {
+        Ok(dims.index_of(&self.to_zero_based())? + 1)
+    }
+
+    /// Returns the components of this index.
+    pub fn as_slice(&self) -> &[usize] {
+        &self.index
+    }
+
+    /// Returns the number of dimensions.
+    pub fn rank(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Convert a Julia `CartesianIndex` to a `CartesianIndex`.
+    ///
+    /// Returns an error if `value` is not a `CartesianIndex`, or if any of its components is
+    /// smaller than 1.
+    pub fn from_julia(value: Value) -> JlrsResult<Self> {
+        let ty = value.datatype();
+        if !is_cartesian_index(ty) {
+            Err(TypeError::NotA {
+                value: value.display_string_or(CANNOT_DISPLAY_TYPE),
+                field_type: "CartesianIndex".into(),
+            })?;
+        }
+
+        // Safety: a CartesianIndex{N} contains a single field of type NTuple{N, Int}, which is
+        // stored inline. This has been checked above.
+        let index = unsafe {
+            let rank = ty.size().unwrap_or(0) as usize / mem::size_of::<isize>();
+            slice::from_raw_parts(value.data_ptr().cast::<isize>().as_ptr(), rank)
+        };
+
+        // A CartesianIndex can contain any Int, but only positive indices are valid.
+        if index.iter().any(|&i| i < 1) {
+            Err(TypeError::NotA {
+                value: value.display_string_or(CANNOT_DISPLAY_TYPE),
+                field_type: "one-based index".into(),
+            })?;
+        }
+
+        let index = index.iter().map(|&i| i as usize).collect();
+        Ok(CartesianIndex { index })
+    }
+
+    /// Convert this index to a Julia `CartesianIndex`.
+    pub fn to_julia<'target, T>(
+        &self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> JlrsResult<ValueResult<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (output, scope) = target.split();
+        scope.scope(|mut frame| {
+            let func = inline_static_global!(CARTESIAN_INDEX, "Base.CartesianIndex", frame);
+
+            let mut args = Vec::with_capacity(self.rank());
+            for &i in self.index.iter() {
+                args.push(Value::new(&mut frame, i as isize));
+            }
+
+            // Safety: CartesianIndex is called with Int arguments.
+            unsafe { Ok(func.call(output, args)) }
+        })
+    }
+}
+
+// Returns `true` if `ty` is `Base.CartesianIndex{N}` and its field is an `NTuple{N, Int}`.
+fn is_cartesian_index(ty: DataType) -> bool {
+    // Safety: CartesianIndex is a UnionAll, so check if the typenames match. The field type and
+    // its parameters are only borrowed temporarily.
+    unsafe {
+        let unrooted = Unrooted::new();
+        let ua = inline_static_global!(
+            CARTESIAN_INDEX_TYPE,
+            UnionAll,
+            "Base.CartesianIndex",
+            unrooted
+        );
+
+        if ty.type_name() != ua.base_type().type_name() {
+            return false;
+        }
+
+        let field_ty = match ty.field_type(unrooted, 0) {
+            Some(field_ty) => field_ty.as_value(),
+            None => return false,
+        };
+
+        match field_ty.cast::<DataType>() {
+            Ok(field_ty) => field_ty
+                .parameters()
+                .data()
+                .as_slice()
+                .iter()
+                .all(|param| param.map_or(false, |param| isize::valid_field(param.as_value()))),
+            Err(_) => false,
+        }
+    }
+}
+
+impl Display for CartesianIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "CartesianIndex(")?;
+        for (n, i) in self.index.iter().enumerate() {
+            if n != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", i)?;
+        }
+        write!(f, ")")
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Dimensions, Dims};
+    use super::{CartesianIndex, Dimensions, Dims};
     #[test]
     fn convert_usize() {
         let d: Dimensions = 4.into_dimensions();
@@ -455,4 +647,30 @@ mod tests {
         assert_eq!(d.n_elements(2), 3);
         assert_eq!(d.size(), 6);
     }
+
+    #[test]
+    fn cartesian_index_zero_based() {
+        let idx = CartesianIndex::from_zero_based(&(0, 2, 1));
+        assert_eq!(idx.as_slice(), &[1, 3, 2]);
+        assert_eq!(idx.to_zero_based().as_slice(), &[0, 2, 1]);
+        assert!(CartesianIndex::new(&[1, 0]).is_none());
+        assert_eq!(
+            format!("{}", CartesianIndex::new(&[1, 2]).unwrap()),
+            "CartesianIndex(1, 2)"
+        );
+    }
+
+    #[test]
+    fn cartesian_index_linear() {
+        let dims = (2, 3);
+        let idx = CartesianIndex::from_linear_index(&dims, 4).unwrap();
+        assert_eq!(idx.as_slice(), &[2, 2]);
+        assert_eq!(idx.to_linear_index(&dims).unwrap(), 4);
+        assert!(CartesianIndex::from_linear_index(&dims, 0).is_none());
+        assert!(CartesianIndex::from_linear_index(&dims, 7).is_none());
+        assert!(CartesianIndex::new(&[3, 1])
+            .unwrap()
+            .to_linear_index(&dims)
+            .is_err());
+    }
 }
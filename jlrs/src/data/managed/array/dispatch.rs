@@ -0,0 +1,173 @@
+//! Dispatch on the element type of an untyped array.
+//!
+//! Functions that take an [`Array`] whose element type is unknown often need to handle several
+//! element types in the same way. Rather than checking each type manually with
+//! [`Array::contains`], you can use [`dispatch_array!`] to convert the array to a [`TypedArray`]
+//! with the correct element type and evaluate an expression with it:
+//!
+//! ```ignore
+//! let n_bytes = dispatch_array!(array, |typed| {
+//!     typed.dimensions().size() * typed.element_size()
+//! })?;
+//! ```
+//!
+//! The expression is expanded once for every supported element type, so it can call generic
+//! functions that are implemented for all of them:
+//!
+//! ```ignore
+//! fn sum<T: ValidField + Copy + std::iter::Sum>(array: TypedArray<T>) -> JlrsResult<f64>
+//! where
+//!     f64: From<T>,
+//! {
+//!     // ...
+//! }
+//!
+//! let total = dispatch_array!(array, [i8, i16, i32, u8, u16, u32, f32, f64], |typed| {
+//!     sum(typed)
+//! })??;
+//! ```
+//!
+//! By default the following element types are supported: `Int8` through `Int64`, `UInt8`
+//! through `UInt64`, `Float32`, `Float64`, `Bool`, `ComplexF32` and `ComplexF64`. If the `f16`
+//! feature is enabled `Float16` and `ComplexF16` are supported too. A custom set of element types
+//! can be provided as a list of layouts. If the element type is not supported,
+//! `TypeError::UnsupportedElementType` is returned.
+//!
+//! [`dispatch_array!`]: crate::dispatch_array
+
+use crate::{
+    data::{
+        layout::valid_layout::ValidField,
+        managed::{
+            array::{Array, TypedArray},
+            value::Value,
+            Managed,
+        },
+    },
+    error::{JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
+};
+
+/// Convert an [`Array`] to a [`TypedArray`] with the correct element type and evaluate an
+/// expression with it.
+///
+/// The first argument must evaluate to an `Array`. It can be followed by a list of layouts, if
+/// it's omitted the default set of numeric types is used. The last argument looks like a closure
+/// that takes a single argument, its body is evaluated with a `TypedArray<T>` where `T` is the
+/// first type in the list that is a valid layout for the element type of the array.
+///
+/// Unlike a closure, the body is evaluated in the enclosing function so `return` and `?` apply
+/// to that function. The macro evaluates to `JlrsResult<R>`, where `R` is the type of the body,
+/// `TypeError::UnsupportedElementType` is returned if none of the types are valid layouts for the
+/// element type.
+///
+/// See the [module-level docs] for examples.
+///
+/// [module-level docs]: crate::data::managed::array::dispatch
+#[macro_export]
+macro_rules! dispatch_array {
+    ($array:expr, [$($ty:ty),+ $(,)?], |$arg:ident| $body:expr) => {{
+        let array: $crate::data::managed::array::Array = $array;
+        let element_type = $crate::data::managed::array::Array::element_type(array);
+        $crate::__dispatch_array!(array, element_type, [$($ty),+], |$arg| $body)
+    }};
+    ($array:expr, |$arg:ident| $body:expr) => {
+        $crate::__dispatch_array_default!($array, |$arg| $body)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dispatch_array {
+    ($array:ident, $element_type:ident, [$ty:ty $(, $rest:ty)*], |$arg:ident| $body:expr) => {
+        if let ::std::option::Option::Some($arg) =
+            $crate::data::managed::array::dispatch::typed_if_valid::<$ty>($array, $element_type)
+        {
+            ::std::result::Result::Ok($body)
+        } else {
+            $crate::__dispatch_array!($array, $element_type, [$($rest),*], |$arg| $body)
+        }
+    };
+    ($array:ident, $element_type:ident, [], |$arg:ident| $body:expr) => {
+        $crate::data::managed::array::dispatch::unsupported_element_type($array)
+    };
+}
+
+#[cfg(not(feature = "f16"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dispatch_array_default {
+    ($array:expr, |$arg:ident| $body:expr) => {
+        $crate::dispatch_array!(
+            $array,
+            [
+                f64,
+                f32,
+                i64,
+                i32,
+                i16,
+                i8,
+                u64,
+                u32,
+                u16,
+                u8,
+                bool,
+                $crate::data::layout::complex::Complex<f64>,
+                $crate::data::layout::complex::Complex<f32>
+            ],
+            |$arg| $body
+        )
+    };
+}
+
+#[cfg(feature = "f16")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __dispatch_array_default {
+    ($array:expr, |$arg:ident| $body:expr) => {
+        $crate::dispatch_array!(
+            $array,
+            [
+                f64,
+                f32,
+                $crate::data::layout::f16::f16,
+                i64,
+                i32,
+                i16,
+                i8,
+                u64,
+                u32,
+                u16,
+                u8,
+                bool,
+                $crate::data::layout::complex::Complex<f64>,
+                $crate::data::layout::complex::Complex<f32>,
+                $crate::data::layout::complex::Complex<$crate::data::layout::f16::f16>
+            ],
+            |$arg| $body
+        )
+    };
+}
+
+// Used by `dispatch_array!`, returns the array as a `TypedArray<T>` if `T` is a valid layout for
+// its element type.
+#[doc(hidden)]
+#[inline]
+pub fn typed_if_valid<'scope, 'data, T: ValidField>(
+    array: Array<'scope, 'data>,
+    element_type: Value,
+) -> Option<TypedArray<'scope, 'data, T>> {
+    if T::valid_field(element_type) {
+        // Safety: T is a valid layout for the element type.
+        unsafe { Some(array.as_typed_unchecked()) }
+    } else {
+        None
+    }
+}
+
+// Used by `dispatch_array!` if none of the types is a valid layout for the element type.
+#[doc(hidden)]
+#[cold]
+pub fn unsupported_element_type<T>(array: Array) -> JlrsResult<T> {
+    let element_type = array.element_type().display_string_or(CANNOT_DISPLAY_TYPE);
+    Err(TypeError::UnsupportedElementType { element_type })?
+}
@@ -36,10 +36,11 @@ use jl_sys::{
     jl_apply_tuple_type_v, jl_array_data, jl_array_del_beg, jl_array_del_end, jl_array_dims_ptr,
     jl_array_eltype, jl_array_grow_beg, jl_array_grow_end, jl_array_ndims, jl_array_t,
     jl_datatype_t, jl_gc_add_ptr_finalizer, jl_new_array, jl_new_struct_uninit, jl_pchar_to_array,
-    jl_ptr_to_array, jl_ptr_to_array_1d, jl_reshape_array,
+    jl_ptr_to_array, jl_ptr_to_array_1d, jl_reshape_array, jl_value_t,
 };
 
 use self::{
+    buffer::{register_buffer, release_buffer, ArrayBuffer},
     data::accessor::{
         ArrayAccessor, BitsArrayAccessorI, BitsArrayAccessorMut, Immutable,
         IndeterminateArrayAccessor, IndeterminateArrayAccessorI, InlinePtrArrayAccessorI,
@@ -93,8 +94,13 @@ use crate::{
     private::Private,
 };
 
+pub mod bit_array;
+pub mod buffer;
 pub mod data;
 pub mod dimensions;
+pub mod dispatch;
+pub mod sparse;
+pub mod strided;
 pub mod tracked;
 
 /// An n-dimensional Julia array.
@@ -656,6 +662,127 @@ impl<'data> Array<'_, 'data> {
         })
     }
 
+    /// Create a new n-dimensional Julia array of dimensions `dims` that aliases the data of
+    /// `buffer`.
+    ///
+    /// The buffer is kept alive until the array is freed by the garbage collector, at which point
+    /// [`ArrayBuffer::release`] is called. This method can only be used in combination with
+    /// element types that implement `IntoJulia`. Because the data is allocated by Rust,
+    /// operations that can change the size of the array (e.g. `push!`) will fail.
+    ///
+    /// If the array size is too large, Julia will throw an error. This error is caught and
+    /// returned, and the buffer is dropped.
+    pub fn from_buffer<'target, B, D, S>(
+        target: ExtendedTarget<'target, '_, '_, S>,
+        mut buffer: B,
+        dims: D,
+    ) -> JlrsResult<ArrayResult<'target, 'static, S>>
+    where
+        B: ArrayBuffer,
+        B::Element: IntoJulia,
+        D: Dims,
+        S: Target<'target>,
+    {
+        if dims.size() != buffer.len() {
+            Err(InstantiationError::ArraySizeMismatch {
+                vec_size: buffer.len(),
+                dim_size: dims.size(),
+            })?;
+        }
+
+        let (output, scope) = target.split();
+        scope.scope(|mut frame| {
+            let elty_ptr = B::Element::julia_type(&frame).ptr().cast();
+            let data = buffer.as_mut_ptr().cast();
+
+            // Safety: The array type is rooted until the array has been constructed, all C API
+            // functions are called with valid data. The buffer is only registered if the array
+            // has been created successfully, it's kept alive until the array is finalized.
+            unsafe {
+                let mut callback =
+                    |frame: &mut GcFrame, result: &mut MaybeUninit<*mut jl_array_t>| {
+                        let array_type = jl_apply_array_type(elty_ptr.as_ptr(), dims.rank());
+                        let _: Value = frame
+                            .as_mut()
+                            .data_from_ptr(NonNull::new_unchecked(array_type), Private);
+
+                        let array = buffer_to_array(frame, array_type, data, &dims);
+                        result.write(array);
+                        Ok(())
+                    };
+
+                let res = match catch_exceptions_with_slots(&mut frame, &mut callback).unwrap() {
+                    Ok(array_ptr) => {
+                        let array = NonNull::new_unchecked(array_ptr);
+                        register_buffer(Array::wrap_non_null(array, Private), buffer);
+                        jl_gc_add_ptr_finalizer(
+                            get_tls(),
+                            array_ptr.cast(),
+                            release_buffer::<B> as *mut c_void,
+                        );
+                        Ok(array)
+                    }
+                    Err(e) => Err(e.ptr()),
+                };
+
+                Ok(output.result_from_ptr(res, Private))
+            }
+        })
+    }
+
+    /// Create a new n-dimensional Julia array of dimensions `dims` that aliases the data of
+    /// `buffer`.
+    ///
+    /// The buffer is kept alive until the array is freed by the garbage collector, at which point
+    /// [`ArrayBuffer::release`] is called. This method can only be used in combination with
+    /// element types that implement `IntoJulia`. Because the data is allocated by Rust,
+    /// operations that can change the size of the array (e.g. `push!`) will fail.
+    ///
+    /// Safety: If the array size is too large, Julia will throw an error. This error is not
+    /// caught, which is UB from a `ccall`ed function.
+    pub unsafe fn from_buffer_unchecked<'target, B, D, S>(
+        target: ExtendedTarget<'target, '_, '_, S>,
+        mut buffer: B,
+        dims: D,
+    ) -> JlrsResult<ArrayData<'target, 'static, S>>
+    where
+        B: ArrayBuffer,
+        B::Element: IntoJulia,
+        D: Dims,
+        S: Target<'target>,
+    {
+        if dims.size() != buffer.len() {
+            Err(InstantiationError::ArraySizeMismatch {
+                vec_size: buffer.len(),
+                dim_size: dims.size(),
+            })?;
+        }
+
+        let (output, scope) = target.split();
+        scope.scope(|mut frame| {
+            let array_type = jl_apply_array_type(
+                B::Element::julia_type(&frame).ptr().cast().as_ptr(),
+                dims.rank(),
+            );
+            let _: Value = frame
+                .as_mut()
+                .data_from_ptr(NonNull::new_unchecked(array_type), Private);
+
+            let data = buffer.as_mut_ptr().cast();
+            let array = buffer_to_array(&mut frame, array_type, data, &dims);
+            let array = NonNull::new_unchecked(array);
+
+            register_buffer(Array::wrap_non_null(array, Private), buffer);
+            jl_gc_add_ptr_finalizer(
+                get_tls(),
+                array.as_ptr().cast(),
+                release_buffer::<B> as *mut c_void,
+            );
+
+            Ok(output.data_from_ptr(array, Private))
+        })
+    }
+
     /// Convert a string to a Julia array.
     pub fn from_string<'target, A, T>(target: T, data: A) -> ArrayData<'target, 'static, T>
     where
@@ -1778,6 +1905,78 @@ where
             Ok(output.data_from_ptr(res.unwrap_non_null(Private), Private))
         })
     }
+
+    /// Create a new n-dimensional Julia array of dimensions `dims` that aliases the data of
+    /// `buffer`.
+    ///
+    /// The buffer is kept alive until the array is freed by the garbage collector, at which point
+    /// [`ArrayBuffer::release`] is called. Because the data is allocated by Rust, operations that
+    /// can change the size of the array (e.g. `push!`) will fail.
+    ///
+    /// If the array size is too large, Julia will throw an error. This error is caught and
+    /// returned, and the buffer is dropped.
+    pub fn from_buffer<'target, B, D, S>(
+        target: ExtendedTarget<'target, '_, '_, S>,
+        buffer: B,
+        dims: D,
+    ) -> JlrsResult<TypedArrayResult<'target, 'static, S, T>>
+    where
+        B: ArrayBuffer<Element = T>,
+        T: IntoJulia,
+        D: Dims,
+        S: Target<'target>,
+    {
+        unsafe {
+            let (output, frame) = target.split();
+            frame.scope(|mut frame| {
+                let global = frame.unrooted();
+                let target = frame.extended_target(global);
+
+                let res = match Array::from_buffer(target, buffer, dims)? {
+                    Ok(arr) => Ok(arr
+                        .as_managed()
+                        .as_typed_unchecked::<T>()
+                        .unwrap_non_null(Private)),
+                    Err(e) => Err(e.as_managed().unwrap_non_null(Private)),
+                };
+
+                Ok(output.result_from_ptr(res, Private))
+            })
+        }
+    }
+
+    /// Create a new n-dimensional Julia array of dimensions `dims` that aliases the data of
+    /// `buffer`.
+    ///
+    /// The buffer is kept alive until the array is freed by the garbage collector, at which point
+    /// [`ArrayBuffer::release`] is called. Because the data is allocated by Rust, operations that
+    /// can change the size of the array (e.g. `push!`) will fail.
+    ///
+    /// Safety: If the array size is too large, Julia will throw an error. This error is not
+    /// caught, which is UB from a `ccall`ed function.
+    pub unsafe fn from_buffer_unchecked<'target, B, D, S>(
+        target: ExtendedTarget<'target, '_, '_, S>,
+        buffer: B,
+        dims: D,
+    ) -> JlrsResult<TypedArrayData<'target, 'static, S, T>>
+    where
+        B: ArrayBuffer<Element = T>,
+        T: IntoJulia,
+        D: Dims,
+        S: Target<'target>,
+    {
+        let (output, frame) = target.split();
+        frame.scope(|mut frame| {
+            let inner_output = frame.unrooted();
+            let target = frame.extended_target(inner_output);
+
+            let res = Array::from_buffer_unchecked(target, buffer, dims)?
+                .as_managed()
+                .as_typed_unchecked::<T>();
+
+            Ok(output.data_from_ptr(res.unwrap_non_null(Private), Private))
+        })
+    }
 }
 
 impl<'data, T> TypedArray<'_, 'data, T>
@@ -2440,6 +2639,30 @@ where
     }
 }
 
+// Safety: `data` must point to `dims.size()` elements, the array doesn't take ownership of this
+// data.
+unsafe fn buffer_to_array<D>(
+    frame: &mut GcFrame,
+    array_type: *mut jl_value_t,
+    data: *mut c_void,
+    dims: &D,
+) -> *mut jl_array_t
+where
+    D: Dims,
+{
+    match dims.rank() {
+        1 => jl_ptr_to_array_1d(array_type, data, dims.n_elements(0), 0),
+        n if n <= 8 => {
+            let tuple = small_dim_tuple(frame, dims);
+            jl_ptr_to_array(array_type, data, tuple.unwrap(Private), 0)
+        }
+        _ => {
+            let tuple = large_dim_tuple(frame, dims);
+            jl_ptr_to_array(array_type, data, tuple.unwrap(Private), 0)
+        }
+    }
+}
+
 // Safety: must be used as a finalizer when moving array data from Rust to Julia
 // to ensure it's freed correctly.
 unsafe extern "C" fn droparray<T>(a: Array) {
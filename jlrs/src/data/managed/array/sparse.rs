@@ -0,0 +1,593 @@
+//! Managed type for `SparseMatrixCSC`.
+//!
+//! `SparseArrays.SparseMatrixCSC{Tv, Ti}` stores a sparse matrix in compressed sparse column
+//! format: the row indices and values of the stored elements of column `j` are found in
+//! `rowval[colptr[j]:colptr[j+1]-1]` and `nzval[colptr[j]:colptr[j+1]-1]`. All indices are
+//! one-based. The `SparseArrays` package must have been loaded to use this type.
+//!
+//! ```ignore
+//! Value::eval_string(&mut frame, "using SparseArrays").into_jlrs_result()?;
+//! let mat = Value::eval_string(&mut frame, "sparse([1, 2], [1, 3], [1.0, 2.0], 2, 3)")
+//!     .into_jlrs_result()?
+//!     .cast::<SparseMatrixCsc<f64, i64>>()?;
+//!
+//! assert_eq!(mat.n_rows(), 2);
+//! assert_eq!(mat.nnz(), 2);
+//! assert_eq!(mat.get(1, 2), Some(2.0));
+//! assert_eq!(mat.get(1, 1), None);
+//! ```
+//!
+//! A new matrix can be created from zero-based CSC buffers with [`SparseMatrixCsc::new`]. If
+//! the `sprs` feature is enabled, a `SparseMatrixCsc` can be converted to and from a `CsMat`
+//! from sprs.
+
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    marker::PhantomData,
+    ptr::NonNull,
+    slice,
+};
+
+use jl_sys::{jl_array_t, jl_value_t};
+
+use crate::{
+    call::Call,
+    convert::{
+        ccall_types::{CCallArg, CCallReturn},
+        into_jlrs_result::IntoJlrsResult,
+        into_julia::IntoJulia,
+    },
+    data::{
+        layout::valid_layout::ValidField,
+        managed::{
+            array::{Array, TypedArray},
+            datatype::DataType,
+            module::Module,
+            private::ManagedPriv,
+            union_all::UnionAll,
+            value::{Value, ValueData},
+            Managed, Ref,
+        },
+        static_data::StaticGlobal,
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    error::{AccessError, InstantiationError, JlrsResult},
+    memory::target::{target_type::TargetType, unrooted::Unrooted, ExtendedTarget, Target},
+    private::Private,
+};
+
+// The layout of a `SparseMatrixCSC`.
+#[repr(C)]
+struct SparseMatrixCscLayout {
+    m: isize,
+    n: isize,
+    colptr: NonNull<jl_array_t>,
+    rowval: NonNull<jl_array_t>,
+    nzval: NonNull<jl_array_t>,
+}
+
+/// Integer types that can be used as the index type of a [`SparseMatrixCsc`].
+pub trait SparseIndex: private::SparseIndexPriv {}
+impl<I: private::SparseIndexPriv> SparseIndex for I {}
+
+/// A Julia `SparseMatrixCSC{T, I}`.
+#[repr(transparent)]
+pub struct SparseMatrixCsc<'scope, 'data, T, I>(
+    NonNull<jl_value_t>,
+    PhantomData<&'scope ()>,
+    PhantomData<&'data mut ()>,
+    PhantomData<(T, I)>,
+);
+
+impl<'scope, 'data, T, I> SparseMatrixCsc<'scope, 'data, T, I>
+where
+    T: ValidField,
+    I: SparseIndex,
+{
+    /// Create a new `SparseMatrixCsc` with `n_rows` rows and `n_cols` columns from CSC buffers
+    /// with zero-based indices.
+    ///
+    /// `colptr` must contain `n_cols + 1` non-decreasing offsets that start at 0 and end at
+    /// `nzval.len()`, the row indices of each column in `rowval` must be strictly increasing and
+    /// smaller than `n_rows`. If these requirements aren't met, or an index doesn't fit in `I`,
+    /// `InstantiationError::InvalidSparseMatrix` is returned. If the `SparseArrays` package
+    /// hasn't been loaded, `AccessError::GlobalNotFound` is returned.
+    ///
+    /// If Julia throws an error while the matrix is created it's caught and returned.
+    pub fn new<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+        n_rows: usize,
+        n_cols: usize,
+        colptr: &[usize],
+        rowval: &[usize],
+        nzval: Vec<T>,
+    ) -> JlrsResult<SparseMatrixCscResult<'target, T, I, Tgt>>
+    where
+        T: IntoJulia,
+        Tgt: Target<'target>,
+    {
+        check_csc(n_rows, n_cols, colptr, rowval, nzval.len())
+            .map_err(|reason| InstantiationError::InvalidSparseMatrix { reason })?;
+        let colptr = to_one_based::<I>(colptr)?;
+        let rowval = to_one_based::<I>(rowval)?;
+
+        let (output, scope) = target.split();
+        scope.scope(|mut frame| {
+            let ty = match sparse_matrix_csc_type(&frame) {
+                Some(ty) => ty,
+                None => Err(AccessError::GlobalNotFound {
+                    name: "SparseArrays".into(),
+                    module: "Main".into(),
+                })?,
+            };
+
+            let n_colptr = colptr.len();
+            let n_nz = nzval.len();
+            let colptr = Array::from_vec(frame.as_extended_target(), colptr, n_colptr)?
+                .into_jlrs_result()?;
+            let rowval =
+                Array::from_vec(frame.as_extended_target(), rowval, n_nz)?.into_jlrs_result()?;
+            let nzval =
+                Array::from_vec(frame.as_extended_target(), nzval, n_nz)?.into_jlrs_result()?;
+            let m = Value::new(&mut frame, n_rows as isize);
+            let n = Value::new(&mut frame, n_cols as isize);
+
+            // Safety: the constructor is called with valid arguments, the result is a
+            // SparseMatrixCSC{T, I}.
+            unsafe {
+                let args = [m, n, colptr.as_value(), rowval.as_value(), nzval.as_value()];
+
+                let res = match ty.as_value().call(&mut frame, args) {
+                    Ok(mat) => Ok(mat.unwrap_non_null(Private)),
+                    Err(e) => Err(e.unwrap_non_null(Private)),
+                };
+
+                Ok(output.result_from_ptr(res, Private))
+            }
+        })
+    }
+
+    /// Returns the number of rows.
+    pub fn n_rows(self) -> usize {
+        self.layout().m as usize
+    }
+
+    /// Returns the number of columns.
+    pub fn n_cols(self) -> usize {
+        self.layout().n as usize
+    }
+
+    /// Returns the number of rows and columns.
+    pub fn dims(self) -> (usize, usize) {
+        (self.n_rows(), self.n_cols())
+    }
+
+    /// Returns the number of stored elements.
+    pub fn nnz(self) -> usize {
+        // Safety: colptr has n + 1 elements, the last one is nnz + 1.
+        unsafe {
+            self.colptr_slice()[self.n_cols()]
+                .to_usize()
+                .map_or(0, |idx| idx - 1)
+        }
+    }
+
+    /// Returns the `Vector{I}` with the one-based offsets of the columns.
+    pub fn colptr(self) -> TypedArray<'scope, 'data, I> {
+        // Safety: colptr is a Vector{I} that is reachable from this matrix.
+        unsafe { Array::wrap_non_null(self.layout().colptr, Private).as_typed_unchecked() }
+    }
+
+    /// Returns the `Vector{I}` with the one-based row indices of the stored elements.
+    pub fn rowval(self) -> TypedArray<'scope, 'data, I> {
+        // Safety: rowval is a Vector{I} that is reachable from this matrix.
+        unsafe { Array::wrap_non_null(self.layout().rowval, Private).as_typed_unchecked() }
+    }
+
+    /// Returns the `Vector{T}` with the stored elements.
+    pub fn nzval(self) -> TypedArray<'scope, 'data, T> {
+        // Safety: nzval is a Vector{T} that is reachable from this matrix.
+        unsafe { Array::wrap_non_null(self.layout().nzval, Private).as_typed_unchecked() }
+    }
+
+    /// Returns the one-based offsets of the columns as a slice.
+    ///
+    /// Safety: no mutable references to the offsets can exist while this slice is in use.
+    pub unsafe fn colptr_slice(&self) -> &[I] {
+        let (ptr, len) = vector_raw(self.layout().colptr);
+        slice::from_raw_parts(ptr, len)
+    }
+
+    /// Returns the one-based row indices of the stored elements as a slice.
+    ///
+    /// Safety: no mutable references to the row indices can exist while this slice is in use.
+    pub unsafe fn rowval_slice(&self) -> &[I] {
+        let (ptr, len) = vector_raw(self.layout().rowval);
+        slice::from_raw_parts(ptr, len)
+    }
+
+    /// Returns the stored elements as a slice.
+    ///
+    /// Safety: no mutable references to the stored elements can exist while this slice is in
+    /// use.
+    pub unsafe fn nzval_slice(&self) -> &[T] {
+        let (ptr, len) = vector_raw(self.layout().nzval);
+        slice::from_raw_parts(ptr, len)
+    }
+
+    /// Returns the stored elements as a mutable slice.
+    ///
+    /// Safety: no other references to the stored elements can exist while this slice is in use.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn nzval_mut(&self) -> &mut [T] {
+        let (ptr, len) = vector_raw(self.layout().nzval);
+        slice::from_raw_parts_mut(ptr, len)
+    }
+
+    /// Returns the element at the zero-based position `(row, col)`, or `None` if the position is
+    /// out of bounds or no element is stored at that position.
+    pub fn get(self, row: usize, col: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        if row >= self.n_rows() || col >= self.n_cols() {
+            return None;
+        }
+
+        // Safety: the data is only read.
+        unsafe {
+            let colptr = self.colptr_slice();
+            let start = colptr[col].to_usize()? - 1;
+            let end = colptr[col + 1].to_usize()? - 1;
+
+            let rows = &self.rowval_slice()[start..end];
+            let offset = rows
+                .binary_search_by_key(&(row + 1), |idx| idx.to_usize().unwrap_or(0))
+                .ok()?;
+
+            Some(self.nzval_slice()[start + offset].clone())
+        }
+    }
+
+    fn layout(&self) -> &SparseMatrixCscLayout {
+        // Safety: a SparseMatrixCSC has a fixed layout.
+        unsafe { self.0.cast().as_ref() }
+    }
+}
+
+#[cfg(feature = "sprs")]
+impl<'scope, 'data, T, I> SparseMatrixCsc<'scope, 'data, T, I>
+where
+    T: ValidField,
+    I: SparseIndex,
+{
+    /// Create a new `SparseMatrixCsc` with the content of `mat`.
+    ///
+    /// If `mat` is stored in CSR format it's converted to CSC first. See
+    /// [`SparseMatrixCsc::new`] for the errors that can be returned.
+    pub fn from_sprs<'target, J, Iptr, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+        mat: sprs::CsMatViewI<'_, T, J, Iptr>,
+    ) -> JlrsResult<SparseMatrixCscResult<'target, T, I, Tgt>>
+    where
+        T: IntoJulia + Clone,
+        J: sprs::SpIndex,
+        Iptr: sprs::SpIndex,
+        Tgt: Target<'target>,
+    {
+        let csc;
+        let mat = if mat.is_csc() {
+            mat
+        } else {
+            csc = mat.to_csc();
+            csc.view()
+        };
+
+        let colptr = mat
+            .indptr()
+            .to_proper()
+            .iter()
+            .map(|idx| idx.index())
+            .collect::<Vec<_>>();
+        let rowval = mat
+            .indices()
+            .iter()
+            .map(|idx| idx.index())
+            .collect::<Vec<_>>();
+
+        Self::new(
+            target,
+            mat.rows(),
+            mat.cols(),
+            &colptr,
+            &rowval,
+            mat.data().to_vec(),
+        )
+    }
+
+    /// Copy the content of this matrix to a `CsMat` in CSC format.
+    ///
+    /// If the structure of this matrix is invalid the `StructureError` returned by sprs is
+    /// converted to `JlrsError::Other`.
+    pub fn to_sprs(self) -> JlrsResult<sprs::CsMat<T>>
+    where
+        T: Clone,
+    {
+        // Safety: the data is only read.
+        let (colptr, rowval, nzval) = unsafe {
+            let colptr = from_one_based(self.colptr_slice())?;
+            let rowval = from_one_based(self.rowval_slice())?;
+            (colptr, rowval, self.nzval_slice().to_vec())
+        };
+
+        sprs::CsMat::try_new_csc(self.dims(), colptr, rowval, nzval)
+            .map_err(|(_, _, _, e)| crate::error::JlrsError::other(e).into())
+    }
+}
+
+unsafe impl<T: ValidField, I: SparseIndex> Typecheck for SparseMatrixCsc<'_, '_, T, I> {
+    fn typecheck(t: DataType) -> bool {
+        // Safety: SparseMatrixCSC is a UnionAll, so check if the typenames match. The
+        // parameters are only borrowed temporarily.
+        unsafe {
+            let unrooted = Unrooted::new();
+            let ua = match sparse_matrix_csc_type(&unrooted) {
+                Some(ua) => ua,
+                None => return false,
+            };
+
+            if t.type_name() != ua.base_type().type_name() {
+                return false;
+            }
+
+            let params = t.parameters().data().as_slice();
+            T::valid_field(params[0].unwrap().as_value())
+                && I::valid_field(params[1].unwrap().as_value())
+        }
+    }
+}
+
+impl<T: ValidField, I: SparseIndex> Debug for SparseMatrixCsc<'_, '_, T, I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.display_string() {
+            Ok(s) => write!(f, "{}", s),
+            Err(e) => write!(f, "<Cannot display value: {}>", e),
+        }
+    }
+}
+
+impl<T, I> Clone for SparseMatrixCsc<'_, '_, T, I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, I> Copy for SparseMatrixCsc<'_, '_, T, I> {}
+
+impl<'scope, 'data, T: ValidField, I: SparseIndex> ManagedPriv<'scope, 'data>
+    for SparseMatrixCsc<'scope, 'data, T, I>
+{
+    type Wraps = jl_value_t;
+    type TypeConstructorPriv<'target, 'da> = SparseMatrixCsc<'target, 'da, T, I>;
+    const NAME: &'static str = "SparseMatrixCSC";
+
+    // Safety: `inner` must not have been freed yet, the result must never be
+    // used after the GC might have freed it.
+    unsafe fn wrap_non_null(inner: NonNull<Self::Wraps>, _: Private) -> Self {
+        Self(inner, PhantomData, PhantomData, PhantomData)
+    }
+
+    #[inline(always)]
+    fn unwrap_non_null(self, _: Private) -> NonNull<Self::Wraps> {
+        self.0
+    }
+}
+
+unsafe impl<T: ConstructType, I: SparseIndex> ConstructType for SparseMatrixCsc<'_, '_, T, I> {
+    /// Panics if the `SparseArrays` package hasn't been loaded.
+    fn construct_type<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+    ) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        let (target, frame) = target.split();
+
+        frame
+            .scope(|mut frame| {
+                let ty_param = T::construct_type(frame.as_extended_target());
+                let idx_param = I::construct_type(frame.as_extended_target());
+                let params = [ty_param, idx_param];
+                unsafe {
+                    let ua = SPARSE_MATRIX_CSC_TYPE.get_or_init(&frame);
+                    Ok(ua.as_value().apply_type_unchecked(target, params))
+                }
+            })
+            .unwrap()
+    }
+
+    fn base_type<'target, Tgt>(target: &Tgt) -> Option<Value<'target, 'static>>
+    where
+        Tgt: Target<'target>,
+    {
+        sparse_matrix_csc_type(target).map(|ua| ua.as_value())
+    }
+}
+
+unsafe impl<'scope, 'data, T: ValidField + ConstructType, I: SparseIndex> CCallArg
+    for SparseMatrixCsc<'scope, 'data, T, I>
+{
+    type CCallArgType = Value<'scope, 'data>;
+    type FunctionArgType = Self;
+}
+
+unsafe impl<T: ValidField + ConstructType, I: SparseIndex> CCallReturn
+    for SparseMatrixCscRet<T, I>
+{
+    type CCallReturnType = Value<'static, 'static>;
+    type FunctionReturnType = SparseMatrixCsc<'static, 'static, T, I>;
+}
+
+/// A reference to a [`SparseMatrixCsc`] that has not been explicitly rooted.
+pub type SparseMatrixCscRef<'scope, 'data, T, I> =
+    Ref<'scope, 'data, SparseMatrixCsc<'scope, 'data, T, I>>;
+
+/// A [`SparseMatrixCscRef`] with static lifetimes. This is a useful shorthand for signatures of
+/// `ccall`able functions that return a [`SparseMatrixCsc`].
+pub type SparseMatrixCscRet<T, I> = Ref<'static, 'static, SparseMatrixCsc<'static, 'static, T, I>>;
+
+/// `SparseMatrixCsc` or `SparseMatrixCscRef`, depending on the target type `Tgt`.
+pub type SparseMatrixCscData<'target, T, I, Tgt> =
+    <Tgt as TargetType<'target>>::Data<'static, SparseMatrixCsc<'target, 'static, T, I>>;
+
+/// `JuliaResult<SparseMatrixCsc>` or `JuliaResultRef<SparseMatrixCscRef>`, depending on the
+/// target type `Tgt`.
+pub type SparseMatrixCscResult<'target, T, I, Tgt> =
+    <Tgt as TargetType<'target>>::Result<'static, SparseMatrixCsc<'target, 'static, T, I>>;
+
+static SPARSE_MATRIX_CSC_TYPE: StaticGlobal<UnionAll> =
+    StaticGlobal::new("SparseArrays.SparseMatrixCSC");
+
+// Returns `SparseArrays.SparseMatrixCSC`, or `None` if SparseArrays hasn't been loaded. The
+// package is only looked up until the type has been found, the type is cached afterwards.
+fn sparse_matrix_csc_type<'target, Tgt>(target: &Tgt) -> Option<UnionAll<'target>>
+where
+    Tgt: Target<'target>,
+{
+    if let Some(ua) = SPARSE_MATRIX_CSC_TYPE.get() {
+        return Some(ua);
+    }
+
+    Module::package_root_module(target, "SparseArrays")?;
+    Some(SPARSE_MATRIX_CSC_TYPE.get_or_init(target))
+}
+
+// Returns a pointer to the first element of a vector and its length.
+unsafe fn vector_raw<U>(vec: NonNull<jl_array_t>) -> (*mut U, usize) {
+    let arr = Array::wrap_non_null(vec, Private);
+    (arr.data_ptr().cast(), vec.as_ref().length)
+}
+
+// Checks if the zero-based CSC buffers describe a valid `n_rows × n_cols` matrix, returns the
+// reason why they don't otherwise.
+fn check_csc(
+    n_rows: usize,
+    n_cols: usize,
+    colptr: &[usize],
+    rowval: &[usize],
+    nnz: usize,
+) -> Result<(), String> {
+    if colptr.len() != n_cols + 1 {
+        return Err(format!(
+            "expected {} column offsets, got {}",
+            n_cols + 1,
+            colptr.len()
+        ));
+    }
+
+    if rowval.len() != nnz {
+        return Err(format!(
+            "got {} row indices for {} stored elements",
+            rowval.len(),
+            nnz
+        ));
+    }
+
+    if colptr[0] != 0 || colptr[n_cols] != nnz {
+        return Err(format!("column offsets must start at 0 and end at {}", nnz));
+    }
+
+    // All offsets must be valid before the row indices of a column can be sliced
+    for (col, offsets) in colptr.windows(2).enumerate() {
+        if offsets[0] > offsets[1] {
+            return Err(format!("the offset of column {} is decreasing", col));
+        }
+    }
+
+    for (col, offsets) in colptr.windows(2).enumerate() {
+        let rows = &rowval[offsets[0]..offsets[1]];
+        if rows.windows(2).any(|rows| rows[0] >= rows[1]) {
+            return Err(format!(
+                "the row indices of column {} are not strictly increasing",
+                col
+            ));
+        }
+
+        if let Some(row) = rows.iter().find(|&&row| row >= n_rows) {
+            return Err(format!(
+                "row index {} of column {} is out of bounds",
+                row, col
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Converts zero-based indices to one-based indices of type `I`.
+fn to_one_based<I: SparseIndex>(indices: &[usize]) -> JlrsResult<Vec<I>> {
+    let mut converted = Vec::with_capacity(indices.len());
+    for &idx in indices {
+        match I::from_usize(idx + 1) {
+            Some(idx) => converted.push(idx),
+            None => Err(InstantiationError::InvalidSparseMatrix {
+                reason: format!("index {} doesn't fit in the index type", idx),
+            })?,
+        }
+    }
+
+    Ok(converted)
+}
+
+// Converts one-based indices of type `I` to zero-based indices.
+#[cfg(feature = "sprs")]
+fn from_one_based<I: SparseIndex>(indices: &[I]) -> JlrsResult<Vec<usize>> {
+    let mut converted = Vec::with_capacity(indices.len());
+    for &idx in indices {
+        match idx.to_usize() {
+            Some(i) if i > 0 => converted.push(i - 1),
+            _ => Err(InstantiationError::InvalidSparseMatrix {
+                reason: format!("invalid index {:?}", idx),
+            })?,
+        }
+    }
+
+    Ok(converted)
+}
+
+mod private {
+    use std::{convert::TryFrom, fmt::Debug};
+
+    use crate::{
+        convert::into_julia::IntoJulia,
+        data::{layout::valid_layout::ValidField, types::construct_type::ConstructType},
+    };
+
+    pub trait SparseIndexPriv:
+        ValidField + IntoJulia + ConstructType + Copy + Debug + 'static
+    {
+        fn to_usize(self) -> Option<usize>;
+
+        fn from_usize(idx: usize) -> Option<Self>;
+    }
+
+    macro_rules! impl_sparse_index {
+        ($($ty:ty),+) => {
+            $(
+                impl SparseIndexPriv for $ty {
+                    #[inline]
+                    fn to_usize(self) -> Option<usize> {
+                        usize::try_from(self).ok()
+                    }
+
+                    #[inline]
+                    fn from_usize(idx: usize) -> Option<Self> {
+                        <$ty>::try_from(idx).ok()
+                    }
+                }
+            )+
+        };
+    }
+
+    impl_sparse_index!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+}
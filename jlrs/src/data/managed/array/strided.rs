@@ -0,0 +1,330 @@
+//! Managed types for strided views of Julia arrays.
+//!
+//! Many operations in Julia return a lazy wrapper around an existing array rather than a new
+//! [`Array`]. Taking a `view` of an array returns a `SubArray`, `reshape`ing a non-`Array` or
+//! reshaping a view returns a `ReshapedArray`, and `PermutedDimsArray` permutes the dimensions of
+//! its parent without copying it. This module provides [`SubArray`], [`ReshapedArray`] and
+//! [`PermutedDimsArray`] for these types.
+//!
+//! If the view is strided, i.e. its elements are stored at regular intervals in the dense array
+//! at the root of the view, the [`StridedLayout`] of the view can be computed and the elements
+//! can be accessed directly with a [`StridedArrayAccessor`] if the element type is an `isbits`
+//! type:
+//!
+//! ```ignore
+//! let arr = Value::eval_string(&mut frame, "view(reshape(collect(1.0:12.0), 3, 4), 2, 2:2:4)")
+//!     .into_jlrs_result()?
+//!     .cast::<SubArray>()?;
+//!
+//! let accessor = unsafe { arr.bits_data::<f64>(&mut frame)? };
+//! assert_eq!(accessor.dimensions().as_slice(), &[2]);
+//! assert_eq!(accessor.strides(), &[6]);
+//! assert_eq!(accessor[0], 5.0);
+//! assert_eq!(accessor[1], 11.0);
+//! ```
+//!
+//! [`StridedArrayAccessor`]: crate::data::managed::array::data::accessor::StridedArrayAccessor
+
+use std::{ffi::c_void, marker::PhantomData, ptr::NonNull, slice};
+
+use jl_sys::jl_value_t;
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::{
+        layout::valid_layout::ValidField,
+        managed::{
+            array::{
+                data::accessor::{StridedArrayAccessorI, StridedArrayAccessorMut},
+                dimensions::{Dimensions, Dims},
+                Array,
+            },
+            datatype::DataType,
+            private::ManagedPriv,
+            union_all::UnionAll,
+            value::Value,
+            Managed, Ref,
+        },
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    error::{AccessError, ArrayLayoutError, JlrsResult, CANNOT_DISPLAY_TYPE},
+    inline_static_global,
+    memory::target::{frame::GcFrame, unrooted::Unrooted, ExtendedTarget, Target},
+    private::Private,
+};
+
+/// The layout of a strided view of a dense Julia array.
+#[derive(Clone, Debug)]
+pub struct StridedLayout<'scope, 'data> {
+    array: Array<'scope, 'data>,
+    dims: Dimensions,
+    strides: Box<[isize]>,
+    offset: usize,
+}
+
+impl<'scope, 'data> StridedLayout<'scope, 'data> {
+    /// Returns the dense array at the root of the view, which owns the data.
+    pub fn array(&self) -> Array<'scope, 'data> {
+        self.array
+    }
+
+    /// Returns the dimensions of the view.
+    pub fn dimensions(&self) -> &Dimensions {
+        &self.dims
+    }
+
+    /// Returns the strides of the view in number of elements, strides can be negative.
+    pub fn strides(&self) -> &[isize] {
+        &self.strides
+    }
+
+    /// Returns the offset of the first element of the view in the data of the array.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    // Calculate the offset of the element at `index` relative to the first element of the view.
+    pub(crate) fn index_of<D: Dims>(&self, index: &D) -> JlrsResult<isize> {
+        let rank = self.dims.rank();
+        if rank != index.rank() {
+            Err(AccessError::InvalidIndex {
+                idx: index.into_dimensions(),
+                sz: self.dims.clone(),
+            })?;
+        }
+
+        let mut idx = 0;
+        for dim in 0..rank {
+            let i = index.n_elements(dim);
+            if self.dims.n_elements(dim) <= i {
+                Err(AccessError::InvalidIndex {
+                    idx: index.into_dimensions(),
+                    sz: self.dims.clone(),
+                })?;
+            }
+
+            idx += i as isize * self.strides[dim];
+        }
+
+        Ok(idx)
+    }
+}
+
+// Compute the layout of `view`, which must be a `SubArray`, `ReshapedArray` or
+// `PermutedDimsArray`. Julia throws an exception if the view isn't strided.
+unsafe fn strided_layout<'scope, 'data>(
+    view: Value<'scope, 'data>,
+    frame: &mut GcFrame,
+) -> JlrsResult<StridedLayout<'scope, 'data>> {
+    frame.scope(|mut frame| {
+        let parent_fn = inline_static_global!(PARENT, "Base.parent", frame);
+        let size_fn = inline_static_global!(SIZE, "Base.size", frame);
+        let strides_fn = inline_static_global!(STRIDES, "Base.strides", frame);
+        let pointer_fn = inline_static_global!(POINTER, "Base.pointer", frame);
+
+        let mut parent = view.as_value();
+        while !parent.is::<Array>() {
+            let ty = parent.datatype();
+            if !(ty.is::<SubArray>() || ty.is::<ReshapedArray>() || ty.is::<PermutedDimsArray>()) {
+                Err(ArrayLayoutError::NotStrided {
+                    value_type: view.datatype().display_string_or(CANNOT_DISPLAY_TYPE),
+                })?;
+            }
+
+            parent = parent_fn.call1(&mut frame, parent).into_jlrs_result()?;
+        }
+
+        // Safety: the array is reachable from the view, the parent of a view can't be changed.
+        let array = Array::wrap_non_null(parent.unwrap_non_null(Private).cast(), Private);
+
+        let size = size_fn.call1(&mut frame, view).into_jlrs_result()?;
+        let strides = strides_fn.call1(&mut frame, view).into_jlrs_result()?;
+        let pointer = pointer_fn.call1(&mut frame, view).into_jlrs_result()?;
+
+        // Both tuples are NTuple{N, Int}, the pointer is a Ptr{T}.
+        let rank = size.datatype().n_fields().unwrap() as usize;
+        let size = slice::from_raw_parts(size.data_ptr().cast::<usize>().as_ptr(), rank);
+        let strides = slice::from_raw_parts(strides.data_ptr().cast::<isize>().as_ptr(), rank);
+        let pointer = pointer.data_ptr().cast::<*mut c_void>().as_ptr().read();
+
+        // Zero-sized elements don't have a meaningful offset.
+        let offset = (pointer as usize - array.data_ptr() as usize)
+            .checked_div(array.element_size())
+            .unwrap_or(0);
+
+        Ok(StridedLayout {
+            array,
+            dims: Dimensions::from_dims(&size),
+            strides: strides.into(),
+            offset,
+        })
+    })
+}
+
+macro_rules! impl_strided_view {
+    ($(#[$meta:meta])* $name:ident, $ref:ident, $ret:ident, $path:literal) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone)]
+        #[repr(transparent)]
+        pub struct $name<'scope, 'data>(
+            NonNull<jl_value_t>,
+            PhantomData<&'scope ()>,
+            PhantomData<&'data mut ()>,
+        );
+
+        impl<'scope, 'data> $name<'scope, 'data> {
+            /// Returns the element type of this view.
+            pub fn element_type(self) -> Value<'scope, 'static> {
+                // Safety: the first type parameter of a view is its element type.
+                unsafe {
+                    let ty = self.as_value().datatype();
+                    ty.parameter(ty.unrooted_target(), 0).unwrap().as_managed()
+                }
+            }
+
+            /// Returns the rank of this view.
+            pub fn rank(self) -> usize {
+                // Safety: the second type parameter of a view is its rank.
+                unsafe {
+                    let ty = self.as_value().datatype();
+                    ty.parameter(ty.unrooted_target(), 1)
+                        .unwrap()
+                        .as_value()
+                        .unbox_unchecked::<isize>() as usize
+                }
+            }
+
+            /// Compute the strided layout of this view.
+            ///
+            /// Returns an error if the view isn't strided, or if it isn't a view of a dense array
+            /// through `SubArray`s, `ReshapedArray`s and `PermutedDimsArray`s.
+            pub fn strided_layout(self, frame: &mut GcFrame) -> JlrsResult<StridedLayout<'scope, 'data>> {
+                // Safety: only functions from Base are called.
+                unsafe { strided_layout(self.as_value(), frame) }
+            }
+
+            /// Immutably access the contents of this view. The elements must have an `isbits`
+            /// type that's compatible with `T`.
+            ///
+            /// Safety: no mutable references to the data of the array at the root of this view
+            /// can exist while the data is accessed.
+            pub unsafe fn bits_data<'borrow, T>(
+                &'borrow self,
+                frame: &mut GcFrame,
+            ) -> JlrsResult<StridedArrayAccessorI<'borrow, 'scope, 'data, T>>
+            where
+                T: ValidField,
+            {
+                let layout = self.strided_layout(frame)?;
+                layout.array().ensure_bits_containing::<T>()?;
+                Ok(StridedArrayAccessorI::new(layout))
+            }
+
+            /// Mutably access the contents of this view. The elements must have an `isbits`
+            /// type that's compatible with `T`.
+            ///
+            /// Safety: no other references to the data of the array at the root of this view can
+            /// exist while the data is accessed, this includes other views of the same array.
+            pub unsafe fn bits_data_mut<'borrow, T>(
+                &'borrow mut self,
+                frame: &mut GcFrame,
+            ) -> JlrsResult<StridedArrayAccessorMut<'borrow, 'scope, 'data, T>>
+            where
+                T: ValidField,
+            {
+                let layout = self.strided_layout(frame)?;
+                layout.array().ensure_bits_containing::<T>()?;
+                Ok(StridedArrayAccessorMut::new(layout))
+            }
+        }
+
+        unsafe impl Typecheck for $name<'_, '_> {
+            fn typecheck(t: DataType) -> bool {
+                // Safety: the view type is a UnionAll, so check if the typenames match
+                unsafe {
+                    let unrooted = Unrooted::new();
+                    let ua = inline_static_global!(VIEW_TYPE, UnionAll, $path, unrooted);
+                    t.type_name() == ua.base_type().type_name()
+                }
+            }
+        }
+
+        impl_debug!($name<'_, '_>);
+
+        impl<'scope, 'data> ManagedPriv<'scope, 'data> for $name<'scope, 'data> {
+            type Wraps = jl_value_t;
+            type TypeConstructorPriv<'target, 'da> = $name<'target, 'da>;
+            const NAME: &'static str = stringify!($name);
+
+            // Safety: `inner` must not have been freed yet, the result must never be
+            // used after the GC might have freed it.
+            unsafe fn wrap_non_null(inner: NonNull<Self::Wraps>, _: Private) -> Self {
+                Self(inner, PhantomData, PhantomData)
+            }
+
+            #[inline(always)]
+            fn unwrap_non_null(self, _: Private) -> NonNull<Self::Wraps> {
+                self.0
+            }
+        }
+
+        unsafe impl ConstructType for $name<'_, '_> {
+            fn construct_type<'target, T>(
+                target: ExtendedTarget<'target, '_, '_, T>,
+            ) -> crate::data::managed::value::ValueData<'target, 'static, T>
+            where
+                T: Target<'target>,
+            {
+                let (target, _) = target.split();
+                let ua = inline_static_global!(VIEW_TYPE, UnionAll, $path, target);
+                ua.as_value().root(target)
+            }
+
+            fn base_type<'target, Tgt>(target: &Tgt) -> Option<Value<'target, 'static>>
+            where
+                Tgt: Target<'target>,
+            {
+                let ua = inline_static_global!(VIEW_TYPE, UnionAll, $path, target);
+                Some(ua.as_value())
+            }
+        }
+
+        impl_ccall_arg_managed!($name, 2);
+
+        #[doc = concat!("A reference to a [`", stringify!($name), "`] that has not been explicitly rooted.")]
+        pub type $ref<'scope, 'data> = Ref<'scope, 'data, $name<'scope, 'data>>;
+
+        #[doc = concat!("A [`", stringify!($ref), "`] with static lifetimes. This is a useful shorthand for signatures of")]
+        #[doc = concat!("`ccall`able functions that return a [`", stringify!($name), "`].")]
+        pub type $ret = Ref<'static, 'static, $name<'static, 'static>>;
+    };
+}
+
+impl_strided_view!(
+    /// A view of a subset of the elements of an array, i.e. a `Base.SubArray`.
+    ///
+    /// A `SubArray` is strided if all its indices are integers or ranges.
+    SubArray,
+    SubArrayRef,
+    SubArrayRet,
+    "Base.SubArray"
+);
+
+impl_strided_view!(
+    /// An array with the same elements as its parent but different dimensions, i.e. a
+    /// `Base.ReshapedArray`.
+    ReshapedArray,
+    ReshapedArrayRef,
+    ReshapedArrayRet,
+    "Base.ReshapedArray"
+);
+
+impl_strided_view!(
+    /// An array whose dimensions are a permutation of the dimensions of its parent, i.e. a
+    /// `Base.PermutedDimsArray`.
+    PermutedDimsArray,
+    PermutedDimsArrayRef,
+    PermutedDimsArrayRet,
+    "Base.PermutedDimsArray"
+);
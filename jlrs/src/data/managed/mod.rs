@@ -185,6 +185,8 @@ pub mod function;
 pub mod internal;
 pub mod module;
 pub mod parachute;
+#[cfg(feature = "ccall")]
+pub mod rust_closure;
 pub mod rust_result;
 pub mod simple_vector;
 pub mod string;
@@ -30,14 +30,245 @@ use crate::{
     data::{
         layout::nothing::Nothing,
         managed::{
-            function::Function, private::ManagedPriv, symbol::Symbol, value::Value, Managed as _,
+            function::Function, private::ManagedPriv, string::JuliaString, symbol::Symbol,
+            value::Value, Managed as _,
         },
     },
     error::{AccessError, JlrsResult, TypeError},
     impl_julia_typecheck,
-    memory::target::Target,
+    memory::target::{ExtendedTarget, Target},
     private::Private,
 };
+#[cfg(feature = "ccall")]
+use crate::{
+    convert::into_jlrs_result::IntoJlrsResult, data::managed::value::ValueRet,
+    memory::target::frame::GcFrame,
+};
+
+// Returns the expressions that define the functions and docs described by the `JlrsModuleInfo`
+// returned by the initialization function of a module defined with `julia_module`, and the index
+// of the function pointer field of `JlrsFunctionInfo`. This generator is shared by
+// `Module::install_rust_module` and `Module::generate_rust_module_bindings`: `type_expr` and
+// `module_expr` return the expression used to refer to a type or module, `fptr_expr` returns the
+// expression that evaluates to the function pointer of the `idx`th function.
+#[cfg(feature = "ccall")]
+const RUST_MODULE_EXPRS: &str = "function (info, mod, type_expr, module_expr, fptr_expr)
+    functions, docs = getfield(info, 1), getfield(info, 2)
+
+    # The fields of `JlrsCore.Wrap.JlrsFunctionInfo` and `JlrsCore.Wrap.DocItem`, in order.
+    function_fields = (:name, :ccall_arg_types, :julia_arg_types, :ccall_ret_type, :julia_ret_type, :fptr, :mod, :is_async)
+    doc_fields = (:mod, :item, :signature, :doc)
+    fields(x, names) = NamedTuple{names}(ntuple(i -> getfield(x, i), length(names)))
+
+    # Removes line numbers, including those of macro calls, so the expressions print cleanly.
+    function strip_lines!(ex)
+        if ex isa Expr
+            ex.head === :macrocall && (ex.args[2] = nothing)
+            ex.head === :block && filter!(arg -> !(arg isa LineNumberNode), ex.args)
+            foreach(strip_lines!, ex.args)
+        end
+        ex
+    end
+
+    function_exprs = Expr[]
+    for (idx, func) in enumerate(functions)
+        f = fields(func, function_fields)
+        args = [Symbol(:arg, i) for i in 1:length(f.julia_arg_types)]
+        sig = [:($arg::$(type_expr(ty))) for (arg, ty) in zip(args, f.julia_arg_types)]
+        fptr = fptr_expr(idx, f.fptr)
+        ccall_ret = type_expr(f.ccall_ret_type)
+        ccall_types = Expr(:tuple, map(type_expr, f.ccall_arg_types)...)
+
+        body = if f.is_async
+            result_ty = type_expr(JlrsCore.RustResult{f.julia_ret_type})
+            quote
+                cond = Base.AsyncCondition()
+                res = GC.@preserve $(args...) begin
+                    call = ccall($fptr, $ccall_ret, $ccall_types, cond.handle, $(args...))
+                    wait(cond)
+                    ccall(call.join_func, $result_ty, (Ptr{Cvoid},), call.join_handle)
+                end
+                res.is_exc ? throw(res.data) : res.data
+            end
+        elseif f.ccall_ret_type <: JlrsCore.RustResult
+            quote
+                res = ccall($fptr, $ccall_ret, $ccall_types, $(args...))
+                res.is_exc ? throw(res.data) : res.data
+            end
+        else
+            quote
+                ccall($fptr, $ccall_ret, $ccall_types, $(args...))
+            end
+        end
+
+        fname = f.mod === mod ? f.name : Expr(:., module_expr(f.mod), QuoteNode(f.name))
+        push!(function_exprs, strip_lines!(Expr(:function, Expr(:call, fname, sig...), body)))
+    end
+
+    doc_exprs = Expr[]
+    for doc in docs
+        d = fields(doc, doc_fields)
+        dmod = module_expr(d.mod)
+        binding = :(Base.Docs.Binding($dmod, $(QuoteNode(d.item))))
+        push!(doc_exprs, strip_lines!(:(Base.Docs.doc!($dmod, $binding, Base.Docs.docstr($(d.doc)), $(type_expr(d.signature))))))
+    end
+
+    function_exprs, doc_exprs, findfirst(==(:fptr), function_fields)
+end";
+
+// Defines the functions and docs described by the `JlrsModuleInfo` returned by the
+// initialization function of a module defined with `julia_module`.
+#[cfg(feature = "ccall")]
+const INSTALL_RUST_MODULE: &str = "function (exprs, info, mod)
+    info === nothing && return nothing
+    function_exprs, doc_exprs, _ = exprs(info, mod, identity, identity, (idx, fptr) -> fptr)
+
+    for ex in function_exprs
+        Core.eval(mod, ex)
+    end
+
+    for ex in doc_exprs
+        Core.eval(mod, ex)
+    end
+
+    nothing
+end";
+
+// Generates the source code of a Julia module that defines the functions and docs described by
+// the `JlrsModuleInfo` returned by the initialization function of a module defined with
+// `julia_module`, and the keyword, future and property wrappers. The initialization function
+// must have been called with `precompiling` set to 2, in which case it returns the
+// `JlrsModuleInfo` and the expressions that define these wrappers instead of evaluating them.
+// Types, constants and globals are created by calling the initialization function when the
+// generated module is loaded.
+#[cfg(feature = "ccall")]
+const GENERATE_RUST_MODULE_BINDINGS: &str = r##"function (exprs, info, mod, library, init_fn, fingerprint)
+    info === nothing && error("the initialization function has already been called")
+    info, wrapper_exprs = info
+    imports = Set{Symbol}()
+
+    name_src(name) = Base.isidentifier(name) ? string(name) : string("var", repr(string(name)))
+
+    function mod_src(m)
+        m === mod && return "@__MODULE__"
+        path = String[]
+        parent = m
+        while parent !== mod && parentmodule(parent) !== parent
+            pushfirst!(path, name_src(nameof(parent)))
+            parent = parentmodule(parent)
+        end
+        parent === mod && return join(path, ".")
+
+        root = nameof(Base.moduleroot(m))
+        root in (:Core, :Base, :Main, :JlrsCore) || push!(imports, root)
+        string(m)
+    end
+
+    tvar_src(v) = v.ub === Any ? name_src(v.name) : string(name_src(v.name), "<:", type_src(v.ub))
+
+    function type_src(T)
+        if T isa DataType
+            tmod = T.name.module
+            base = tmod === mod ? name_src(T.name.name) : string(mod_src(tmod), ".", name_src(T.name.name))
+            isempty(T.parameters) && return base
+            params = [p isa Type || p isa TypeVar ? type_src(p) : repr(p) for p in T.parameters]
+            string(base, "{", join(params, ", "), "}")
+        elseif T === Union{}
+            "Union{}"
+        elseif T isa Union
+            string("Union{", join(map(type_src, Base.uniontypes(T)), ", "), "}")
+        elseif T isa UnionAll
+            string(type_src(T.body), " where ", tvar_src(T.var))
+        elseif T isa TypeVar
+            name_src(T.name)
+        else
+            repr(T)
+        end
+    end
+
+    # Removes line numbers and replaces types that have been interpolated into the wrappers with
+    # expressions that refer to them.
+    function clean!(ex)
+        if ex isa Expr
+            ex.head === :macrocall && (ex.args[2] = nothing)
+            filter!(arg -> !(arg isa LineNumberNode), ex.args)
+            for (i, arg) in enumerate(ex.args)
+                ex.args[i] = arg isa Type ? Meta.parse(type_src(arg)) : clean!(arg)
+            end
+        end
+        ex
+    end
+
+    function_exprs, doc_exprs, fptr_field = exprs(
+        info,
+        mod,
+        T -> Meta.parse(type_src(T)),
+        m -> Meta.parse(mod_src(m)),
+        (idx, _) -> :(FUNCTION_POINTERS[$idx]),
+    )
+
+    body = IOBuffer()
+    for ex in function_exprs
+        println(body, ex)
+        println(body)
+    end
+
+    for ex in wrapper_exprs
+        for def in (Meta.isexpr(ex, (:toplevel, :block)) ? ex.args : Any[ex])
+            def isa LineNumberNode && continue
+            println(body, clean!(def))
+            println(body)
+        end
+    end
+
+    for ex in doc_exprs
+        println(body, ex)
+    end
+
+    init_fn_sym = repr(Symbol(init_fn))
+    fingerprint_fn_sym = repr(Symbol(init_fn, "_abi_fingerprint"))
+
+    io = IOBuffer()
+    println(io, "# This file has been generated by jlrs, it should not be edited manually.")
+    println(io, "module ", name_src(nameof(mod)))
+    println(io)
+    println(io, "using JlrsCore")
+    for root in sort!(collect(imports))
+        println(io, "import ", name_src(root))
+    end
+    println(io)
+    println(io, "const LIBRARY = ", repr(library))
+    println(io, "const ABI_FINGERPRINT = ", repr(UInt64(fingerprint)))
+    println(io, "const FUNCTION_POINTERS = Ptr{Cvoid}[]")
+    println(io)
+    println(io, "function check_abi_fingerprint()")
+    println(io, "    fingerprint = ccall(($fingerprint_fn_sym, LIBRARY), UInt64, ())")
+    println(io, "    fingerprint == ABI_FINGERPRINT || error(\"the ABI fingerprint of \$(LIBRARY) doesn't match these bindings, they must be regenerated\")")
+    println(io, "end")
+    println(io)
+    println(io, "function set_function_pointers!(info)")
+    println(io, "    info === nothing && return")
+    println(io, "    info isa Tuple && (info = first(info))")
+    println(io, "    empty!(FUNCTION_POINTERS)")
+    println(io, "    for func in getfield(info, 1)")
+    println(io, "        push!(FUNCTION_POINTERS, getfield(func, $fptr_field))")
+    println(io, "    end")
+    println(io, "end")
+    println(io)
+    println(io, "# Creates the exported types, constants and globals, the wrappers are defined below.")
+    println(io, "check_abi_fingerprint()")
+    println(io, "set_function_pointers!(ccall(($init_fn_sym, LIBRARY), Any, (Any, UInt8), @__MODULE__, 2))")
+    println(io)
+    println(io, "function __init__()")
+    println(io, "    check_abi_fingerprint()")
+    println(io, "    set_function_pointers!(ccall(($init_fn_sym, LIBRARY), Any, (Any, UInt8), @__MODULE__, 0))")
+    println(io, "end")
+    println(io)
+    write(io, take!(body))
+    println(io, "end")
+
+    String(take!(io))
+end"##;
 
 /// Functionality in Julia can be accessed through its module system. You can get a handle to the
 /// three standard modules, `Main`, `Base`, and `Core` and access their submodules through them.
@@ -382,6 +613,97 @@ impl<'scope> Module<'scope> {
         }
     }
 
+    /// Install the content of a module defined with the `julia_module` macro in this module.
+    ///
+    /// `julia_module` generates an initialization function that is normally called by the
+    /// `@wrapmodule` macro from `JlrsCore.Wrap` after loading the library that exports it. This
+    /// method calls that function directly and defines the exported functions and their
+    /// documentation, which lets you use a module defined in the same binary that embeds Julia.
+    /// The initialization function of a module can only be installed once, if it's installed
+    /// again nothing happens and `nothing` is returned.
+    ///
+    /// Safety: the content of the module is defined by evaluating Julia code, the functions that
+    /// are exported must be safe to call from Julia.
+    #[cfg(feature = "ccall")]
+    pub unsafe fn install_rust_module<'target, T>(
+        self,
+        target: ExtendedTarget<'target, '_, '_, T>,
+        init_fn: unsafe extern "C" fn(Module, u8) -> ValueRet,
+    ) -> JlrsResult<ValueResult<'target, 'static, T>>
+    where
+        T: Target<'target>,
+    {
+        let (output, scope) = target.split();
+        scope.scope(|mut frame| {
+            let module_info = init_fn(self, 1).root(&mut frame);
+            let exprs = Value::eval_string(&mut frame, RUST_MODULE_EXPRS).into_jlrs_result()?;
+            let install = Value::eval_string(&mut frame, INSTALL_RUST_MODULE).into_jlrs_result()?;
+            Ok(install.call3(output, exprs, module_info, self.as_value()))
+        })
+    }
+
+    /// Generate the source code of a Julia module that defines the content of a module defined
+    /// with the `julia_module` macro.
+    ///
+    /// Unlike `@wrapmodule`, which generates the exported functions when the library is loaded,
+    /// the generated module defines these functions and their documentation statically. The
+    /// exported types, constants and globals are still created by calling the initialization
+    /// function `init_fn_name` in `library_path`. `julia_module` also generates a function named
+    /// `<init_fn_name>_abi_fingerprint` which returns a fingerprint of the module. `fingerprint`
+    /// must be the value returned by this function, the generated module checks that the
+    /// fingerprint of the loaded library matches this value when it's loaded and throws an
+    /// exception if it doesn't.
+    ///
+    /// The keyword, future and property wrappers that `julia_module` generates are defined
+    /// statically as well, the initialization function is called with `precompiling` set to 2
+    /// which makes it return the expressions that define them instead of evaluating them.
+    ///
+    /// The exported types can't be defined statically because their layouts and super-types are
+    /// created by Rust, so the generated file isn't a complete definition of the module without
+    /// the library. [`write_rust_module_bindings`] can be used to write the bindings to a file
+    /// from a build script or binary.
+    ///
+    /// The generated module is named after this module, which should be empty. `init_fn` is
+    /// called to initialize this module, so it must not have been called before.
+    ///
+    /// [`write_rust_module_bindings`]: crate::ccall::write_rust_module_bindings
+    ///
+    /// Safety: `init_fn` and `init_fn_name` must refer to the same initialization function.
+    #[cfg(feature = "ccall")]
+    pub unsafe fn generate_rust_module_bindings(
+        self,
+        frame: &mut GcFrame,
+        library_path: &str,
+        init_fn_name: &str,
+        init_fn: unsafe extern "C" fn(Module, u8) -> ValueRet,
+        fingerprint: u64,
+    ) -> JlrsResult<String> {
+        frame.scope(|mut frame| {
+            let module_info = init_fn(self, 2).root(&mut frame);
+            let library_path = JuliaString::new(&mut frame, library_path).as_value();
+            let init_fn_name = JuliaString::new(&mut frame, init_fn_name).as_value();
+            let fingerprint = Value::new(&mut frame, fingerprint);
+            let exprs = Value::eval_string(&mut frame, RUST_MODULE_EXPRS).into_jlrs_result()?;
+
+            let bindings = Value::eval_string(&mut frame, GENERATE_RUST_MODULE_BINDINGS)
+                .into_jlrs_result()?
+                .call(
+                    &mut frame,
+                    [
+                        exprs,
+                        module_info,
+                        self.as_value(),
+                        library_path,
+                        init_fn_name,
+                        fingerprint,
+                    ],
+                )
+                .into_jlrs_result()?;
+
+            Ok(bindings.cast::<JuliaString>()?.as_str()?.to_string())
+        })
+    }
+
     /// Load a module by calling `Base.require` and return this module if it has been loaded
     /// successfully. This method can be used to load parts of the standard library like
     /// `LinearAlgebra`. This requires one slot on the GC stack. Note that the loaded module is
@@ -0,0 +1,159 @@
+//! Managed type for Rust closures that can be called from Julia.
+//!
+//! Many Julia functions take another function as an argument, e.g. `map`, or `sort!` with the
+//! `by` keyword argument. A Rust closure can be used as such an argument by wrapping it in a
+//! [`RustClosure`]. An instance of this type is a callable Julia object, when it's called the
+//! arguments are passed to the closure as a slice of `Value`s.
+//!
+//! ```ignore
+//! let closure = RustClosure::new(frame.as_extended_target(), |frame, args| {
+//!     let x = args[0].unbox::<f64>()?;
+//!     Ok(Value::new(frame, 2.0 * x).leak())
+//! })?;
+//! ```
+//!
+//! Because the closure is an `FnMut`, it can't be called while it's already being called. When a
+//! `RustClosure` is called it's tracked exclusively, if it's already tracked a
+//! `JlrsCore.BorrowError` is thrown.
+//!
+//! The closure must not capture any references to Julia data. This module is only available if
+//! the `ccall` feature is enabled.
+
+use std::{ffi::c_void, ptr::NonNull};
+
+use jl_sys::jl_tagged_gensym;
+use once_cell::sync::OnceCell;
+
+use super::{
+    private::ManagedPriv,
+    rust_result::{RustResult, RustResultRet},
+    symbol::Symbol,
+    value::{
+        typed::{TypedValue, TypedValueData},
+        Value, ValueRet,
+    },
+    Managed,
+};
+use crate::{
+    call::Call,
+    ccall::CCall,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::types::{abstract_types::AnyType, foreign_type::OpaqueType},
+    error::JlrsResult,
+    memory::target::{frame::GcFrame, ExtendedTarget, Target},
+    private::Private,
+};
+
+// Defines the method that makes instances of the closure type callable. The result of the
+// `ccall` is a `RustResult{Any}`, if it contains an exception that exception is thrown.
+const CALL_METHOD_GENERATOR: &str = "(T, fptr) -> @eval function (f::$T)(args...)
+    res = ccall($fptr, Any, (Any, Any), f, args)
+    res.is_exc ? throw(res.data) : res.data
+end";
+
+// Set when the closure type has been created successfully.
+static CLOSURE_TYPE: OnceCell<()> = OnceCell::new();
+
+/// Trait implemented by closures that can be wrapped in a [`RustClosure`].
+pub trait RustFn:
+    'static
+    + Send
+    + for<'scope> FnMut(&mut GcFrame<'scope>, &[Value<'scope, 'static>]) -> JlrsResult<ValueRet>
+{
+}
+
+impl<F> RustFn for F where
+    F: 'static
+        + Send
+        + for<'scope> FnMut(&mut GcFrame<'scope>, &[Value<'scope, 'static>]) -> JlrsResult<ValueRet>
+{
+}
+
+/// A Rust closure that can be called from Julia.
+pub struct RustClosure {
+    func: Box<dyn RustFn>,
+}
+
+impl RustClosure {
+    /// Wrap `func` in a callable Julia object.
+    ///
+    /// The type of this object is created in the `Main` module when this function is called for
+    /// the first time, an error is returned if this type can't be created.
+    pub fn new<'target, F, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+        func: F,
+    ) -> JlrsResult<TypedValueData<'target, 'static, RustClosure, T>>
+    where
+        F: RustFn,
+        T: Target<'target>,
+    {
+        let (target, frame) = target.split();
+
+        frame.scope(|mut frame| {
+            unsafe { init_closure_type(&mut frame)? };
+            Ok(TypedValue::new(
+                target,
+                RustClosure {
+                    func: Box::new(func),
+                },
+            ))
+        })
+    }
+}
+
+// The closure can't capture references to Julia data, so the type doesn't need to be marked.
+unsafe impl OpaqueType for RustClosure {}
+
+// Creates the closure type and its call method if they haven't been created yet.
+unsafe fn init_closure_type(frame: &mut GcFrame) -> JlrsResult<()> {
+    CLOSURE_TYPE
+        .get_or_try_init(|| {
+            frame.scope(|mut frame| {
+                let name = "RustClosure";
+                let sym = jl_tagged_gensym(name.as_ptr().cast(), name.len());
+                let sym = Symbol::wrap_non_null(NonNull::new_unchecked(sym), Private);
+
+                let module = super::module::Module::main(&frame);
+                let ty = <RustClosure as OpaqueType>::create_type(&mut frame, sym, module);
+                module.set_const_unchecked(sym, ty.as_value());
+
+                let fptr = Value::new(&mut frame, call_rust_closure as *mut c_void);
+                Value::eval_string(&mut frame, CALL_METHOD_GENERATOR)
+                    .into_jlrs_result()?
+                    .call2(&mut frame, ty.as_value(), fptr)
+                    .into_jlrs_result()?;
+
+                Ok(())
+            })
+        })
+        .map(|_| ())
+}
+
+unsafe extern "C" fn call_rust_closure(
+    mut closure: TypedValue<'static, 'static, RustClosure>,
+    args: Value<'static, 'static>,
+) -> RustResultRet<AnyType> {
+    CCall::invoke(|mut frame| {
+        let mut tracked = match closure.track_exclusive() {
+            Ok(tracked) => tracked,
+            Err(_) => return RustResult::borrow_error(frame.as_extended_target()).leak(),
+        };
+
+        let n_args = args.n_fields();
+        let mut values = Vec::with_capacity(n_args);
+        for i in 0..n_args {
+            match args.get_nth_field(&mut frame, i) {
+                Ok(value) => values.push(value),
+                Err(e) => return RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+            }
+        }
+
+        match (tracked.func)(&mut frame, &values) {
+            Ok(res) => {
+                let res = res.root(&mut frame).as_typed_unchecked::<AnyType>();
+                RustResult::ok(frame.as_extended_target(), res).leak()
+            }
+            Err(e) => RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+        }
+    })
+}
@@ -14,7 +14,7 @@ use jlrs_macros::julia_version;
 use super::{Value, ValueRef};
 use crate::{
     data::{
-        layout::valid_layout::ValidLayout,
+        layout::{union::UnionEnum, valid_layout::ValidLayout},
         managed::{
             array::Array,
             datatype::{DataType, DataTypeRef},
@@ -148,6 +148,55 @@ impl<'scope, 'data> FieldAccessor<'scope, 'data> {
         }
     }
 
+    /// Access the field the accessor is currently pointing to as a variant of `E`.
+    ///
+    /// The concrete type of the field must be the type of the data of one of the variants of
+    /// `E`. This can be used to access a field whose type is a bits-union of these types.
+    pub fn access_enum<E: UnionEnum>(self) -> JlrsResult<E> {
+        if self.current_field_type.is_none() {
+            Err(AccessError::UndefRef)?;
+        }
+
+        if self.value.is_none() {
+            Err(AccessError::UndefRef)?;
+        }
+
+        // Safety: the data is read from the same location as `access` reads it from, E checks
+        // the type of the data before reading it.
+        unsafe {
+            let ty = self.current_field_type.unwrap().as_managed();
+
+            let ptr: *const u8 = match self.state {
+                #[cfg(not(feature = "julia-1-6"))]
+                ViewState::AtomicBuffer => {
+                    self.buffer.bytes[self.offset as usize..].as_ptr().cast()
+                }
+                ViewState::Array => self
+                    .value
+                    .unwrap()
+                    .as_value()
+                    .cast_unchecked::<Array>()
+                    .data_ptr()
+                    .cast::<u8>()
+                    .add(self.offset as usize),
+                _ => self
+                    .value
+                    .unwrap()
+                    .ptr()
+                    .cast::<u8>()
+                    .as_ptr()
+                    .add(self.offset as usize),
+            };
+
+            if let Some(value) = E::read_variant(ty, ptr) {
+                return Ok(value);
+            }
+
+            let value_type = ty.display_string_or(CANNOT_DISPLAY_TYPE).into();
+            Err(AccessError::InvalidLayout { value_type })?
+        }
+    }
+
     /// Returns `true` if `self.access::<T>()` will succeed, `false` if it will fail.
     pub fn can_access_as<T: ValidLayout>(&self) -> bool {
         if self.current_field_type.is_none() {
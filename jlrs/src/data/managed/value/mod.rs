@@ -684,9 +684,14 @@ impl<'scope, 'data> Value<'scope, 'data> {
     }
 
     /// Unbox the contents of the value as the output type associated with `T`. Returns an error
-    /// if the layout of `T::Output` is incompatible with the layout of the type in Julia.
+    /// if the layout of `T::Output` is incompatible with the layout of the type in Julia. If `T`
+    /// is a derived layout, this error describes the first field whose layout is incompatible.
     pub fn unbox<T: Unbox + Typecheck>(self) -> JlrsResult<T::Output> {
         if !self.is::<T>() {
+            if let Some(mismatch) = T::mismatch(self.datatype()) {
+                Err(AccessError::LayoutMismatch(mismatch))?;
+            }
+
             Err(AccessError::InvalidLayout {
                 value_type: self.datatype().display_string_or(CANNOT_DISPLAY_TYPE),
             })?;
@@ -80,6 +80,11 @@ where
         }
     }
 
+    /// Get the global data if it has already been looked up.
+    pub fn get(&self) -> Option<T> {
+        unsafe { self.global.get().map(|global| global.0.cast_unchecked()) }
+    }
+
     /// Get the global data, look it up if it doesn't exist yet.
     ///
     /// The global must exist and be an instance of `T`. Otherwise this method will panic.
@@ -0,0 +1,395 @@
+//! Export Rust types as subtypes of `AbstractArray`.
+//!
+//! An opaque type that implements [`ForeignArray`] can be exported as an array type with the
+//! `julia_module` macro by annotating it with `#[jlrs(array)]`. Its super-type must be
+//! `AbstractArray{T, N}`, where `T` is the Julia type of the elements and `N` the rank of the
+//! array. This super-type can be created with [`abstract_array_super_type`]:
+//!
+//! ```ignore
+//! struct Chunked {
+//!     chunks: Vec<Vec<f64>>,
+//!     chunk_len: usize,
+//! }
+//!
+//! unsafe impl OpaqueType for Chunked {
+//!     fn super_type<'target, Tgt>(target: Tgt) -> DataTypeData<'target, Tgt>
+//!     where
+//!         Tgt: Target<'target>,
+//!     {
+//!         abstract_array_super_type::<Self, Tgt>(target)
+//!     }
+//! }
+//!
+//! impl ForeignArray for Chunked {
+//!     type Elem = f64;
+//!     const RANK: usize = 1;
+//!     const LINEAR_INDEXING: bool = true;
+//!
+//!     fn size(&self) -> Vec<usize> {
+//!         vec![self.chunks.len() * self.chunk_len]
+//!     }
+//!
+//!     fn get(&self, index: &[usize]) -> f64 {
+//!         self.chunks[index[0] / self.chunk_len][index[0] % self.chunk_len]
+//!     }
+//! }
+//!
+//! julia_module! {
+//!     become module_jl_init;
+//!
+//!     #[jlrs(array)]
+//!     struct Chunked;
+//! }
+//! ```
+//!
+//! This generates methods for `Base.size`, `Base.getindex`, `Base.setindex!` and
+//! `Base.IndexStyle`. If the data is stored in a strided layout, `#[jlrs(array, strided)]` can be
+//! used to generate methods for `Base.strides`, `Base.elsize` and `Base.unsafe_convert` too, in
+//! this case `ForeignArray::strides` and `ForeignArray::data_ptr` must be implemented. The array
+//! is tracked while it's accessed. The functions in this module are called by the code generated
+//! by `julia_module`.
+
+use std::ptr::null_mut;
+
+use super::{
+    abstract_types::AnyType,
+    foreign_methods::{define_methods, symbol_or_nothing, track_or_return},
+    foreign_type::OpaqueType,
+    typecheck::Typecheck,
+};
+use crate::{
+    call::Call,
+    ccall::CCall,
+    convert::{into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia, unbox::Unbox},
+    data::{
+        layout::nothing::Nothing,
+        managed::{
+            datatype::{DataType, DataTypeData},
+            module::Module,
+            rust_result::{RustResult, RustResultRet},
+            union_all::UnionAll,
+            value::{typed::TypedValue, Value},
+            Managed,
+        },
+    },
+    error::{JlrsError, JlrsResult},
+    memory::{
+        stack_frame::StackFrame,
+        target::{frame::GcFrame, Target},
+    },
+};
+
+// Defines the array methods for the array type `T` with element type `E` and rank `N`. The
+// remaining arguments are the names of the generated functions that access the array, `strides`
+// and `ptr` are `nothing` if the array isn't strided. Bounds are checked by `get` and `set`
+// rather than with `@boundscheck` so they're checked even if the caller uses `@inbounds`.
+const ARRAY_METHODS_GENERATOR: &str =
+    "(mod, T, E, N, linear, size, get, set, strides, ptr) -> begin
+    T <: AbstractArray{E, N} || error(\"$T is not a subtype of AbstractArray{$E, $N}\")
+    sizeref, getref, setref = GlobalRef(mod, size), GlobalRef(mod, get), GlobalRef(mod, set)
+    Core.eval(mod, :(Base.size(a::$T) = $sizeref(a)::NTuple{$N, Int}))
+
+    if linear
+        Core.eval(mod, quote
+            Base.IndexStyle(::Type{<:$T}) = Base.IndexLinear()
+            Base.getindex(a::$T, i::Int) = $getref(a, (i,))::$E
+            function Base.setindex!(a::$T, v, i::Int)
+                $setref(a, Base.convert($E, v), (i,))
+                a
+            end
+        end)
+    else
+        Core.eval(mod, quote
+            Base.IndexStyle(::Type{<:$T}) = Base.IndexCartesian()
+            Base.getindex(a::$T, I::Vararg{Int, $N}) = $getref(a, I)::$E
+            function Base.setindex!(a::$T, v, I::Vararg{Int, $N})
+                $setref(a, Base.convert($E, v), I)
+                a
+            end
+        end)
+    end
+
+    if strides !== nothing
+        stridesref, ptrref = GlobalRef(mod, strides), GlobalRef(mod, ptr)
+        Core.eval(mod, quote
+            Base.strides(a::$T) = $stridesref(a)::NTuple{$N, Int}
+            Base.elsize(::Type{<:$T}) = Base.sizeof($E)
+            Base.unsafe_convert(::Type{Ptr{$E}}, a::$T) = Ptr{$E}($ptrref(a))
+        end)
+    end
+
+    nothing
+end";
+
+/// Trait implemented by opaque types that are exported as subtypes of `AbstractArray`.
+///
+/// All indices are 0-based and have been checked to be in bounds of the size returned by
+/// `ForeignArray::size`, a `BoundsError` is thrown if they aren't. If `LINEAR_INDEXING` is `true`
+/// the index contains a single linear index, otherwise it contains one index for each dimension.
+pub trait ForeignArray: OpaqueType {
+    /// The type of the elements.
+    type Elem: IntoJulia + Unbox<Output = Self::Elem> + Typecheck;
+
+    /// The rank of the array.
+    const RANK: usize;
+
+    /// Whether the array should be indexed with linear indices (`IndexLinear`) or with
+    /// cartesian indices (`IndexCartesian`), the latter is the default.
+    const LINEAR_INDEXING: bool = false;
+
+    /// Returns the size of each dimension.
+    fn size(&self) -> Vec<usize>;
+
+    /// Returns the element at `index`.
+    fn get(&self, index: &[usize]) -> Self::Elem;
+
+    /// Sets the element at `index` to `value`. By default, the array is read-only and an error
+    /// is returned.
+    fn set(&mut self, _index: &[usize], _value: Self::Elem) -> JlrsResult<()> {
+        Err(JlrsError::exception("array is read-only"))?
+    }
+
+    /// Returns the stride of each dimension in number of elements if the data is stored in a
+    /// strided layout.
+    fn strides(&self) -> Option<Vec<usize>> {
+        None
+    }
+
+    /// Returns a pointer to the first element if the data is stored in a strided layout.
+    fn data_ptr(&self) -> *mut Self::Elem {
+        null_mut()
+    }
+}
+
+/// Returns `AbstractArray{T, N}`, where `T` is the Julia type of the elements and `N` the rank of
+/// `A`. This type should be returned by `OpaqueType::super_type`.
+pub fn abstract_array_super_type<'target, A, Tgt>(target: Tgt) -> DataTypeData<'target, Tgt>
+where
+    A: ForeignArray,
+    Tgt: Target<'target>,
+{
+    unsafe {
+        let mut frame = StackFrame::new();
+        let mut ccall = CCall::new(&mut frame);
+
+        ccall
+            .scope(|mut frame| {
+                let elty = <A::Elem as IntoJulia>::julia_type(&frame).as_value();
+                let rank = Value::new(&mut frame, A::RANK as isize);
+                Ok(UnionAll::abstractarray_type(&frame)
+                    .as_value()
+                    .apply_type_unchecked(&frame, [elty, rank])
+                    .as_value()
+                    .cast_unchecked::<DataType>()
+                    .root(target))
+            })
+            .unwrap()
+    }
+}
+
+// Converts the 1-based indices stored in `index`, an `NTuple{N, Int}`, to 0-based indices.
+// Returns `None` if the indices are out of bounds of an array with size `size`.
+unsafe fn read_index<A: ForeignArray>(index: Value, size: &[usize]) -> Option<Vec<usize>> {
+    let n = index.n_fields();
+    let data = std::slice::from_raw_parts(index.data_ptr().cast::<isize>().as_ptr(), n);
+
+    let linear_size;
+    let size = if A::LINEAR_INDEXING {
+        linear_size = [size.iter().product()];
+        &linear_size[..]
+    } else {
+        size
+    };
+
+    if data.len() != size.len() {
+        return None;
+    }
+
+    data.iter()
+        .zip(size.iter())
+        .map(|(&i, &n)| {
+            if i >= 1 && i as usize <= n {
+                Some(i as usize - 1)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// Creates a `BoundsError` for an attempt to access `arr` at `index`.
+unsafe fn bounds_error<'target, 'data>(
+    frame: &mut GcFrame<'target>,
+    arr: Value<'_, 'data>,
+    index: Value<'_, 'data>,
+) -> JlrsResult<Value<'target, 'data>> {
+    DataType::boundserror_type(&frame)
+        .as_value()
+        .call2(frame, arr, index)
+        .into_jlrs_result()
+}
+
+// Converts `values` to an `NTuple{N, Int}`.
+unsafe fn int_tuple<'target>(
+    frame: &mut GcFrame<'target>,
+    values: &[usize],
+) -> JlrsResult<Value<'target, 'static>> {
+    let tuple = Module::core(&frame).function(&frame, "tuple")?.as_managed();
+    let mut args = Vec::with_capacity(values.len());
+    for v in values.iter().copied() {
+        args.push(Value::new(&mut *frame, v as isize));
+    }
+
+    tuple.call(frame, args).into_jlrs_result()
+}
+
+/// Returns the size of the array as an `NTuple{N, Int}`.
+pub unsafe fn array_size<A: ForeignArray>(arr: TypedValue<A>) -> RustResultRet<AnyType> {
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, arr.track_shared());
+
+        match int_tuple(&mut frame, &tracked.size()) {
+            Ok(size) => {
+                RustResult::ok(frame.as_extended_target(), size.as_typed_unchecked()).leak()
+            }
+            Err(e) => RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+        }
+    })
+}
+
+/// Returns the element at `index`, an `NTuple{N, Int}` of 1-based indices. A `BoundsError` is
+/// thrown if the index is out of bounds.
+pub unsafe fn array_getindex<A: ForeignArray>(
+    arr: TypedValue<A>,
+    index: Value,
+) -> RustResultRet<AnyType> {
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, arr.track_shared());
+
+        let index = match read_index::<A>(index, &tracked.size()) {
+            Some(index) => index,
+            None => match bounds_error(&mut frame, arr.as_value(), index) {
+                Ok(err) => return RustResult::error(frame.as_extended_target(), err).leak(),
+                Err(e) => return RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+            },
+        };
+
+        let elem = tracked.get(&index);
+        let elem = Value::new(&mut frame, elem).as_typed_unchecked::<AnyType>();
+        RustResult::ok(frame.as_extended_target(), elem).leak()
+    })
+}
+
+/// Sets the element at `index`, an `NTuple{N, Int}` of 1-based indices, to `value`. A
+/// `BoundsError` is thrown if the index is out of bounds.
+pub unsafe fn array_setindex<A: ForeignArray>(
+    mut arr: TypedValue<A>,
+    value: Value,
+    index: Value,
+) -> RustResultRet<Nothing> {
+    CCall::invoke(|mut frame| {
+        let arr_value = arr.as_value();
+        let mut tracked = track_or_return!(frame, arr.track_exclusive());
+
+        let index = match read_index::<A>(index, &tracked.size()) {
+            Some(index) => index,
+            None => match bounds_error(&mut frame, arr_value, index) {
+                Ok(err) => return RustResult::error(frame.as_extended_target(), err).leak(),
+                Err(e) => return RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+            },
+        };
+
+        let res = value
+            .unbox::<A::Elem>()
+            .and_then(|value| tracked.set(&index, value));
+
+        match res {
+            Ok(_) => {
+                let nothing = Value::nothing(&frame).as_typed_unchecked::<Nothing>();
+                RustResult::ok(frame.as_extended_target(), nothing).leak()
+            }
+            Err(e) => RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+        }
+    })
+}
+
+/// Returns the strides of the array as an `NTuple{N, Int}`.
+pub unsafe fn array_strides<A: ForeignArray>(arr: TypedValue<A>) -> RustResultRet<AnyType> {
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, arr.track_shared());
+
+        let strides = match tracked.strides() {
+            Some(strides) => strides,
+            None => {
+                let err = JlrsError::exception("array is not strided");
+                return RustResult::jlrs_error(frame.as_extended_target(), err).leak();
+            }
+        };
+
+        match int_tuple(&mut frame, &strides) {
+            Ok(strides) => {
+                RustResult::ok(frame.as_extended_target(), strides.as_typed_unchecked()).leak()
+            }
+            Err(e) => RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+        }
+    })
+}
+
+/// Returns the address of the first element of the array.
+pub unsafe fn array_pointer<A: ForeignArray>(arr: TypedValue<A>) -> RustResultRet<usize> {
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, arr.track_shared());
+
+        let ptr = tracked.data_ptr();
+        if ptr.is_null() {
+            let err = JlrsError::exception("array is not strided");
+            return RustResult::jlrs_error(frame.as_extended_target(), err).leak();
+        }
+
+        let ptr = TypedValue::new(&mut frame, ptr as usize);
+        RustResult::ok(frame.as_extended_target(), ptr).leak()
+    })
+}
+
+/// Define the array methods of the array type `A`.
+///
+/// `size_fn`, `getindex_fn` and `setindex_fn` are the names of the functions in `module` that
+/// call [`array_size`], [`array_getindex`] and [`array_setindex`] respectively. If the array is
+/// strided, `strided_fns` contains the names of the functions that call [`array_strides`] and
+/// [`array_pointer`].
+///
+/// Safety: the type `A` must have been created, and the functions must be defined before the
+/// array is used.
+pub unsafe fn init_array_methods<A>(
+    frame: &mut GcFrame,
+    module: Module,
+    size_fn: &str,
+    getindex_fn: &str,
+    setindex_fn: &str,
+    strided_fns: Option<(&str, &str)>,
+) -> JlrsResult<()>
+where
+    A: ForeignArray,
+{
+    define_methods::<A, _>(frame, module, ARRAY_METHODS_GENERATOR, |frame| {
+        let elty = <A::Elem as IntoJulia>::julia_type(&*frame).as_value();
+        let rank = Value::new(&mut *frame, A::RANK as isize);
+        let linear = Value::new(&mut *frame, A::LINEAR_INDEXING);
+        let size_fn = symbol_or_nothing(frame, Some(size_fn));
+        let getindex_fn = symbol_or_nothing(frame, Some(getindex_fn));
+        let setindex_fn = symbol_or_nothing(frame, Some(setindex_fn));
+        let strides_fn = symbol_or_nothing(frame, strided_fns.map(|(strides_fn, _)| strides_fn));
+        let pointer_fn = symbol_or_nothing(frame, strided_fns.map(|(_, pointer_fn)| pointer_fn));
+
+        Ok(vec![
+            elty,
+            rank,
+            linear,
+            size_fn,
+            getindex_fn,
+            setindex_fn,
+            strides_fn,
+            pointer_fn,
+        ])
+    })
+}
@@ -0,0 +1,152 @@
+//! Export Rust iterators as Julia iterables.
+//!
+//! An opaque or foreign type that implements `Iterator` can be exported as an iterable type with
+//! the `julia_module` macro by annotating it with `#[jlrs(iterator)]`. The items must implement
+//! `IntoJulia`. If the type implements `ExactSizeIterator`, `#[jlrs(iterator, exact_size)]` can
+//! be used instead:
+//!
+//! ```ignore
+//! struct Countdown(u32);
+//!
+//! impl Iterator for Countdown {
+//!     type Item = u32;
+//!
+//!     fn next(&mut self) -> Option<u32> {
+//!         if self.0 == 0 {
+//!             None
+//!         } else {
+//!             self.0 -= 1;
+//!             Some(self.0)
+//!         }
+//!     }
+//! }
+//!
+//! unsafe impl OpaqueType for Countdown {}
+//!
+//! julia_module! {
+//!     become module_jl_init;
+//!
+//!     #[jlrs(iterator)]
+//!     struct Countdown;
+//! }
+//! ```
+//!
+//! This generates methods for `Base.iterate`, `Base.IteratorSize`, `Base.eltype` and, if the
+//! iterator has an exact size, `Base.length`. The iterator is tracked exclusively while it's
+//! advanced, and the iterator itself is used as its own state, so iterating over it consumes its
+//! items. The functions in this module are called by the code generated by `julia_module`.
+
+use super::{
+    abstract_types::AnyType,
+    foreign_methods::{define_methods, symbol_or_nothing, track_or_return},
+    foreign_type::OpaqueType,
+};
+use crate::{
+    call::Call,
+    ccall::CCall,
+    convert::into_julia::IntoJulia,
+    data::managed::{
+        module::Module,
+        rust_result::{RustResult, RustResultRet},
+        value::{typed::TypedValue, Value},
+        Managed,
+    },
+    error::JlrsResult,
+    memory::target::frame::GcFrame,
+};
+
+// Defines the iteration methods for the iterator type `T` with element type `E`. `next` and
+// `len` are the names of the generated functions that advance the iterator and return its
+// length, `len` is `nothing` if the iterator doesn't have an exact size.
+const ITERATOR_METHODS_GENERATOR: &str = "(mod, T, E, next, len) -> begin
+    nextref = GlobalRef(mod, next)
+    Core.eval(mod, quote
+        function Base.iterate(it::$T, state=nothing)
+            item = $nextref(it)
+            item === nothing ? nothing : (Base.something(item), nothing)
+        end
+        Base.eltype(::Type{<:$T}) = $E
+    end)
+
+    if len === nothing
+        Core.eval(mod, :(Base.IteratorSize(::Type{<:$T}) = Base.SizeUnknown()))
+    else
+        lenref = GlobalRef(mod, len)
+        Core.eval(mod, quote
+            Base.IteratorSize(::Type{<:$T}) = Base.HasLength()
+            Base.length(it::$T) = $lenref(it)
+        end)
+    end
+
+    nothing
+end";
+
+/// Advance the iterator, returns either `Some(item)` or `nothing`.
+pub unsafe fn iterator_next<I>(mut iter: TypedValue<I>) -> RustResultRet<AnyType>
+where
+    I: OpaqueType + Iterator,
+    I::Item: IntoJulia,
+{
+    CCall::invoke(|mut frame| {
+        let mut tracked = track_or_return!(frame, iter.track_exclusive());
+
+        let item = match tracked.next() {
+            Some(item) => item,
+            None => {
+                let nothing = Value::nothing(&frame).as_typed_unchecked::<AnyType>();
+                return RustResult::ok(frame.as_extended_target(), nothing).leak();
+            }
+        };
+
+        let item = Value::new(&mut frame, item);
+        let some = match Module::base(&frame).global(&frame, "Some") {
+            Ok(some) => some.as_value(),
+            Err(e) => return RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+        };
+
+        match some.call1(&mut frame, item) {
+            Ok(some) => {
+                RustResult::ok(frame.as_extended_target(), some.as_typed_unchecked()).leak()
+            }
+            Err(e) => RustResult::error(frame.as_extended_target(), e).leak(),
+        }
+    })
+}
+
+/// Returns the number of remaining items.
+pub unsafe fn iterator_length<I>(iter: TypedValue<I>) -> RustResultRet<usize>
+where
+    I: OpaqueType + ExactSizeIterator,
+{
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, iter.track_shared());
+
+        let len = TypedValue::new(&mut frame, tracked.len());
+        RustResult::ok(frame.as_extended_target(), len).leak()
+    })
+}
+
+/// Define the iteration methods of the iterator type `I`.
+///
+/// `next_fn` and `length_fn` are the names of the functions in `module` that call
+/// [`iterator_next`] and [`iterator_length`] respectively.
+///
+/// Safety: the type `I` must have been created, and the functions must be defined before the
+/// iterator is used.
+pub unsafe fn init_iterator_methods<I>(
+    frame: &mut GcFrame,
+    module: Module,
+    next_fn: &str,
+    length_fn: Option<&str>,
+) -> JlrsResult<()>
+where
+    I: OpaqueType + Iterator,
+    I::Item: IntoJulia,
+{
+    define_methods::<I, _>(frame, module, ITERATOR_METHODS_GENERATOR, |frame| {
+        let elty = <I::Item as IntoJulia>::julia_type(&*frame).as_value();
+        let next_fn = symbol_or_nothing(frame, Some(next_fn));
+        let length_fn = symbol_or_nothing(frame, length_fn);
+        Ok(vec![elty, next_fn, length_fn])
+    })
+}
@@ -0,0 +1,74 @@
+// Helpers shared by the modules that generate Julia methods for exported types.
+//
+// The functions that are called by these methods track the data before accessing it, if the
+// data is already borrowed a borrow error is thrown. The methods themselves are defined by
+// evaluating a generator function and calling it with the module, the type and the names of the
+// functions that the methods should call. Generators must define these methods with
+// `Core.eval(mod, ...)`: the generator itself is evaluated in `Main`, methods defined with `@eval`
+// would belong to `Main` and wouldn't be saved when the module is precompiled.
+
+use super::construct_type::ConstructType;
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::managed::{module::Module, symbol::Symbol, value::Value, Managed},
+    error::JlrsResult,
+    memory::target::frame::GcFrame,
+};
+
+// Evaluates to the tracked data if `$tracked`, the result of tracking some data, is `Ok`.
+// Otherwise, a borrow error is returned from the enclosing `CCall::invoke` closure.
+macro_rules! track_or_return {
+    ($frame:ident, $tracked:expr) => {
+        match $tracked {
+            Ok(tracked) => tracked,
+            Err(_) => {
+                return $crate::data::managed::rust_result::RustResult::borrow_error(
+                    $frame.as_extended_target(),
+                )
+                .leak()
+            }
+        }
+    };
+}
+
+pub(crate) use track_or_return;
+
+// Returns `name` as a `Symbol`, or `nothing` if it's `None`.
+pub(crate) fn symbol_or_nothing<'scope>(
+    frame: &GcFrame<'scope>,
+    name: Option<&str>,
+) -> Value<'scope, 'static> {
+    match name {
+        Some(name) => Symbol::new(frame, name).as_value(),
+        None => Value::nothing(frame),
+    }
+}
+
+// Evaluates `generator` and calls it with `module`, the type constructed by `T` and the values
+// returned by `args`.
+//
+// Safety: the generator must only define new methods in `module`.
+pub(crate) unsafe fn define_methods<T, F>(
+    frame: &mut GcFrame,
+    module: Module,
+    generator: &str,
+    args: F,
+) -> JlrsResult<()>
+where
+    T: ConstructType,
+    F: for<'scope> FnOnce(&mut GcFrame<'scope>) -> JlrsResult<Vec<Value<'scope, 'static>>>,
+{
+    frame.scope(|mut frame| {
+        let ty = T::construct_type(frame.as_extended_target());
+        let mut all_args = vec![module.as_value(), ty];
+        all_args.extend(args(&mut frame)?);
+
+        Value::eval_string(&mut frame, generator)
+            .into_jlrs_result()?
+            .call(&mut frame, all_args)
+            .into_jlrs_result()?;
+
+        Ok(())
+    })
+}
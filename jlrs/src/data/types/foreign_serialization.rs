@@ -0,0 +1,167 @@
+//! Serialize exported types with Julia's `Serialization` module.
+//!
+//! Julia can't serialize the contents of opaque and foreign types, so instances of these types
+//! can't be written to a file with `Serialization.serialize` or be sent to another process with
+//! `Distributed`. An exported type that implements [`SerializableForeign`] can be annotated with
+//! `#[jlrs(serializable)]` in `julia_module`, which generates methods for
+//! `Serialization.serialize` and `Serialization.deserialize` that call
+//! `SerializableForeign::to_bytes` and `SerializableForeign::from_bytes` respectively:
+//!
+//! ```ignore
+//! struct Point {
+//!     x: f64,
+//!     y: f64,
+//! }
+//!
+//! unsafe impl OpaqueType for Point {}
+//!
+//! impl SerializableForeign for Point {
+//!     fn to_bytes(&self) -> JlrsResult<Vec<u8>> {
+//!         let mut bytes = self.x.to_le_bytes().to_vec();
+//!         bytes.extend_from_slice(&self.y.to_le_bytes());
+//!         Ok(bytes)
+//!     }
+//!
+//!     fn from_bytes(bytes: &[u8]) -> JlrsResult<Self> {
+//!         if bytes.len() != 16 {
+//!             Err(JlrsError::exception("invalid number of bytes"))?
+//!         }
+//!
+//!         let x = f64::from_le_bytes(bytes[0..8].try_into().unwrap());
+//!         let y = f64::from_le_bytes(bytes[8..16].try_into().unwrap());
+//!         Ok(Point { x, y })
+//!     }
+//! }
+//!
+//! julia_module! {
+//!     become module_jl_init;
+//!
+//!     #[jlrs(serializable)]
+//!     struct Point;
+//! }
+//! ```
+//!
+//! The type is serialized as its name followed by the bytes returned by `to_bytes`, so the
+//! module that exports the type must be loaded in the process that deserializes it. Like other
+//! mutable objects, an instance that is referenced more than once is serialized once and
+//! deserialized as a single shared instance. The functions
+//! in this module are called by the code generated by `julia_module`.
+
+use super::{
+    abstract_types::AnyType,
+    foreign_methods::{define_methods, symbol_or_nothing, track_or_return},
+    foreign_type::OpaqueType,
+};
+use crate::{
+    ccall::CCall,
+    data::managed::{
+        array::TypedArray,
+        module::Module,
+        rust_result::{RustResult, RustResultRet},
+        value::typed::TypedValue,
+        Managed,
+    },
+    error::JlrsResult,
+    memory::target::frame::GcFrame,
+};
+
+// Defines the serialization methods for the type `T`. `ser` and `de` are the names of the
+// generated functions that convert an instance of `T` to and from a `Vector{UInt8}`. Like the
+// methods for mutable types in `Serialization`, an instance that has already been serialized is
+// written as a back-reference. The slot of a new instance is reserved when its header is read,
+// `deserialize_cycle` stores the instance in that slot so shared references are preserved.
+const SERIALIZATION_METHODS_GENERATOR: &str = "(mod, T, ser, de) -> begin
+    S = Base.require(Base.PkgId(Base.UUID(\"9e88b42a-f829-5b0c-bbe9-9e923198166b\"), \"Serialization\"))
+    serref, deref = GlobalRef(mod, ser), GlobalRef(mod, de)
+
+    Core.eval(mod, quote
+        function $S.serialize(s::$S.AbstractSerializer, x::$T)
+            $S.serialize_cycle_header(s, x) && return
+            $S.serialize(s, $serref(x)::Vector{UInt8})
+        end
+
+        function $S.deserialize(s::$S.AbstractSerializer, ::Type{$T})
+            bytes = $S.deserialize(s)::Vector{UInt8}
+            x = $deref(bytes)
+            $S.deserialize_cycle(s, x)
+            x
+        end
+    end)
+
+    nothing
+end";
+
+/// Trait implemented by exported types that can be serialized.
+pub trait SerializableForeign: OpaqueType + Sized {
+    /// Convert the data to bytes.
+    fn to_bytes(&self) -> JlrsResult<Vec<u8>>;
+
+    /// Convert bytes returned by `to_bytes` back to the data.
+    fn from_bytes(bytes: &[u8]) -> JlrsResult<Self>;
+}
+
+/// Converts the data to a `Vector{UInt8}` with `SerializableForeign::to_bytes`.
+pub unsafe fn serialize_foreign<T>(value: TypedValue<T>) -> RustResultRet<AnyType>
+where
+    T: SerializableForeign,
+{
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, value.track_shared());
+
+        let bytes = match tracked.to_bytes() {
+            Ok(bytes) => bytes,
+            Err(e) => return RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+        };
+
+        let n_bytes = bytes.len();
+        match TypedArray::<u8>::from_vec(frame.as_extended_target(), bytes, n_bytes) {
+            Ok(Ok(arr)) => {
+                let arr = arr.as_value().as_typed_unchecked::<AnyType>();
+                RustResult::ok(frame.as_extended_target(), arr).leak()
+            }
+            Ok(Err(e)) => RustResult::error(frame.as_extended_target(), e).leak(),
+            Err(e) => RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+        }
+    })
+}
+
+/// Converts a `Vector{UInt8}` to a new instance of `T` with `SerializableForeign::from_bytes`.
+pub unsafe fn deserialize_foreign<T>(bytes: TypedArray<u8>) -> RustResultRet<T>
+where
+    T: SerializableForeign,
+{
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, bytes.track_shared());
+
+        match T::from_bytes(tracked.as_slice()) {
+            Ok(value) => {
+                let value = TypedValue::new(&mut frame, value);
+                RustResult::ok(frame.as_extended_target(), value).leak()
+            }
+            Err(e) => RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+        }
+    })
+}
+
+/// Define the serialization methods of the type `T`.
+///
+/// `serialize_fn` and `deserialize_fn` are the names of the functions in `module` that call
+/// [`serialize_foreign`] and [`deserialize_foreign`] respectively.
+///
+/// Safety: the type `T` must have been created, and the functions must be defined before an
+/// instance of `T` is serialized.
+pub unsafe fn init_serialization_methods<T>(
+    frame: &mut GcFrame,
+    module: Module,
+    serialize_fn: &str,
+    deserialize_fn: &str,
+) -> JlrsResult<()>
+where
+    T: SerializableForeign,
+{
+    define_methods::<T, _>(frame, module, SERIALIZATION_METHODS_GENERATOR, |frame| {
+        let serialize_fn = symbol_or_nothing(frame, Some(serialize_fn));
+        let deserialize_fn = symbol_or_nothing(frame, Some(deserialize_fn));
+        Ok(vec![serialize_fn, deserialize_fn])
+    })
+}
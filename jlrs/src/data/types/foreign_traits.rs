@@ -0,0 +1,235 @@
+//! Implement Julia's standard interface for exported types with Rust traits.
+//!
+//! When an opaque or foreign type is exported with the `julia_module` macro, methods for several
+//! functions in `Base` can be generated by listing the Rust traits they should be implemented with
+//! in a `#[jlrs(derive(...))]` attribute:
+//!
+//! ```ignore
+//! #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+//! struct Version(u32, u32, u32);
+//!
+//! unsafe impl OpaqueType for Version {}
+//!
+//! julia_module! {
+//!     become module_jl_init;
+//!
+//!     #[jlrs(derive(Debug, PartialEq, Hash, Clone, Ord))]
+//!     struct Version;
+//! }
+//! ```
+//!
+//! The following traits are supported:
+//!
+//!  - `Debug`: `Base.show(io::IO, x)`.
+//!  - `Display`: `Base.print(io::IO, x)`, and `Base.show(io::IO, x)` if `Debug` isn't used.
+//!  - `PartialEq`: `Base.:(==)(a, b)`, `Base.isequal` falls back to this method.
+//!  - `Hash`: `Base.hash(x, h::UInt)`.
+//!  - `Clone`: `Base.copy(x)` and `Base.deepcopy_internal(x, dict::IdDict)`.
+//!  - `Ord`: `Base.isless(a, b)`.
+//!
+//! Together, `PartialEq` and `Hash` let instances of the type be used as keys of a `Dict`, and
+//! `Ord` lets them be sorted. The data is tracked while these methods are called. The functions
+//! in this module are called by the code generated by `julia_module`.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+};
+
+use super::{
+    abstract_types::AnyType,
+    foreign_methods::{define_methods, symbol_or_nothing, track_or_return},
+    foreign_type::OpaqueType,
+};
+use crate::{
+    ccall::CCall,
+    data::managed::{
+        module::Module,
+        rust_result::{RustResult, RustResultRet},
+        string::JuliaString,
+        value::typed::TypedValue,
+        Managed,
+    },
+    error::JlrsResult,
+    memory::target::frame::GcFrame,
+};
+
+// Defines the methods for the type `T`. The remaining arguments are the names of the generated
+// functions that call the trait methods, or `nothing` if the trait isn't used.
+const BASE_METHODS_GENERATOR: &str = "(mod, T, debug, display, eq, hash, clone, cmp) -> begin
+    if debug !== nothing
+        debugref = GlobalRef(mod, debug)
+        Core.eval(mod, :(Base.show(io::IO, x::$T) = Base.print(io, $debugref(x)::String)))
+    end
+
+    if display !== nothing
+        displayref = GlobalRef(mod, display)
+        Core.eval(mod, :(Base.print(io::IO, x::$T) = Base.print(io, $displayref(x)::String)))
+        if debug === nothing
+            Core.eval(mod, :(Base.show(io::IO, x::$T) = Base.print(io, $displayref(x)::String)))
+        end
+    end
+
+    if eq !== nothing
+        eqref = GlobalRef(mod, eq)
+        Core.eval(mod, :(Base.:(==)(a::$T, b::$T) = $eqref(a, b)))
+    end
+
+    if hash !== nothing
+        hashref = GlobalRef(mod, hash)
+        Core.eval(mod, :(Base.hash(x::$T, h::UInt) = Base.hash($hashref(x), h)))
+    end
+
+    if clone !== nothing
+        cloneref = GlobalRef(mod, clone)
+        Core.eval(mod, quote
+            Base.copy(x::$T) = $cloneref(x)
+            function Base.deepcopy_internal(x::$T, stackdict::IdDict)
+                Base.haskey(stackdict, x) && return stackdict[x]
+                y = $cloneref(x)
+                stackdict[x] = y
+                y
+            end
+        end)
+    end
+
+    if cmp !== nothing
+        cmpref = GlobalRef(mod, cmp)
+        Core.eval(mod, :(Base.isless(a::$T, b::$T) = $cmpref(a, b)))
+    end
+
+    nothing
+end";
+
+/// The names of the functions that call the trait methods of an exported type, `None` if the
+/// trait isn't used.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BaseMethodFns<'a> {
+    /// The name of the function that calls [`debug_string`].
+    pub debug: Option<&'a str>,
+    /// The name of the function that calls [`display_string`].
+    pub display: Option<&'a str>,
+    /// The name of the function that calls [`partial_eq`].
+    pub partial_eq: Option<&'a str>,
+    /// The name of the function that calls [`hash`].
+    pub hash: Option<&'a str>,
+    /// The name of the function that calls [`clone`].
+    pub clone: Option<&'a str>,
+    /// The name of the function that calls [`is_less`].
+    pub ord: Option<&'a str>,
+}
+
+/// Formats the data with its `Debug` implementation.
+pub unsafe fn debug_string<T>(value: TypedValue<T>) -> RustResultRet<AnyType>
+where
+    T: OpaqueType + Debug,
+{
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, value.track_shared());
+
+        let s = format!("{:?}", &*tracked);
+        let s = JuliaString::new(&mut frame, s).as_value();
+        RustResult::ok(frame.as_extended_target(), s.as_typed_unchecked()).leak()
+    })
+}
+
+/// Formats the data with its `Display` implementation.
+pub unsafe fn display_string<T>(value: TypedValue<T>) -> RustResultRet<AnyType>
+where
+    T: OpaqueType + Display,
+{
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, value.track_shared());
+
+        let s = format!("{}", &*tracked);
+        let s = JuliaString::new(&mut frame, s).as_value();
+        RustResult::ok(frame.as_extended_target(), s.as_typed_unchecked()).leak()
+    })
+}
+
+/// Returns `true` if `a` and `b` are equal.
+pub unsafe fn partial_eq<T>(a: TypedValue<T>, b: TypedValue<T>) -> RustResultRet<bool>
+where
+    T: OpaqueType + PartialEq,
+{
+    CCall::invoke(|mut frame| {
+        let a = track_or_return!(frame, a.track_shared());
+        let b = track_or_return!(frame, b.track_shared());
+
+        let eq = TypedValue::new(&mut frame, *a == *b);
+        RustResult::ok(frame.as_extended_target(), eq).leak()
+    })
+}
+
+/// Hashes the data with its `Hash` implementation.
+pub unsafe fn hash<T>(value: TypedValue<T>) -> RustResultRet<u64>
+where
+    T: OpaqueType + Hash,
+{
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, value.track_shared());
+
+        let mut hasher = DefaultHasher::new();
+        tracked.hash(&mut hasher);
+        let hash = TypedValue::new(&mut frame, hasher.finish());
+        RustResult::ok(frame.as_extended_target(), hash).leak()
+    })
+}
+
+/// Returns a new instance of `T` that contains a clone of the data.
+pub unsafe fn clone<T>(value: TypedValue<T>) -> RustResultRet<T>
+where
+    T: OpaqueType + Clone,
+{
+    CCall::invoke(|mut frame| {
+        let tracked = track_or_return!(frame, value.track_shared());
+
+        let cloned = T::clone(&tracked);
+        let cloned = TypedValue::new(&mut frame, cloned);
+        RustResult::ok(frame.as_extended_target(), cloned).leak()
+    })
+}
+
+/// Returns `true` if `a` is less than `b`.
+pub unsafe fn is_less<T>(a: TypedValue<T>, b: TypedValue<T>) -> RustResultRet<bool>
+where
+    T: OpaqueType + Ord,
+{
+    CCall::invoke(|mut frame| {
+        let a = track_or_return!(frame, a.track_shared());
+        let b = track_or_return!(frame, b.track_shared());
+
+        let is_less = TypedValue::new(&mut frame, *a < *b);
+        RustResult::ok(frame.as_extended_target(), is_less).leak()
+    })
+}
+
+/// Define the methods of the type `T` that call the functions in `fns`.
+///
+/// Safety: the type `T` must have been created, and the functions must be defined before these
+/// methods are called.
+pub unsafe fn init_base_methods<T>(
+    frame: &mut GcFrame,
+    module: Module,
+    fns: BaseMethodFns,
+) -> JlrsResult<()>
+where
+    T: OpaqueType,
+{
+    define_methods::<T, _>(frame, module, BASE_METHODS_GENERATOR, |frame| {
+        let names = [
+            fns.debug,
+            fns.display,
+            fns.partial_eq,
+            fns.hash,
+            fns.clone,
+            fns.ord,
+        ];
+
+        Ok(names
+            .into_iter()
+            .map(|name| symbol_or_nothing(frame, name))
+            .collect())
+    })
+}
@@ -52,7 +52,54 @@
 //! `julia_module` automatically takes care of this, otherwise you must manually call
 //! `OpaqueType::create_type` or `OpaqueType::reinit_type`. The first must be called if the
 //! type doesn't exist yet, the second if the module that defines the type has been precompiled.
-use std::{any::TypeId, ffi::c_void, mem::MaybeUninit, ptr::NonNull, sync::RwLock};
+//!
+//! A generic opaque type can be exported as a single parametric type by implementing
+//! `ParametricBase` and `ParametricVariant`. The base is the parametric type itself, e.g.
+//! `Buffer{T}`, every variant is one of its instantiations, e.g. `Buffer{Float32}`. Variants
+//! must also implement `OpaqueType`. The variants that are exported are listed with a `for`
+//! block in `julia_module`:
+//!
+//! ```ignore
+//! struct Buffer<T> {
+//!     data: Vec<T>,
+//! }
+//!
+//! impl<T: Send + 'static> Buffer<T> {
+//!     fn len(&self) -> usize {
+//!         self.data.len()
+//!     }
+//! }
+//!
+//! unsafe impl<T: Send + 'static> OpaqueType for Buffer<T> {}
+//!
+//! unsafe impl<T: Send + 'static> ParametricBase for Buffer<T> {
+//!     type Key = Buffer<()>;
+//!
+//!     fn type_parameters() -> &'static [&'static str] {
+//!         &["T"]
+//!     }
+//! }
+//!
+//! unsafe impl<T: ConstructType + Send + 'static> ParametricVariant for Buffer<T> {
+//!     fn variant_parameters<'target>(
+//!         frame: &mut GcFrame<'target>,
+//!     ) -> Vec<Value<'target, 'static>> {
+//!         vec![T::construct_type(frame.as_extended_target())]
+//!     }
+//! }
+//!
+//! julia_module! {
+//!     become module_jl_init;
+//!
+//!     for T in [f32, f64] {
+//!         struct Buffer<T>;
+//!         in Buffer<T> fn len(&self) -> usize;
+//!     };
+//! }
+//! ```
+use std::{
+    any::TypeId, ffi::c_void, marker::PhantomData, mem::MaybeUninit, ptr::NonNull, sync::RwLock,
+};
 
 #[julia_version(except = ["1.7"])]
 use jl_sys::jl_gc_schedule_foreign_sweepfunc;
@@ -70,7 +117,10 @@ use crate::{
             datatype::{DataType, DataTypeData},
             module::Module,
             private::ManagedPriv,
+            simple_vector::SimpleVector,
             symbol::Symbol,
+            type_var::TypeVar,
+            union_all::UnionAll,
             value::{Value, ValueData, ValueRef},
             Managed,
         },
@@ -78,7 +128,7 @@ use crate::{
     },
     memory::{
         get_tls,
-        target::{ExtendedTarget, Target},
+        target::{frame::GcFrame, ExtendedTarget, Target},
         PTls,
     },
     private::Private,
@@ -268,6 +318,106 @@ unsafe impl<T: ForeignType> OpaqueType for T {
     }
 }
 
+/// A trait that allows a generic Rust type to be exposed to Julia as a parametric opaque type.
+///
+/// This trait must be implemented for all variants of the generic type, e.g. for `Buffer<T>` for
+/// all `T`. The base type is the parametric type itself, i.e. `Buffer{T}` in Julia.
+///
+/// Safety:
+///
+/// All variants must use the same `Key`, which must not be used as the key of any other
+/// parametric type. The base type must be created before any of its variants can be created.
+pub unsafe trait ParametricBase: Sized + Send + 'static {
+    /// A type that identifies the base type, e.g. `Buffer<()>`.
+    type Key: 'static;
+
+    /// The names of the type parameters of the base type.
+    fn type_parameters() -> &'static [&'static str];
+
+    /// The super-type of this type, `Core.Any` by default.
+    fn super_type<'target, Tgt>(target: Tgt) -> DataTypeData<'target, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        DataType::any_type(&target).root(target)
+    }
+
+    /// Creates a new parametric opaque type named `name` in `module`, the `UnionAll` that wraps
+    /// this type is returned.
+    ///
+    /// This method is called automatically by init functions generated with the `julia_module`
+    /// macro.
+    ///
+    /// Safety:
+    ///
+    /// The new type is not set as a constant in `module`, you must do this manually after calling
+    /// this function. You must not override the default implementation.
+    unsafe fn create_type<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+        name: Symbol,
+        module: Module,
+    ) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        create_parametric_opaque_type::<Self, Tgt>(target, name, module)
+    }
+
+    /// Reinitializes the previously created type `ty`, which must be the `UnionAll` returned by
+    /// `ParametricBase::create_type`.
+    ///
+    /// This method is called automatically by init functions generated with the `julia_module`
+    /// macro.
+    ///
+    /// Safety:
+    ///
+    /// The type must have been originally created by calling `ParametricBase::create_type`. You
+    /// must not override the default implementation.
+    unsafe fn reinit_type(ty: Value) -> bool {
+        reinit_parametric_opaque_type::<Self>(ty)
+    }
+}
+
+/// A variant of a parametric opaque type.
+///
+/// Each variant is an instantiation of the base type, e.g. `Buffer<f32>` is exposed to Julia as
+/// `Buffer{Float32}`. Like monomorphic opaque types, variants implement `IntoJulia`,
+/// `ValidLayout`, `Typecheck`, `Unbox` and `ConstructType` through their implementation of
+/// `OpaqueType`.
+///
+/// Safety:
+///
+/// The parameters returned by `ParametricVariant::variant_parameters` must be valid type
+/// parameters of the base type, and different variants must have different parameters.
+pub unsafe trait ParametricVariant: ParametricBase + OpaqueType {
+    /// Construct the type parameters of this variant, one for each of the type parameters of
+    /// the base type.
+    fn variant_parameters<'target>(frame: &mut GcFrame<'target>) -> Vec<Value<'target, 'static>>;
+
+    /// Creates this variant by applying its parameters to the base type.
+    ///
+    /// This method must be called both when the base type has been created and when it has been
+    /// reinitialized. It's called automatically by init functions generated with the
+    /// `julia_module` macro.
+    ///
+    /// Safety:
+    ///
+    /// The base type must have been created or reinitialized. You must not override the default
+    /// implementation.
+    unsafe fn create_variant<'target, Tgt>(
+        target: ExtendedTarget<'target, '_, '_, Tgt>,
+    ) -> DataTypeData<'target, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        create_parametric_variant::<Self, Tgt>(target)
+    }
+}
+
+// Parametric base types are registered with the `TypeId` of their key wrapped in this type, which
+// prevents them from colliding with opaque types.
+struct BaseKey<K>(PhantomData<K>);
+
 unsafe fn create_foreign_type<'target, U, T>(
     target: T,
     name: Symbol,
@@ -407,6 +557,146 @@ where
     target.data_from_ptr(NonNull::new_unchecked(ty), Private)
 }
 
+unsafe fn create_parametric_opaque_type<'target, U, T>(
+    target: ExtendedTarget<'target, '_, '_, T>,
+    name: Symbol,
+    module: Module,
+) -> ValueData<'target, 'static, T>
+where
+    U: ParametricBase,
+    T: Target<'target>,
+{
+    let (target, frame) = target.split();
+    if let Some(ty) = FOREIGN_TYPE_REGISTRY.find::<BaseKey<U::Key>>() {
+        return type_wrapper(ty).root(target);
+    }
+
+    frame
+        .scope(|mut frame| {
+            let type_params = U::type_parameters();
+            let n_params = type_params.len();
+            let mut params = SimpleVector::with_capacity(&mut frame, n_params);
+
+            {
+                let mut params_ref = params.data_mut();
+                for (i, name) in type_params.iter().copied().enumerate() {
+                    let tvar = TypeVar::new_unchecked(&frame, name, None, None);
+                    params_ref.set(i, Some(tvar.as_value()))?;
+                }
+            }
+
+            let super_type = U::super_type(&frame).ptr().as_ptr();
+            let params = params.unwrap(Private);
+
+            #[cfg(feature = "julia-1-6")]
+            let ty = jl_new_datatype(
+                name.unwrap(Private),
+                module.unwrap(Private),
+                super_type,
+                params,
+                jl_emptysvec,
+                jl_emptysvec,
+                0,
+                1,
+                0,
+            );
+
+            #[cfg(not(feature = "julia-1-6"))]
+            let ty = jl_new_datatype(
+                name.unwrap(Private),
+                module.unwrap(Private),
+                super_type,
+                params,
+                jl_emptysvec,
+                jl_emptysvec,
+                jl_emptysvec,
+                0,
+                1,
+                0,
+            );
+
+            debug_assert!(!ty.is_null());
+            let ty = DataType::wrap_non_null(NonNull::new_unchecked(ty), Private);
+            FOREIGN_TYPE_REGISTRY
+                .data
+                .write()
+                .expect("Foreign type lock was poisoned")
+                .push((TypeId::of::<BaseKey<U::Key>>(), ty));
+
+            Ok(type_wrapper(ty).root(target))
+        })
+        .unwrap()
+}
+
+// Returns the `UnionAll` that wraps the parametric type `ty`.
+unsafe fn type_wrapper(ty: DataType) -> Value<'static, 'static> {
+    let wrapper = ty.type_name().unwrap_non_null(Private).as_ref().wrapper;
+    debug_assert!(!wrapper.is_null());
+    Value::wrap_non_null(NonNull::new_unchecked(wrapper), Private)
+}
+
+unsafe fn reinit_parametric_opaque_type<U>(ty: Value) -> bool
+where
+    U: ParametricBase,
+{
+    if FOREIGN_TYPE_REGISTRY.find::<BaseKey<U::Key>>().is_some() {
+        return true;
+    }
+
+    let ty = match ty.cast::<UnionAll>() {
+        Ok(ua) => ua.base_type(),
+        Err(_) => return false,
+    };
+
+    FOREIGN_TYPE_REGISTRY
+        .data
+        .write()
+        .expect("Foreign type lock was poisoned")
+        .push((
+            TypeId::of::<BaseKey<U::Key>>(),
+            DataType::wrap_non_null(ty.unwrap_non_null(Private), Private),
+        ));
+    true
+}
+
+unsafe fn create_parametric_variant<'target, U, T>(
+    target: ExtendedTarget<'target, '_, '_, T>,
+) -> DataTypeData<'target, T>
+where
+    U: ParametricVariant,
+    T: Target<'target>,
+{
+    let (target, frame) = target.split();
+    if let Some(ty) = FOREIGN_TYPE_REGISTRY.find::<U>() {
+        return target.data_from_ptr(ty.unwrap_non_null(Private), Private);
+    }
+
+    let base = FOREIGN_TYPE_REGISTRY
+        .find::<BaseKey<U::Key>>()
+        .expect("Base type has not been created");
+
+    frame
+        .scope(|mut frame| {
+            let params = U::variant_parameters(&mut frame);
+            let ty = type_wrapper(base)
+                .apply_type(&mut frame, params)
+                .expect("Cannot apply the variant parameters to the base type")
+                .cast::<DataType>()?;
+
+            FOREIGN_TYPE_REGISTRY
+                .data
+                .write()
+                .expect("Foreign type lock was poisoned")
+                .push((
+                    TypeId::of::<U>(),
+                    DataType::wrap_non_null(ty.unwrap_non_null(Private), Private),
+                ));
+
+            Ok(ty.root(target))
+        })
+        .unwrap()
+}
+
 #[julia_version(since = "1.9")]
 unsafe fn reinit_foreign_type<U>(datatype: DataType) -> bool
 where
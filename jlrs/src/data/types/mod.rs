@@ -2,5 +2,17 @@
 
 pub mod abstract_types;
 pub mod construct_type;
+#[cfg(feature = "ccall")]
+pub mod foreign_array;
+#[cfg(feature = "ccall")]
+pub mod foreign_iterator;
+#[cfg(feature = "ccall")]
+mod foreign_methods;
+#[cfg(feature = "ccall")]
+pub mod foreign_serialization;
+#[cfg(feature = "ccall")]
+pub mod foreign_traits;
 pub mod foreign_type;
+#[cfg(feature = "ccall")]
+pub mod trait_object;
 pub mod typecheck;
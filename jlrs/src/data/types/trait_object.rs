@@ -0,0 +1,443 @@
+//! Export Rust traits as abstract Julia types.
+//!
+//! A trait that is declared in `julia_module` is exported as an abstract type with the same name.
+//! Exported types can be declared to implement this trait with `#[jlrs(implements(...))]`, in
+//! which case their super-type must be the abstract type of the trait. This super-type can be
+//! returned by [`trait_abstract_type`]. The methods of the trait are exported once as functions
+//! that take an instance of the abstract type as their first argument, these functions find the
+//! implementation of the trait for the type of that argument at runtime:
+//!
+//! ```ignore
+//! pub trait Model {
+//!     fn predict(&self, x: f64) -> f64;
+//!     fn reset(&mut self);
+//! }
+//!
+//! pub struct Linear(f64);
+//!
+//! impl Model for Linear {
+//!     fn predict(&self, x: f64) -> f64 {
+//!         self.0 * x
+//!     }
+//!
+//!     fn reset(&mut self) {
+//!         self.0 = 1.0;
+//!     }
+//! }
+//!
+//! unsafe impl OpaqueType for Linear {
+//!     fn super_type<'target, Tgt>(target: Tgt) -> DataTypeData<'target, Tgt>
+//!     where
+//!         Tgt: Target<'target>,
+//!     {
+//!         trait_abstract_type::<dyn Model, Tgt>(target)
+//!     }
+//! }
+//!
+//! julia_module! {
+//!     become module_jl_init;
+//!
+//!     trait Model {
+//!         fn predict(&self, x: f64) -> f64;
+//!         fn reset(&mut self) as reset!;
+//!     }
+//!
+//!     #[jlrs(implements(Model))]
+//!     struct Linear;
+//! }
+//! ```
+//!
+//! The receiver of a trait method must be `&self` or `&mut self`, and the instance is tracked
+//! while the method is called. The return type of a trait method must implement `IntoJulia` and
+//! `ConstructType`, or be `()`. The functions in this module are called by the code generated by
+//! `julia_module`.
+
+use std::{
+    any::{Any, TypeId},
+    marker::PhantomData,
+    ptr::NonNull,
+    sync::RwLock,
+};
+
+use jl_sys::{jl_emptysvec, jl_new_datatype};
+
+use super::{construct_type::ConstructType, foreign_type::OpaqueType};
+use crate::{
+    ccall::CCall,
+    convert::{ccall_types::CCallArg, into_julia::IntoJulia},
+    data::managed::{
+        datatype::{DataType, DataTypeData},
+        module::Module,
+        private::ManagedPriv,
+        rust_result::{RustResult, RustResultRet},
+        symbol::Symbol,
+        value::{typed::TypedValue, Value, ValueData},
+        Managed,
+    },
+    error::{JlrsError, JlrsResult},
+    memory::target::{ExtendedTarget, Target},
+    private::Private,
+};
+
+static TRAIT_REGISTRY: TraitRegistry = TraitRegistry::new();
+
+type TraitImplEntry = (TypeId, DataType<'static>, Box<dyn Any + Send + Sync>);
+type SharedCall<Tr> = unsafe fn(Value, &mut dyn FnMut(&Tr)) -> JlrsResult<()>;
+type ExclusiveCall<Tr> = unsafe fn(Value, &mut dyn FnMut(&mut Tr)) -> JlrsResult<()>;
+
+struct TraitRegistry {
+    // The abstract type of each exported trait.
+    types: RwLock<Vec<(TypeId, DataType<'static>)>>,
+    // The implementations of each exported trait, a `TraitImpl<Tr>` is stored for each type
+    // that implements the trait `Tr`.
+    impls: RwLock<Vec<TraitImplEntry>>,
+}
+
+impl TraitRegistry {
+    const fn new() -> Self {
+        TraitRegistry {
+            types: RwLock::new(Vec::new()),
+            impls: RwLock::new(Vec::new()),
+        }
+    }
+
+    fn find_type<Tr: ?Sized + 'static>(&self) -> Option<DataType<'static>> {
+        let tid = TypeId::of::<Tr>();
+        self.types
+            .read()
+            .expect("Lock poisoned")
+            .iter()
+            .find_map(|s| match s {
+                &(type_id, ty) if type_id == tid => Some(ty),
+                _ => None,
+            })
+    }
+
+    fn find_impl<Tr: ?Sized + 'static>(&self, ty: DataType) -> Option<TraitImpl<Tr>> {
+        let tid = TypeId::of::<Tr>();
+        self.impls.read().expect("Lock poisoned").iter().find_map(
+            |(type_id, impl_ty, trait_impl)| {
+                if *type_id == tid && impl_ty.unwrap(Private) == ty.unwrap(Private) {
+                    trait_impl.downcast_ref::<TraitImpl<Tr>>().copied()
+                } else {
+                    None
+                }
+            },
+        )
+    }
+}
+
+unsafe impl Sync for TraitRegistry {}
+unsafe impl Send for TraitRegistry {}
+
+// Calls a function with a reference to the data of a value, which must be an instance of the
+// type this implementation has been registered for.
+struct TraitImpl<Tr: ?Sized> {
+    shared: SharedCall<Tr>,
+    exclusive: ExclusiveCall<Tr>,
+}
+
+impl<Tr: ?Sized> Clone for TraitImpl<Tr> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Tr: ?Sized> Copy for TraitImpl<Tr> {}
+
+unsafe fn call_shared<Tr, T>(value: Value, func: &mut dyn FnMut(&Tr)) -> JlrsResult<()>
+where
+    Tr: ?Sized + 'static,
+    T: ImplementsTrait<Tr>,
+{
+    let value = value.as_typed_unchecked::<T>();
+    let tracked = value.track_shared()?;
+    func(tracked.as_trait_object());
+    Ok(())
+}
+
+unsafe fn call_exclusive<Tr, T>(value: Value, func: &mut dyn FnMut(&mut Tr)) -> JlrsResult<()>
+where
+    Tr: ?Sized + 'static,
+    T: ImplementsTrait<Tr>,
+{
+    let mut value = value.as_typed_unchecked::<T>();
+    let mut tracked = value.track_exclusive()?;
+    func(tracked.as_trait_object_mut());
+    Ok(())
+}
+
+/// Trait implemented by exported types that implement an exported trait `Tr`, which must be a
+/// trait object type.
+///
+/// This trait is implemented by `julia_module` for types annotated with
+/// `#[jlrs(implements(...))]`.
+pub trait ImplementsTrait<Tr: ?Sized + 'static>: OpaqueType {
+    /// Convert a reference to the data to a trait object.
+    fn as_trait_object(&self) -> &Tr;
+
+    /// Convert a mutable reference to the data to a trait object.
+    fn as_trait_object_mut(&mut self) -> &mut Tr;
+}
+
+/// An instance of a type that implements the exported trait `Tr`.
+///
+/// This type can be used as an argument type of functions exported with `julia_module`, the
+/// argument type of the generated Julia function is the abstract type of the trait.
+#[repr(transparent)]
+pub struct TraitObject<'scope, 'data, Tr: ?Sized + 'static> {
+    value: Value<'scope, 'data>,
+    _marker: PhantomData<*const Tr>,
+}
+
+impl<Tr: ?Sized + 'static> Clone for TraitObject<'_, '_, Tr> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Tr: ?Sized + 'static> Copy for TraitObject<'_, '_, Tr> {}
+
+impl<'scope, 'data, Tr: ?Sized + 'static> TraitObject<'scope, 'data, Tr> {
+    /// Returns the instance as a `Value`.
+    pub fn as_value(self) -> Value<'scope, 'data> {
+        self.value
+    }
+
+    /// Tracks the instance immutably and calls `func` with the instance as a trait object.
+    ///
+    /// Returns an error if the type of the instance hasn't been registered as an implementation
+    /// of `Tr`, or if the instance is already tracked mutably.
+    pub unsafe fn with_shared<R>(self, func: impl FnOnce(&Tr) -> R) -> JlrsResult<R> {
+        let trait_impl = self.find_impl()?;
+        let mut func = Some(func);
+        let mut output = None;
+        (trait_impl.shared)(self.value, &mut |obj| {
+            output = func.take().map(|func| func(obj));
+        })?;
+
+        Ok(output.expect("Function was not called"))
+    }
+
+    /// Tracks the instance mutably and calls `func` with the instance as a trait object.
+    ///
+    /// Returns an error if the type of the instance hasn't been registered as an implementation
+    /// of `Tr`, or if the instance is already tracked.
+    pub unsafe fn with_exclusive<R>(self, func: impl FnOnce(&mut Tr) -> R) -> JlrsResult<R> {
+        let trait_impl = self.find_impl()?;
+        let mut func = Some(func);
+        let mut output = None;
+        (trait_impl.exclusive)(self.value, &mut |obj| {
+            output = func.take().map(|func| func(obj));
+        })?;
+
+        Ok(output.expect("Function was not called"))
+    }
+
+    fn find_impl(self) -> JlrsResult<TraitImpl<Tr>> {
+        let ty = self.value.datatype();
+        match TRAIT_REGISTRY.find_impl::<Tr>(ty) {
+            Some(trait_impl) => Ok(trait_impl),
+            None => Err(JlrsError::exception(format!(
+                "{} doesn't implement {}",
+                ty.name(),
+                std::any::type_name::<Tr>()
+            )))?,
+        }
+    }
+}
+
+unsafe impl<Tr: ?Sized + 'static> ConstructType for TraitObject<'_, '_, Tr> {
+    fn construct_type<'target, T>(
+        target: ExtendedTarget<'target, '_, '_, T>,
+    ) -> ValueData<'target, 'static, T>
+    where
+        T: Target<'target>,
+    {
+        let (target, _) = target.split();
+        let ty = TRAIT_REGISTRY
+            .find_type::<Tr>()
+            .expect("Trait has not been exported");
+
+        unsafe { target.data_from_ptr(ty.as_value().unwrap_non_null(Private), Private) }
+    }
+
+    fn base_type<'target, T>(_target: &T) -> Option<Value<'target, 'static>>
+    where
+        T: Target<'target>,
+    {
+        TRAIT_REGISTRY.find_type::<Tr>().map(|ty| ty.as_value())
+    }
+}
+
+unsafe impl<'scope, 'data, Tr: ?Sized + 'static> CCallArg for TraitObject<'scope, 'data, Tr> {
+    type CCallArgType = Value<'scope, 'data>;
+    type FunctionArgType = Self;
+}
+
+/// Returns the abstract type of the exported trait `Tr`. This type should be returned by
+/// `OpaqueType::super_type` of types that implement `Tr`.
+///
+/// Panics if `Tr` hasn't been exported.
+pub fn trait_abstract_type<'target, Tr, Tgt>(target: Tgt) -> DataTypeData<'target, Tgt>
+where
+    Tr: ?Sized + 'static,
+    Tgt: Target<'target>,
+{
+    let ty = TRAIT_REGISTRY
+        .find_type::<Tr>()
+        .expect("Trait has not been exported");
+
+    unsafe { target.data_from_ptr(ty.unwrap_non_null(Private), Private) }
+}
+
+/// Creates the abstract type of the trait `Tr` named `name` in `module`.
+///
+/// Safety: this function must be called before types that implement `Tr` are created.
+pub unsafe fn create_trait_type<'target, Tr, Tgt>(
+    target: Tgt,
+    name: Symbol,
+    module: Module,
+) -> DataTypeData<'target, Tgt>
+where
+    Tr: ?Sized + 'static,
+    Tgt: Target<'target>,
+{
+    if let Some(ty) = TRAIT_REGISTRY.find_type::<Tr>() {
+        return target.data_from_ptr(ty.unwrap_non_null(Private), Private);
+    }
+
+    let super_type = DataType::any_type(&target).unwrap(Private);
+
+    #[cfg(feature = "julia-1-6")]
+    let ty = jl_new_datatype(
+        name.unwrap(Private),
+        module.unwrap(Private),
+        super_type,
+        jl_emptysvec,
+        jl_emptysvec,
+        jl_emptysvec,
+        1,
+        0,
+        0,
+    );
+
+    #[cfg(not(feature = "julia-1-6"))]
+    let ty = jl_new_datatype(
+        name.unwrap(Private),
+        module.unwrap(Private),
+        super_type,
+        jl_emptysvec,
+        jl_emptysvec,
+        jl_emptysvec,
+        jl_emptysvec,
+        1,
+        0,
+        0,
+    );
+
+    debug_assert!(!ty.is_null());
+    reinit_trait_type::<Tr>(DataType::wrap_non_null(NonNull::new_unchecked(ty), Private));
+    target.data_from_ptr(NonNull::new_unchecked(ty), Private)
+}
+
+/// Registers `ty`, which has been created by [`create_trait_type`] when the module was
+/// precompiled, as the abstract type of the trait `Tr`.
+///
+/// Safety: `ty` must be the abstract type of the trait `Tr`.
+pub unsafe fn reinit_trait_type<Tr>(ty: DataType)
+where
+    Tr: ?Sized + 'static,
+{
+    if TRAIT_REGISTRY.find_type::<Tr>().is_some() {
+        return;
+    }
+
+    TRAIT_REGISTRY
+        .types
+        .write()
+        .expect("Trait registry lock was poisoned")
+        .push((
+            TypeId::of::<Tr>(),
+            DataType::wrap_non_null(ty.unwrap_non_null(Private), Private),
+        ));
+}
+
+/// Registers `T` as an implementation of the trait `Tr`.
+///
+/// Returns an error if `T` isn't a subtype of the abstract type of `Tr`.
+///
+/// Safety: the type `T` and the abstract type of `Tr` must have been created.
+pub unsafe fn register_trait_impl<'target, Tr, T, Tgt>(target: &Tgt) -> JlrsResult<()>
+where
+    Tr: ?Sized + 'static,
+    T: ImplementsTrait<Tr>,
+    Tgt: Target<'target>,
+{
+    let ty = T::julia_type(target).as_managed();
+    let trait_ty = trait_abstract_type::<Tr, _>(target).as_managed();
+    if ty.super_type().unwrap(Private) != trait_ty.unwrap(Private) {
+        Err(JlrsError::exception(format!(
+            "{} is not a subtype of {}",
+            ty.name(),
+            trait_ty.name()
+        )))?;
+    }
+
+    if TRAIT_REGISTRY.find_impl::<Tr>(ty).is_some() {
+        return Ok(());
+    }
+
+    let trait_impl = TraitImpl::<Tr> {
+        shared: call_shared::<Tr, T>,
+        exclusive: call_exclusive::<Tr, T>,
+    };
+
+    TRAIT_REGISTRY
+        .impls
+        .write()
+        .expect("Trait registry lock was poisoned")
+        .push((
+            TypeId::of::<Tr>(),
+            DataType::wrap_non_null(ty.unwrap_non_null(Private), Private),
+            Box::new(trait_impl),
+        ));
+
+    Ok(())
+}
+
+/// Calls `func` with `this` as a trait object and returns the result to Julia.
+pub unsafe fn call_trait_method<Tr, R>(
+    this: TraitObject<Tr>,
+    func: impl FnOnce(&Tr) -> R,
+) -> RustResultRet<R>
+where
+    Tr: ?Sized + 'static,
+    R: IntoJulia + ConstructType,
+{
+    CCall::invoke(|mut frame| match this.with_shared(func) {
+        Ok(output) => {
+            let output = TypedValue::new(&mut frame, output);
+            RustResult::ok(frame.as_extended_target(), output).leak()
+        }
+        Err(e) => RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+    })
+}
+
+/// Calls `func` with `this` as a mutable trait object and returns the result to Julia.
+pub unsafe fn call_trait_method_mut<Tr, R>(
+    this: TraitObject<Tr>,
+    func: impl FnOnce(&mut Tr) -> R,
+) -> RustResultRet<R>
+where
+    Tr: ?Sized + 'static,
+    R: IntoJulia + ConstructType,
+{
+    CCall::invoke(|mut frame| match this.with_exclusive(func) {
+        Ok(output) => {
+            let output = TypedValue::new(&mut frame, output);
+            RustResult::ok(frame.as_extended_target(), output).leak()
+        }
+        Err(e) => RustResult::jlrs_error(frame.as_extended_target(), *e).leak(),
+    })
+}
@@ -23,8 +23,12 @@ use jlrs_macros::julia_version;
 
 use crate::{
     convert::into_julia::IntoJulia,
-    data::managed::{
-        datatype::DataType, private::ManagedPriv, type_name::TypeName, union_all::UnionAll, Managed,
+    data::{
+        layout::valid_layout::LayoutMismatch,
+        managed::{
+            datatype::DataType, private::ManagedPriv, type_name::TypeName, union_all::UnionAll,
+            Managed,
+        },
     },
     memory::target::unrooted::Unrooted,
     private::Private,
@@ -43,6 +47,12 @@ use crate::{
 pub unsafe trait Typecheck {
     /// Returns whether the property implied by `Self` holds true.
     fn typecheck(t: DataType) -> bool;
+
+    /// Returns why the layout of `Self` is incompatible with `t` if `Self` is a layout and
+    /// `typecheck` returns `false`. The default implementation returns `None`.
+    fn mismatch(_t: DataType) -> Option<LayoutMismatch> {
+        None
+    }
 }
 
 #[doc(hidden)]
@@ -4,9 +4,12 @@ use std::error::Error as StdErr;
 
 use thiserror::Error;
 
-use crate::data::managed::{
-    array::dimensions::Dimensions,
-    value::{Value, ValueRef},
+use crate::data::{
+    layout::valid_layout::LayoutMismatch,
+    managed::{
+        array::dimensions::Dimensions,
+        value::{Value, ValueRef},
+    },
 };
 
 pub(crate) static CANNOT_DISPLAY_TYPE: &'static str = "<Cannot display type>";
@@ -72,6 +75,8 @@ pub enum TypeError {
     NoBaseType,
     #[error("The layout of this type is incompatible with {base_type}")]
     IncompatibleBaseType { base_type: String },
+    #[error("element type {element_type} is not supported")]
+    UnsupportedElementType { element_type: String },
 }
 
 /// Array layout errors.
@@ -87,6 +92,8 @@ pub enum ArrayLayoutError {
     NotPointer { element_type: String },
     #[error("rank is {found}, not {provided}")]
     RankMismatch { found: isize, provided: isize },
+    #[error("{value_type} is not a strided view of an Array")]
+    NotStrided { value_type: String },
 }
 
 /// Data access errors.
@@ -99,6 +106,8 @@ pub enum AccessError {
     },
     #[error("layout is invalid for {value_type}")]
     InvalidLayout { value_type: String },
+    #[error("{0}")]
+    LayoutMismatch(LayoutMismatch),
     #[error("no value named {name} in {module}")]
     GlobalNotFound { name: String, module: String },
     #[error("the current value is locked")]
@@ -122,6 +131,8 @@ pub enum AccessError {
     OutOfBoundsSVec { idx: usize, len: usize },
     #[error("index {idx} is invalid for array with shape {sz}")]
     InvalidIndex { idx: Dimensions, sz: Dimensions },
+    #[error("axis {axis} is out-of-bounds for array of rank {rank}")]
+    OutOfBoundsAxis { axis: usize, rank: usize },
     #[error("arrays can only be accessed with n-dimensional indices")]
     ArrayNeedsNumericalIndex,
     #[error("fields cannot be accessed with n-dimensional indices")]
@@ -141,6 +152,8 @@ pub enum InstantiationError {
     NamedTupleSizeMismatch { n_names: usize, n_values: usize },
     #[error("expected a shape for {vec_size} elements, got a shape for {dim_size} elements")]
     ArraySizeMismatch { dim_size: usize, vec_size: usize },
+    #[error("invalid sparse matrix: {reason}")]
+    InvalidSparseMatrix { reason: String },
 }
 
 /// Julia exception converted to a string.
@@ -17,8 +17,8 @@
 //!  - Create values that Julia can use, and convert them back to Rust, from Rust.
 //!  - Access the type information and fields of values. The contents of inline and bits-union
 //!    fields can be accessed directly.
-//!  - Create and use n-dimensional arrays. The `jlrs-ndarray` feature can be enabled for
-//!    integration with ndarray.
+//!  - Create and use n-dimensional arrays. The `jlrs-ndarray` and `jlrs-nalgebra` features can
+//!    be enabled for integration with ndarray and nalgebra.
 //!  - Map Julia structs to Rust structs, the Rust implementation can be generated with the
 //!    JlrsCore package.
 //!  - Structs that can be mapped to Rust include those with type parameters and bits unions.
@@ -157,9 +157,32 @@
 //!   structs, this generated code uses custom derive macros made available with this feature to
 //!   enable the safe conversion of data from Julia to Rust, and from Rust to Julia in some cases.
 //!
+//! - `layout-registry`
+//!
+//!   Registers all layouts that derive `ValidLayout` so they can be checked against their Julia
+//!   types with `verify_all_layouts`, for example when your application starts or in a test.
+//!
 //! - `jlrs-ndarray`
 //!
-//!   Access the content of a Julia array as an `ArrayView` or `ArrayViewMut` from ndarray.
+//!   Access the content of a Julia array as an `ArrayView` or `ArrayViewMut` from ndarray, and
+//!   move an `Array` from ndarray into Julia.
+//!
+//! - `jlrs-nalgebra`
+//!
+//!   Access the content of a Julia array as a `DMatrixView` or `DMatrixViewMut` from nalgebra,
+//!   and move a `DMatrix` or `DVector` from nalgebra into Julia.
+//!
+//! - `jlrs-bitvec`
+//!
+//!   Convert a `BitArray` to and from a `BitVec` from bitvec.
+//!
+//! - `rayon`
+//!
+//!   Iterate over the content of an array of `isbits` elements in parallel with rayon.
+//!
+//! - `sprs`
+//!
+//!   Convert a `SparseMatrixCsc` to and from a `CsMat` from sprs.
 //!
 //! - `f16`
 //!
@@ -710,7 +733,9 @@
 //! In order to map a struct in Rust to one in Julia you can derive several traits. You normally
 //! shouldn't need to implement these structs or traits manually. The `reflect` function defined
 //! in the `JlrsCore.Reflect` module can generate Rust structs whose layouts match their counterparts
-//! in Julia and automatically derive the supported traits.
+//! in Julia and automatically derive the supported traits. With the `jlrs-derive` feature, the
+//! `reflect!` macro calls this function at compile time and expands to the generated code, which
+//! avoids having to copy the generated layouts into your crate and update them manually.
 //!
 //! The main restriction is that structs with atomic fields, and tuple or union fields with type
 //! parameters are not supported. The reason for this restriction is that the layout of such
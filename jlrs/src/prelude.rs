@@ -7,7 +7,8 @@ pub use jlrs_macros::julia_module;
 pub use jlrs_macros::julia_version;
 #[cfg(feature = "jlrs-derive")]
 pub use jlrs_macros::{
-    CCallArg, CCallReturn, ConstructType, IntoJulia, Typecheck, Unbox, ValidField, ValidLayout,
+    reflect, CCallArg, CCallReturn, ConstructType, Enum, IntoJulia, Typecheck, Unbox, UnionEnum,
+    ValidField, ValidLayout,
 };
 
 #[cfg(feature = "ccall")]
@@ -56,6 +57,7 @@ pub use crate::{
             /* Ref, */ Managed,
         },
     },
+    dispatch_array,
     error::JlrsResult,
     memory::target::{target_type::TargetType, Target},
     named_tuple,
@@ -0,0 +1,148 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use jlrs::{
+        data::managed::array::{buffer::ArrayBuffer, dimensions::Dims},
+        memory::gc::{Gc, GcCollection},
+        prelude::*,
+    };
+
+    use crate::util::JULIA;
+
+    static RELEASED: AtomicBool = AtomicBool::new(false);
+
+    struct SharedBuffer(Arc<[f64]>);
+
+    unsafe impl ArrayBuffer for SharedBuffer {
+        type Element = f64;
+
+        fn as_mut_ptr(&mut self) -> *mut f64 {
+            self.0.as_ptr() as *mut f64
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    struct ReleaseBuffer(Vec<u32>);
+
+    unsafe impl ArrayBuffer for ReleaseBuffer {
+        type Element = u32;
+
+        fn as_mut_ptr(&mut self) -> *mut u32 {
+            self.0.as_mut_ptr()
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn release(self) {
+            RELEASED.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn array_from_arc_buffer() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            let shared: Arc<[f64]> = Arc::from(vec![1.0, 2.0, 3.0, 4.0]);
+            let buffer = SharedBuffer(shared.clone());
+
+            let unboxed = jlrs
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let array = Array::from_buffer(frame.as_extended_target(), buffer, (2, 2))?
+                        .into_jlrs_result()?;
+                    let ptr = unsafe { array.bits_data::<f64>()?.as_slice().as_ptr() };
+                    assert_eq!(ptr, shared.as_ptr());
+                    unsafe { array.copy_inline_data::<f64>() }
+                })
+                .unwrap();
+
+            let (data, dims) = unboxed.splat();
+            assert_eq!(dims.rank(), 2);
+            assert_eq!(dims.n_elements(0), 2);
+            assert_eq!(dims.n_elements(1), 2);
+            assert_eq!(data.to_vec(), vec![1., 2., 3., 4.]);
+        });
+    }
+
+    fn typed_array_from_vec_buffer() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            let unboxed = jlrs
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = vec![1i32, 2, 3];
+                    let array =
+                        TypedArray::<i32>::from_buffer(frame.as_extended_target(), data, 3)?
+                            .into_jlrs_result()?;
+                    unsafe { array.copy_inline_data() }
+                })
+                .unwrap();
+
+            let (data, dims) = unboxed.splat();
+            assert_eq!(dims.rank(), 1);
+            assert_eq!(dims.n_elements(0), 3);
+            assert_eq!(data.to_vec(), vec![1, 2, 3]);
+        });
+    }
+
+    fn array_from_buffer_size_mismatch() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = vec![1u8, 2, 3].into_boxed_slice();
+                    let res = Array::from_buffer(frame.as_extended_target(), data, (2, 2));
+                    assert!(res.is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn buffer_is_released() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    frame.scope(|mut frame| {
+                        let buffer = ReleaseBuffer(vec![1, 2, 3]);
+                        Array::from_buffer(frame.as_extended_target(), buffer, 3)?
+                            .into_jlrs_result()?;
+                        Ok(())
+                    })?;
+
+                    frame.gc_collect(GcCollection::Full);
+                    frame.gc_collect(GcCollection::Full);
+                    assert!(RELEASED.load(Ordering::SeqCst));
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn array_buffer_tests() {
+        array_from_arc_buffer();
+        typed_array_from_vec_buffer();
+        array_from_buffer_size_mismatch();
+        buffer_is_released();
+    }
+}
@@ -0,0 +1,184 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use std::thread;
+
+    use jlrs::prelude::*;
+
+    use crate::util::JULIA;
+
+    fn split_columns() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let data: Vec<f64> = (0..12).map(|i| i as f64).collect();
+                    let mut arr = Array::from_vec(frame.as_extended_target(), data, (3, 4))?
+                        .into_jlrs_result()?;
+
+                    {
+                        let mut tracked = arr.track_exclusive()?;
+                        let mut accessor = tracked.bits_data_mut::<f64>()?;
+                        let columns = accessor.columns_mut()?;
+                        assert_eq!(columns.len(), 4);
+
+                        thread::scope(|s| {
+                            for mut column in columns {
+                                s.spawn(move || {
+                                    assert!(column.is_contiguous());
+                                    let offset = column.offset() as f64;
+                                    for x in column.as_mut_slice().unwrap() {
+                                        *x += 100.0 * offset;
+                                    }
+                                });
+                            }
+                        });
+                    }
+
+                    let accessor = arr.bits_data::<f64>()?;
+                    assert_eq!(accessor[(0, 0)], 0.0);
+                    assert_eq!(accessor[(2, 1)], 105.0);
+                    assert_eq!(accessor[(1, 3)], 310.0);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn split_rows() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let data: Vec<i32> = (0..12).collect();
+                    let mut arr = Array::from_vec(frame.as_extended_target(), data, (4, 3))?
+                        .into_jlrs_result()?;
+
+                    {
+                        let mut accessor = arr.bits_data_mut::<i32>()?;
+                        let chunks = accessor.axis_chunks_mut(0, 3)?;
+                        assert_eq!(chunks.len(), 2);
+
+                        thread::scope(|s| {
+                            for mut chunk in chunks {
+                                s.spawn(move || {
+                                    assert!(!chunk.is_contiguous());
+                                    assert!(chunk.as_mut_slice().is_none());
+                                    let n_rows = chunk.dimensions().as_slice()[0];
+                                    for row in 0..n_rows {
+                                        for col in 0..3 {
+                                            chunk[(row, col)] *= -1;
+                                        }
+                                    }
+                                });
+                            }
+                        });
+                    }
+
+                    let accessor = arr.bits_data::<i32>()?;
+                    assert_eq!(
+                        accessor.as_slice(),
+                        &(0..12).map(|i| -i).collect::<Vec<_>>()
+                    );
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn chunk_indices() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let data: Vec<u8> = (0..24).collect();
+                    let mut arr = Array::from_vec(frame.as_extended_target(), data, (2, 3, 4))?
+                        .into_jlrs_result()?;
+
+                    let mut accessor = arr.bits_data_mut::<u8>()?;
+                    let mut chunks = accessor.axis_chunks_mut(1, 2)?;
+
+                    let first = chunks.next().unwrap();
+                    assert_eq!(first.offset(), 0);
+                    assert_eq!(first.dimensions().as_slice(), &[2, 2, 4]);
+                    assert_eq!(first[(1, 1, 2)], 15);
+                    assert!(first.get((0, 2, 0)).is_none());
+
+                    let second = chunks.next().unwrap();
+                    assert_eq!(second.offset(), 2);
+                    assert_eq!(second.dimensions().as_slice(), &[2, 1, 4]);
+                    assert_eq!(second[(1, 0, 3)], 23);
+
+                    assert!(chunks.next().is_none());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn invalid_axis() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut arr = Array::new::<f32, _, _>(frame.as_extended_target(), 4)
+                        .into_jlrs_result()?;
+                    let mut accessor = arr.bits_data_mut::<f32>()?;
+                    assert!(accessor.axis_chunks_mut(1, 1).is_err());
+                    assert!(accessor.columns_mut().is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[cfg(feature = "rayon")]
+    fn parallel_iteration() {
+        use rayon::prelude::*;
+
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let data: Vec<f64> = (0..16).map(|i| i as f64).collect();
+                    let mut arr = Array::from_vec(frame.as_extended_target(), data, (4, 4))?
+                        .into_jlrs_result()?;
+
+                    {
+                        let mut accessor = arr.bits_data_mut::<f64>()?;
+                        accessor.par_iter_mut().for_each(|x| *x *= 2.0);
+                        accessor
+                            .par_axis_chunks_mut(1, 1)?
+                            .for_each(|mut column| column[(0, 0)] = -1.0);
+                    }
+
+                    let accessor = arr.bits_data::<f64>()?;
+                    let sum: f64 = accessor.par_iter().sum();
+                    assert_eq!(sum, 2.0 * 120.0 - (0.0 + 8.0 + 16.0 + 24.0) - 4.0);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn array_chunks_tests() {
+        split_columns();
+        split_rows();
+        chunk_indices();
+        invalid_axis();
+        #[cfg(feature = "rayon")]
+        parallel_iteration();
+    }
+}
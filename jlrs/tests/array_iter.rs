@@ -0,0 +1,150 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{data::managed::array::dimensions::CartesianIndex, prelude::*};
+
+    use crate::util::JULIA;
+
+    fn iterate_bits_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(&mut frame, "reshape(collect(1:6), 2, 3)")
+                        .into_jlrs_result()?
+                        .cast::<Array>()?;
+                    let data = arr.bits_data::<isize>()?;
+
+                    for (idx, value) in data.indexed_iter() {
+                        assert_eq!(data[idx.as_slice()], *value);
+                    }
+
+                    let indices: Vec<_> = data.indices().map(|i| i.as_slice().to_vec()).collect();
+                    assert_eq!(indices[3], vec![1, 1]);
+
+                    let columns: Vec<_> = data.columns()?.collect();
+                    assert_eq!(columns, vec![&[1, 2][..], &[3, 4][..], &[5, 6][..]]);
+
+                    let rows: Vec<Vec<_>> =
+                        data.lanes(1)?.map(|row| row.copied().collect()).collect();
+                    assert_eq!(rows, vec![vec![1, 3, 5], vec![2, 4, 6]]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn iterate_bits_array_mut() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut arr = Array::new::<usize, _, _>(frame.as_extended_target(), (2, 2))
+                        .into_jlrs_result()?;
+
+                    {
+                        let mut data = arr.bits_data_mut::<usize>()?;
+                        for (idx, value) in data.indexed_iter_mut() {
+                            *value = 10 * idx.as_slice()[0] + idx.as_slice()[1];
+                        }
+                    }
+
+                    let data = arr.bits_data::<usize>()?;
+                    assert_eq!(data.as_slice(), &[0, 10, 1, 11]);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn iterate_copied_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(&mut frame, "reshape(collect(1.0:8.0), 2, 2, 2)")
+                        .into_jlrs_result()?
+                        .cast::<Array>()?;
+                    let copied = arr.copy_inline_data::<f64>()?;
+
+                    assert_eq!(copied.indexed_iter().count(), 8);
+                    let lanes: Vec<Vec<_>> = copied
+                        .lanes(2)?
+                        .map(|lane| lane.copied().collect())
+                        .collect();
+                    assert_eq!(lanes.len(), 4);
+                    assert_eq!(lanes[0], vec![1.0, 5.0]);
+                    assert_eq!(lanes[3], vec![4.0, 8.0]);
+                    assert!(copied.lanes(3).is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_cartesian_index() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let value = Value::eval_string(&mut frame, "CartesianIndex(2, 3)")
+                        .into_jlrs_result()?;
+                    let idx = CartesianIndex::from_julia(value)?;
+                    assert_eq!(idx.as_slice(), &[2, 3]);
+                    assert_eq!(idx.to_zero_based().as_slice(), &[1, 2]);
+
+                    let arr = Value::eval_string(&mut frame, "reshape(collect(1:6), 2, 3)")
+                        .into_jlrs_result()?
+                        .cast::<Array>()?;
+                    let data = arr.bits_data::<isize>()?;
+                    assert_eq!(data[idx.to_zero_based()], 6);
+
+                    let value = CartesianIndex::from_zero_based(&(0, 1))
+                        .to_julia(frame.as_extended_target())?
+                        .into_jlrs_result()?;
+                    let func = Module::base(&frame)
+                        .function(&frame, "getindex")?
+                        .as_managed();
+                    let elem = func
+                        .call2(&mut frame, arr.as_value(), value)
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+                    assert_eq!(elem, 3);
+
+                    let invalid = Value::eval_string(&mut frame, "CartesianIndex(0, 1)")
+                        .into_jlrs_result()?;
+                    assert!(CartesianIndex::from_julia(invalid).is_err());
+
+                    // A type with the same name but a different layout must be rejected.
+                    let other = Value::eval_string(
+                        &mut frame,
+                        "module OtherCartesian; struct CartesianIndex; x::Float64; end; end; OtherCartesian.CartesianIndex(1.0)",
+                    )
+                    .into_jlrs_result()?;
+                    assert!(CartesianIndex::from_julia(other).is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn array_iter_tests() {
+        iterate_bits_array();
+        iterate_bits_array_mut();
+        iterate_copied_array();
+        convert_cartesian_index();
+    }
+}
@@ -0,0 +1,128 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{data::managed::array::bit_array::BitArray, prelude::*};
+
+    use crate::util::JULIA;
+
+    fn access_bit_vector() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let arr =
+                        Value::eval_string(&mut frame, "[1, -2, 3, 0] .> 0").into_jlrs_result()?;
+                    assert!(!arr.is::<Array>());
+
+                    let arr = arr.cast::<BitArray>()?;
+                    assert_eq!(arr.len(), 4);
+                    assert_eq!(arr.rank(), 1);
+                    assert_eq!(arr.dimensions().as_slice(), &[4]);
+                    assert_eq!(arr.get(0), Some(true));
+                    assert_eq!(arr.get(1), Some(false));
+                    assert_eq!(arr.get(4), None);
+                    assert_eq!(arr.to_vec(), vec![true, false, true, false]);
+                    assert_eq!(unsafe { arr.chunks_slice() }, &[0b0101]);
+                    assert!(arr.chunks().as_value().is::<Array>());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn access_bit_matrix() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let arr = Value::eval_string(&mut frame, "reshape(1:6, 2, 3) .> 3")
+                        .into_jlrs_result()?
+                        .cast::<BitArray>()?;
+
+                    assert_eq!(arr.rank(), 2);
+                    assert_eq!(arr.dimensions().as_slice(), &[2, 3]);
+                    assert_eq!(arr.get((1, 1)), Some(true));
+                    assert_eq!(arr.get((0, 1)), Some(false));
+                    assert_eq!(arr.get((0, 3)), None);
+
+                    unsafe { arr.set((0, 0), true)? };
+                    unsafe { arr.set((1, 2), false)? };
+                    assert_eq!(arr.to_vec(), vec![true, false, false, true, true, false]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn create_bit_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let arr =
+                        BitArray::new(frame.as_extended_target(), (3, 2))?.into_jlrs_result()?;
+                    assert_eq!(arr.len(), 6);
+                    assert_eq!(arr.to_vec(), vec![false; 6]);
+
+                    let data: Vec<bool> = (0..100).map(|i| i % 3 == 0).collect();
+                    let arr = BitArray::from_bools(frame.as_extended_target(), &data, 100)?
+                        .into_jlrs_result()?;
+                    assert_eq!(arr.to_vec(), data);
+
+                    let func = Module::base(&frame).function(&frame, "count")?.as_managed();
+                    let count = unsafe { func.call1(&mut frame, arr.as_value()) }
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+                    assert_eq!(count, 34);
+
+                    assert!(BitArray::from_bools(frame.as_extended_target(), &data, 99).is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[cfg(feature = "jlrs-bitvec")]
+    fn convert_bitvec() {
+        use bitvec::prelude::*;
+
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let bits = bitvec![u8, Msb0; 1, 0, 1, 1, 0, 0, 1, 0, 1, 1];
+                    let arr = BitArray::from_bitslice(frame.as_extended_target(), &bits, (5, 2))?
+                        .into_jlrs_result()?;
+                    assert_eq!(arr.get((2, 0)), Some(true));
+                    assert_eq!(arr.get((0, 1)), Some(false));
+
+                    let converted = arr.to_bitvec();
+                    assert_eq!(converted.len(), 10);
+                    assert!(converted.iter().by_vals().eq(bits.iter().by_vals()));
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn bit_array_tests() {
+        access_bit_vector();
+        access_bit_matrix();
+        create_bit_array();
+        #[cfg(feature = "jlrs-bitvec")]
+        convert_bitvec();
+    }
+}
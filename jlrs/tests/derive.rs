@@ -3,7 +3,15 @@ mod util;
 
 #[cfg(all(test, feature = "jlrs-derive", feature = "sync-rt"))]
 mod tests {
-    use jlrs::prelude::*;
+    #[cfg(feature = "layout-registry")]
+    use jlrs::data::layout::registry::{
+        registered_layouts, verify_all_layouts, LayoutVerificationError,
+    };
+    use jlrs::{
+        data::layout::valid_layout::MismatchReason,
+        error::{AccessError, JlrsError},
+        prelude::*,
+    };
 
     use super::util::{new_derive_impls::*, JULIA_DERIVE};
     fn derive_bits_type_bool() {
@@ -28,6 +36,114 @@ mod tests {
         })
     }
 
+    fn derive_layout_mismatch() {
+        JULIA_DERIVE.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let s = BitsTypeInt32 { a: 1 };
+                    let v = Value::new(&mut frame, s);
+
+                    let err = v.unbox::<BitsTypeInt64>().unwrap_err();
+                    match *err {
+                        JlrsError::AccessError(AccessError::LayoutMismatch(ref mismatch)) => {
+                            match mismatch.reason {
+                                MismatchReason::Field(ref field) => {
+                                    assert_eq!(field.index, 0);
+                                    assert_eq!(field.name, "a");
+                                    assert_eq!(field.size, 4);
+                                    assert_eq!(field.offset, 0);
+                                    assert_eq!(field.rust_type, "i64");
+                                }
+                                _ => panic!("expected a field mismatch"),
+                            }
+                        }
+                        _ => panic!("expected a layout mismatch"),
+                    }
+
+                    let err = v.unbox::<BitsIntBool>().unwrap_err();
+                    match *err {
+                        JlrsError::AccessError(AccessError::LayoutMismatch(ref mismatch)) => {
+                            match mismatch.reason {
+                                MismatchReason::FieldCount { expected, found } => {
+                                    assert_eq!(expected, 2);
+                                    assert_eq!(found, 1);
+                                }
+                                _ => panic!("expected a field count mismatch"),
+                            }
+                        }
+                        _ => panic!("expected a layout mismatch"),
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    #[cfg(feature = "layout-registry")]
+    fn derive_registered_layouts() {
+        JULIA_DERIVE.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|frame| {
+                    let main = Module::main(&frame);
+                    let names = [
+                        "BitsTypeBool",
+                        "BitsTypeInt32",
+                        "BitsTypeInt64",
+                        "BitsIntBool",
+                        "BitsCharFloat32Float64",
+                        "BitsUInt8TupleInt32Int64",
+                    ];
+
+                    for name in names {
+                        let julia_type = format!("Main.{}", name);
+                        let layout = registered_layouts()
+                            .find(|l| l.julia_type == julia_type)
+                            .unwrap_or_else(|| panic!("{} has not been registered", name));
+                        assert!(layout.rust_type.ends_with(&format!("::{}", name)));
+
+                        let ty = unsafe { main.global(&frame, name).unwrap().as_managed() };
+                        assert!((layout.layout_mismatch)(ty).is_none());
+                    }
+
+                    // The registered check must reject other Julia types.
+                    let int32 = registered_layouts()
+                        .find(|l| l.julia_type == "Main.BitsTypeInt32")
+                        .unwrap();
+                    let int64_ty =
+                        unsafe { main.global(&frame, "BitsTypeInt64").unwrap().as_managed() };
+                    let mismatch = (int32.layout_mismatch)(int64_ty).unwrap();
+                    assert!(mismatch.julia_type.ends_with("BitsTypeInt64"));
+
+                    if let Err(errors) = verify_all_layouts(&frame) {
+                        for error in errors {
+                            match error {
+                                LayoutVerificationError::TypeNotFound { julia_type, .. } => {
+                                    assert!(!names
+                                        .iter()
+                                        .any(|n| julia_type == format!("Main.{}", n)))
+                                }
+                                LayoutVerificationError::Mismatch(mismatch) => {
+                                    assert!(!names.iter().any(|n| mismatch.julia_type.ends_with(n)))
+                                }
+                            }
+                        }
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
     /*
        fn derive_generic_tu() {
            JULIA_DERIVE.with(|j| {
@@ -1100,6 +1216,9 @@ mod tests {
     #[test]
     fn derive_tests() {
         derive_bits_type_bool();
+        derive_layout_mismatch();
+        #[cfg(feature = "layout-registry")]
+        derive_registered_layouts();
         //derive_generic_tu();
         // derive_bits_type_char();
         // derive_bits_type_uint8();
@@ -0,0 +1,106 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use std::any::type_name;
+
+    use jlrs::{
+        data::layout::{complex::Complex, valid_layout::ValidField},
+        prelude::*,
+    };
+
+    use crate::util::JULIA;
+
+    fn first_as_f64<T: ValidField + Copy + Into<f64>>(array: TypedArray<T>) -> JlrsResult<f64> {
+        // Safety: the data is only read.
+        let data = unsafe { array.bits_data()? };
+        Ok(data.as_slice()[0].into())
+    }
+
+    fn element_type_name<T: ValidField>(_: &TypedArray<T>) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn dispatch_default_types() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let cases = [
+                        ("Int8[1, 2]", type_name::<i8>()),
+                        ("Int64[1, 2]", type_name::<i64>()),
+                        ("UInt16[1, 2]", type_name::<u16>()),
+                        ("UInt64[1, 2]", type_name::<u64>()),
+                        ("Float32[1, 2]", type_name::<f32>()),
+                        ("Float64[1, 2]", type_name::<f64>()),
+                        ("Bool[true, false]", type_name::<bool>()),
+                        ("ComplexF64[1, 2im]", type_name::<Complex<f64>>()),
+                    ];
+
+                    for (code, expected) in cases {
+                        let array = Value::eval_string(&mut frame, code)
+                            .into_jlrs_result()?
+                            .cast::<Array>()?;
+
+                        let name = dispatch_array!(array, |typed| {
+                            assert_eq!(typed.dimensions().size(), 2);
+                            element_type_name(&typed)
+                        })?;
+                        assert_eq!(name, expected);
+                    }
+
+                    let array = Value::eval_string(&mut frame, "Any[1, 2]")
+                        .into_jlrs_result()?
+                        .cast::<Array>()?;
+                    let res = dispatch_array!(array, |typed| typed.element_size());
+                    assert!(res.is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn dispatch_custom_types() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let array = Value::eval_string(&mut frame, "UInt8[3, 4]")
+                        .into_jlrs_result()?
+                        .cast::<Array>()?;
+                    let first =
+                        dispatch_array!(array, [i32, u8, f32], |typed| first_as_f64(typed))??;
+                    assert_eq!(first, 3.0);
+
+                    let array = Value::eval_string(&mut frame, "Int64[3, 4]")
+                        .into_jlrs_result()?
+                        .cast::<Array>()?;
+                    let res = dispatch_array!(array, [i32, u8, f32], |typed| first_as_f64(typed));
+                    assert!(res.is_err());
+
+                    let array = Value::eval_string(&mut frame, "ComplexF32[1 + 2im]")
+                        .into_jlrs_result()?
+                        .cast::<Array>()?;
+                    let value = dispatch_array!(array, [Complex<f32>], |typed| {
+                        let data = unsafe { typed.bits_data()? };
+                        data.as_slice()[0]
+                    })?;
+                    assert_eq!(value, Complex::new(1.0, 2.0));
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn dispatch_array_tests() {
+        dispatch_default_types();
+        dispatch_custom_types();
+    }
+}
@@ -0,0 +1,78 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "ccall"))]
+mod tests {
+    use jlrs::prelude::*;
+
+    use super::util::JULIA;
+
+    unsafe extern "C" fn add_one(a: isize) -> isize {
+        a + 1
+    }
+
+    julia_module! {
+        become install_rust_module_test_init_fn;
+
+        fn add_one(a: isize) -> isize;
+    }
+
+    fn install_rust_module() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let module = Value::eval_string(&mut frame, "module RustModuleTest end")
+                        .into_jlrs_result()?
+                        .cast::<Module>()?;
+
+                    module
+                        .install_rust_module(
+                            frame.as_extended_target(),
+                            install_rust_module_test_init_fn,
+                        )?
+                        .into_jlrs_result()?;
+
+                    let a = Value::new(&mut frame, 1isize);
+                    let res = module
+                        .function(&frame, "add_one")?
+                        .as_managed()
+                        .call1(&mut frame, a)
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+
+                    assert_eq!(res, 2);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn install_rust_module_twice() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let res = Module::main(&frame)
+                        .install_rust_module(
+                            frame.as_extended_target(),
+                            install_rust_module_test_init_fn,
+                        )?
+                        .into_jlrs_result()?;
+
+                    assert!(res.is::<Nothing>());
+                    assert!(Module::main(&frame).global(&frame, "add_one").is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn install_rust_module_tests() {
+        install_rust_module();
+        install_rust_module_twice();
+    }
+}
@@ -0,0 +1,179 @@
+mod util;
+
+#[cfg(test)]
+#[cfg(all(feature = "sync-rt", feature = "jlrs-nalgebra"))]
+mod tests {
+    use jlrs::{
+        convert::{
+            into_jlrs_result::IntoJlrsResult,
+            nalgebra::{NalgebraIntoJulia, NalgebraView, NalgebraViewMut},
+        },
+        data::managed::array::{Array, TypedArray},
+        memory::stack_frame::StackFrame,
+    };
+    use nalgebra::{DMatrix, DVector};
+
+    use super::util::JULIA;
+
+    fn bits_matrix_view() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let mut data = vec![1usize, 2, 3, 4, 5, 6];
+                    let slice = &mut data.as_mut_slice();
+                    let borrowed = unsafe {
+                        Array::from_slice_unchecked(frame.as_extended_target(), slice, (3, 2))?
+                    };
+
+                    let data = unsafe { borrowed.bits_data::<usize>()? };
+                    let x = data[(2, 1)];
+
+                    let matrix = data.matrix_view()?;
+                    assert_eq!(matrix.shape(), (3, 2));
+                    assert_eq!(matrix[(2, 1)], x);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn bits_matrix_view_mut() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut data = vec![1usize, 2, 3, 4, 5, 6];
+                    let slice = &mut data.as_mut_slice();
+                    let mut borrowed =
+                        Array::from_slice_unchecked(frame.as_extended_target(), slice, (3, 2))?;
+
+                    let x = {
+                        let mut inline = borrowed.bits_data_mut::<usize>()?;
+                        let x = inline[(2, 1)];
+
+                        let mut matrix = inline.matrix_view_mut()?;
+                        matrix[(2, 1)] += 1;
+                        x
+                    };
+
+                    let inline = borrowed.bits_data::<usize>()?;
+                    assert_eq!(inline[(2, 1)], x + 1);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn vector_matrix_view() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let arr = TypedArray::<f32>::from_vec(
+                        frame.as_extended_target(),
+                        vec![1.0, 2.0, 3.0],
+                        3,
+                    )?
+                    .into_jlrs_result()?;
+
+                    let data = unsafe { arr.bits_data()? };
+                    let matrix = data.matrix_view()?;
+                    assert_eq!(matrix.shape(), (3, 1));
+                    assert_eq!(matrix[(1, 0)], 2.0);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn rank_3_matrix_view_is_err() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let arr = TypedArray::<u8>::new(frame.as_extended_target(), (1, 2, 3))
+                        .into_jlrs_result()?;
+
+                    let data = unsafe { arr.bits_data()? };
+                    assert!(data.matrix_view().is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn matrix_into_julia() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let matrix = DMatrix::from_row_slice(2, 3, &[1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0]);
+                    let ptr = matrix.as_ptr();
+
+                    let arr = matrix
+                        .into_julia_array(frame.as_extended_target())?
+                        .into_jlrs_result()?;
+                    let data = unsafe { arr.bits_data()? };
+                    assert_eq!(unsafe { data.dimensions().as_slice() }, &[2, 3]);
+                    assert_eq!(data.as_slice().as_ptr(), ptr);
+                    assert_eq!(data[(1, 0)], 4.0);
+                    assert_eq!(data[(0, 2)], 3.0);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn vector_into_julia() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let vector = DVector::from_vec(vec![1i64, 2, 3]);
+
+                    let arr = vector
+                        .into_julia_array(frame.as_extended_target())?
+                        .into_jlrs_result()?;
+                    let data = unsafe { arr.bits_data()? };
+                    assert_eq!(unsafe { data.dimensions().as_slice() }, &[3]);
+                    assert_eq!(data.as_slice(), &[1, 2, 3]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn nalgebra_tests() {
+        bits_matrix_view();
+        bits_matrix_view_mut();
+        vector_matrix_view();
+        rank_3_matrix_view_is_err();
+        matrix_into_julia();
+        vector_into_julia();
+    }
+}
@@ -4,10 +4,17 @@ mod util;
 #[cfg(all(feature = "sync-rt", feature = "jlrs-ndarray"))]
 mod tests {
     use jlrs::{
-        convert::ndarray::{NdArrayView, NdArrayViewMut},
-        data::managed::array::{Array, TypedArray},
+        convert::{
+            into_jlrs_result::IntoJlrsResult,
+            ndarray::{NdArrayIntoJulia, NdArrayView, NdArrayViewMut},
+        },
+        data::managed::{
+            array::{strided::SubArray, Array, TypedArray},
+            value::Value,
+        },
         memory::stack_frame::StackFrame,
     };
+    use ndarray::{s, Array2, ShapeBuilder};
 
     use super::util::JULIA;
 
@@ -149,6 +156,134 @@ mod tests {
         });
     }
 
+    fn strided_array_view() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(
+                        &mut frame,
+                        "view(reshape(collect(1:12), 3, 4), 3:-2:1, 2:2:4)",
+                    )
+                    .into_jlrs_result()?
+                    .cast::<SubArray>()?;
+
+                    let data = arr.bits_data::<i64>(&mut frame)?;
+                    let array = data.array_view();
+                    assert_eq!(array.shape(), &[2, 2]);
+                    assert_eq!(array[[0, 0]], 6);
+                    assert_eq!(array[[1, 0]], 4);
+                    assert_eq!(array[[0, 1]], 12);
+                    assert_eq!(array[[1, 1]], 10);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn strided_array_view_mut() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut arr = Value::eval_string(&mut frame, "view(zeros(Int, 4, 4), 2, :)")
+                        .into_jlrs_result()?
+                        .cast::<SubArray>()?;
+
+                    let mut data = arr.bits_data_mut::<i64>(&mut frame)?;
+                    let mut array = data.array_view_mut();
+                    array[[3]] = 1;
+                    assert_eq!(data[3], 1);
+                    assert_eq!(data.array().bits_data::<i64>()?[(1, 3)], 1);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn column_major_into_julia() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = vec![1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0];
+                    let ptr = data.as_ptr();
+                    let array = Array2::from_shape_vec((2, 3).f(), data).unwrap();
+
+                    let arr = array
+                        .into_julia_array(frame.as_extended_target())?
+                        .into_jlrs_result()?;
+                    let data = unsafe { arr.bits_data()? };
+                    assert_eq!(unsafe { data.dimensions().as_slice() }, &[2, 3]);
+                    assert_eq!(data.as_slice().as_ptr(), ptr);
+                    assert_eq!(data[(1, 2)], 6.0);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn row_major_into_julia() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = vec![1i32, 2, 3, 4, 5, 6];
+                    let array = Array2::from_shape_vec((2, 3), data).unwrap();
+
+                    let arr = array
+                        .into_julia_array(frame.as_extended_target())?
+                        .into_jlrs_result()?;
+                    let data = unsafe { arr.bits_data()? };
+                    assert_eq!(unsafe { data.dimensions().as_slice() }, &[2, 3]);
+                    assert_eq!(data.as_slice(), &[1, 4, 2, 5, 3, 6]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn sliced_into_julia() {
+        JULIA.with(|j| {
+            let mut julia = j.borrow_mut();
+            let mut frame = StackFrame::new();
+
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = vec![1u8, 2, 3, 4, 5, 6];
+                    let mut array = Array2::from_shape_vec((2, 3).f(), data).unwrap();
+                    array.slice_collapse(s![.., 1..]);
+
+                    let arr = array
+                        .into_julia_array(frame.as_extended_target())?
+                        .into_jlrs_result()?;
+                    let data = unsafe { arr.bits_data()? };
+                    assert_eq!(unsafe { data.dimensions().as_slice() }, &[2, 2]);
+                    assert_eq!(data.as_slice(), &[3, 4, 5, 6]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
     #[test]
     fn ndarray_tests() {
         bits_array_view();
@@ -156,5 +291,10 @@ mod tests {
         inline_array_view();
         copied_array_view();
         copied_array_view_mut();
+        strided_array_view();
+        strided_array_view_mut();
+        column_major_into_julia();
+        row_major_into_julia();
+        sliced_into_julia();
     }
 }
@@ -0,0 +1,136 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{data::types::construct_type::ConstructType, prelude::*};
+
+    use crate::util::JULIA;
+
+    #[cfg(feature = "jlrs-derive")]
+    #[repr(C)]
+    #[derive(Clone, Debug, Unbox, ValidLayout, ValidField, Typecheck)]
+    struct WithNTuple<const N: usize> {
+        position: [f64; N],
+        id: i32,
+    }
+
+    fn ntuple_into_julia() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let value = Value::new(&mut frame, [1.0f64, 2.0, 3.0]);
+                    assert!(value.is::<[f64; 3]>());
+                    assert!(!value.is::<[f64; 2]>());
+                    assert!(!value.is::<[f32; 3]>());
+
+                    let expected =
+                        Value::eval_string(&mut frame, "NTuple{3, Float64}").into_jlrs_result()?;
+                    assert_eq!(value.datatype().as_value(), expected);
+                    assert_eq!(value.unbox::<[f64; 3]>()?, [1.0, 2.0, 3.0]);
+
+                    let empty = Value::new(&mut frame, [0u8; 0]);
+                    assert!(empty.is::<[u8; 0]>());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn unbox_ntuple() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let value = Value::eval_string(&mut frame, "ntuple(i -> Int32(i), 40)")
+                        .into_jlrs_result()?;
+                    let unboxed = value.unbox::<[i32; 40]>()?;
+                    assert_eq!(unboxed[0], 1);
+                    assert_eq!(unboxed[39], 40);
+
+                    let mixed = Value::eval_string(&mut frame, "(1, 2.0)").into_jlrs_result()?;
+                    assert!(mixed.unbox::<[i64; 2]>().is_err());
+
+                    let nested = Value::eval_string(&mut frame, "((1, 2), (3, 4), (5, 6))")
+                        .into_jlrs_result()?;
+                    assert_eq!(nested.unbox::<[[i64; 2]; 3]>()?, [[1, 2], [3, 4], [5, 6]]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn construct_ntuple_type() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let ty =
+                        <[u16; 4] as ConstructType>::construct_type(frame.as_extended_target());
+                    let expected =
+                        Value::eval_string(&mut frame, "NTuple{4, UInt16}").into_jlrs_result()?;
+                    assert_eq!(ty, expected);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[cfg(feature = "jlrs-derive")]
+    fn derive_ntuple_field() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    Value::eval_string(
+                        &mut frame,
+                        "struct WithNTuple{N}
+                            position::NTuple{N, Float64}
+                            id::Int32
+                        end",
+                    )
+                    .into_jlrs_result()?;
+
+                    let value =
+                        Value::eval_string(&mut frame, "WithNTuple((1.0, 2.0, 3.0), Int32(7))")
+                            .into_jlrs_result()?;
+
+                    assert!(value.is::<WithNTuple<3>>());
+                    assert!(!value.is::<WithNTuple<2>>());
+
+                    let unboxed = value.unbox::<WithNTuple<3>>()?;
+                    assert_eq!(unboxed.position, [1.0, 2.0, 3.0]);
+                    assert_eq!(unboxed.id, 7);
+
+                    let position = value
+                        .field_accessor()
+                        .field("position")?
+                        .access::<[f64; 3]>()?;
+                    assert_eq!(position, [1.0, 2.0, 3.0]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn ntuple_tests() {
+        ntuple_into_julia();
+        unbox_ntuple();
+        construct_ntuple_type();
+        #[cfg(feature = "jlrs-derive")]
+        derive_ntuple_field();
+    }
+}
@@ -0,0 +1,124 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "ccall"))]
+mod tests {
+    use jlrs::{data::managed::rust_closure::RustClosure, error::JlrsError, prelude::*};
+
+    use super::util::JULIA;
+
+    fn call_rust_closure() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let closure = RustClosure::new(frame.as_extended_target(), |frame, args| {
+                        let a = args[0].unbox::<isize>()?;
+                        let b = args[1].unbox::<isize>()?;
+                        Ok(Value::new(frame, a + b).leak())
+                    })?;
+
+                    let a = Value::new(&mut frame, 1isize);
+                    let b = Value::new(&mut frame, 2isize);
+                    let res = closure
+                        .as_value()
+                        .call2(&mut frame, a, b)
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+
+                    assert_eq!(res, 3);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn map_rust_closure() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut n_calls = 0usize;
+                    let closure =
+                        RustClosure::new(frame.as_extended_target(), move |frame, args| {
+                            n_calls += 1;
+                            let x = args[0].unbox::<f64>()?;
+                            Ok(Value::new(frame, 2.0 * x + n_calls as f64).leak())
+                        })?;
+
+                    let data = vec![1.0f64, 2.0, 3.0];
+                    let arr =
+                        Array::from_vec(frame.as_extended_target(), data, 3)?.into_jlrs_result()?;
+
+                    let res = Module::base(&frame)
+                        .function(&frame, "map")?
+                        .as_managed()
+                        .call2(&mut frame, closure.as_value(), arr.as_value())
+                        .into_jlrs_result()?
+                        .cast::<Array>()?;
+
+                    let res = res.copy_inline_data::<f64>()?;
+                    assert_eq!(res.as_slice(), &[3.0, 6.0, 9.0]);
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn rust_closure_error() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let closure = RustClosure::new(frame.as_extended_target(), |_, _| {
+                        Err(JlrsError::exception("Err"))?
+                    })?;
+
+                    let res = closure.as_value().call0(&mut frame);
+                    assert!(res.is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn rust_closure_reentrant() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    // The closure is called with itself as its argument and calls it while it's
+                    // being called, the exception thrown by the inner call is returned.
+                    let closure = RustClosure::new(frame.as_extended_target(), |frame, args| {
+                        match args[0].call1(&mut *frame, args[0]) {
+                            Ok(res) => Ok(res.leak()),
+                            Err(exc) => Ok(exc.leak()),
+                        }
+                    })?;
+
+                    let res = closure
+                        .as_value()
+                        .call1(&mut frame, closure.as_value())
+                        .into_jlrs_result()?;
+
+                    assert_eq!(res.datatype().name(), "BorrowError");
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn rust_closure_tests() {
+        call_rust_closure();
+        map_rust_closure();
+        rust_closure_error();
+        rust_closure_reentrant();
+    }
+}
@@ -0,0 +1,98 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "ccall"))]
+mod tests {
+    use jlrs::prelude::*;
+
+    use super::util::JULIA;
+
+    unsafe extern "C" fn add_two(a: isize) -> isize {
+        a + 2
+    }
+
+    unsafe extern "C" fn scale(a: f64, factor: f64) -> f64 {
+        a * factor
+    }
+
+    julia_module! {
+        become rust_module_bindings_test_init_fn;
+
+        #[doc = "    add_two(a::Int)"]
+        fn add_two(a: isize) -> isize;
+
+        fn scale(a: f64, #[kw] factor: f64 = 2.0) -> f64;
+    }
+
+    fn generate_rust_module_bindings() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let module = Value::eval_string(&mut frame, "module RustModuleBindingsTest end")
+                        .into_jlrs_result()?
+                        .cast::<Module>()?;
+
+                    let fingerprint = rust_module_bindings_test_init_fn_abi_fingerprint();
+                    let bindings = module.generate_rust_module_bindings(
+                        &mut frame,
+                        "path/to/lib",
+                        "rust_module_bindings_test_init_fn",
+                        rust_module_bindings_test_init_fn,
+                        fingerprint,
+                    )?;
+
+                    assert!(bindings.contains("module RustModuleBindingsTest"));
+                    assert!(bindings.contains("const LIBRARY = \"path/to/lib\""));
+                    assert!(bindings.contains(&format!("const ABI_FINGERPRINT = {:#018x}", fingerprint)));
+                    assert!(bindings.contains(":rust_module_bindings_test_init_fn_abi_fingerprint"));
+                    assert!(bindings.contains("function add_two(arg1::Core.Int64)"));
+                    assert!(bindings.contains("add_two(a::Int)"));
+                    assert!(bindings.contains("factor::Core.Float64"));
+
+                    // The generated code must be valid Julia code
+                    let code = JuliaString::new(&mut frame, bindings).as_value();
+                    let is_valid = Value::eval_string(
+                        &mut frame,
+                        "code -> all(ex -> !Meta.isexpr(ex, (:error, :incomplete)), Meta.parseall(code).args)",
+                    )
+                    .into_jlrs_result()?
+                    .call1(&mut frame, code)
+                    .into_jlrs_result()?
+                    .unbox::<bool>()?;
+                    assert!(is_valid.as_bool());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn generate_rust_module_bindings_twice() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let res = Module::main(&frame).generate_rust_module_bindings(
+                        &mut frame,
+                        "path/to/lib",
+                        "rust_module_bindings_test_init_fn",
+                        rust_module_bindings_test_init_fn,
+                        rust_module_bindings_test_init_fn_abi_fingerprint(),
+                    );
+
+                    assert!(res.is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn rust_module_bindings_tests() {
+        generate_rust_module_bindings();
+        generate_rust_module_bindings_twice();
+    }
+}
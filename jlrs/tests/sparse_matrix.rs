@@ -0,0 +1,223 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        data::{managed::array::sparse::SparseMatrixCsc, types::construct_type::ConstructType},
+        error::{InstantiationError, JlrsError},
+        prelude::*,
+    };
+
+    use crate::util::JULIA;
+
+    fn load_sparse_arrays(frame: &mut GcFrame) -> JlrsResult<()> {
+        Value::eval_string(frame, "using SparseArrays").into_jlrs_result()?;
+        Ok(())
+    }
+
+    fn access_sparse_matrix() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    load_sparse_arrays(&mut frame)?;
+                    let value = Value::eval_string(
+                        &mut frame,
+                        "sparse([1, 3, 2], [1, 1, 3], [1.0, 2.0, 3.0], 3, 4)",
+                    )
+                    .into_jlrs_result()?;
+
+                    assert!(value.is::<SparseMatrixCsc<f64, i64>>());
+                    assert!(!value.is::<SparseMatrixCsc<f32, i64>>());
+                    assert!(!value.is::<SparseMatrixCsc<f64, i32>>());
+
+                    let mat = value.cast::<SparseMatrixCsc<f64, i64>>()?;
+                    assert_eq!(mat.dims(), (3, 4));
+                    assert_eq!(mat.nnz(), 3);
+                    assert_eq!(mat.colptr_slice(), &[1, 3, 3, 4, 4]);
+                    assert_eq!(mat.rowval_slice(), &[1, 3, 2]);
+                    assert_eq!(mat.nzval_slice(), &[1.0, 2.0, 3.0]);
+                    assert_eq!(mat.nzval().as_array().dimensions().size(), 3);
+
+                    assert_eq!(mat.get(0, 0), Some(1.0));
+                    assert_eq!(mat.get(2, 0), Some(2.0));
+                    assert_eq!(mat.get(1, 2), Some(3.0));
+                    assert_eq!(mat.get(1, 0), None);
+                    assert_eq!(mat.get(3, 0), None);
+
+                    mat.nzval_mut()[2] = 4.0;
+                    let sum = Value::eval_string(&mut frame, "sum")
+                        .into_jlrs_result()?
+                        .call1(&mut frame, mat.as_value())
+                        .into_jlrs_result()?
+                        .unbox::<f64>()?;
+                    assert_eq!(sum, 7.0);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn create_sparse_matrix() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    load_sparse_arrays(&mut frame)?;
+                    let mat = SparseMatrixCsc::<f32, i32>::new(
+                        frame.as_extended_target(),
+                        2,
+                        3,
+                        &[0, 1, 1, 3],
+                        &[1, 0, 1],
+                        vec![1.0, 2.0, 3.0],
+                    )?
+                    .into_jlrs_result()?;
+
+                    assert_eq!(mat.dims(), (2, 3));
+                    assert_eq!(mat.colptr_slice(), &[1, 2, 2, 4]);
+                    assert_eq!(mat.rowval_slice(), &[2, 1, 2]);
+                    assert_eq!(mat.get(1, 0), Some(1.0));
+                    assert_eq!(mat.get(0, 2), Some(2.0));
+
+                    let expected = Value::eval_string(
+                        &mut frame,
+                        "sparse(Int32[2, 1, 2], Int32[1, 3, 3], Float32[1.0, 2.0, 3.0], 2, 3)",
+                    )
+                    .into_jlrs_result()?;
+                    let eq = Value::eval_string(&mut frame, "==")
+                        .into_jlrs_result()?
+                        .call2(&mut frame, mat.as_value(), expected)
+                        .into_jlrs_result()?
+                        .unbox::<bool>()?
+                        .as_bool();
+                    assert!(eq);
+
+                    let ty = <SparseMatrixCsc<f32, i32> as ConstructType>::construct_type(
+                        frame.as_extended_target(),
+                    );
+                    assert_eq!(ty, mat.as_value().datatype().as_value());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn create_invalid_sparse_matrix() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    load_sparse_arrays(&mut frame)?;
+
+                    let wrong_colptr = SparseMatrixCsc::<f64, i64>::new(
+                        frame.as_extended_target(),
+                        2,
+                        2,
+                        &[0, 1],
+                        &[0],
+                        vec![1.0],
+                    );
+                    assert!(wrong_colptr.is_err());
+
+                    let out_of_bounds = SparseMatrixCsc::<f64, i64>::new(
+                        frame.as_extended_target(),
+                        2,
+                        1,
+                        &[0, 1],
+                        &[2],
+                        vec![1.0],
+                    );
+                    assert!(out_of_bounds.is_err());
+
+                    let unsorted = SparseMatrixCsc::<f64, i64>::new(
+                        frame.as_extended_target(),
+                        2,
+                        1,
+                        &[0, 2],
+                        &[1, 0],
+                        vec![1.0, 2.0],
+                    );
+                    assert!(unsorted.is_err());
+
+                    let non_monotonic = SparseMatrixCsc::<f64, i64>::new(
+                        frame.as_extended_target(),
+                        5,
+                        2,
+                        &[0, 5, 2],
+                        &[0, 1],
+                        vec![1.0, 2.0],
+                    );
+                    match non_monotonic.map(|_| ()).map_err(|e| *e) {
+                        Err(JlrsError::InstantiationError(
+                            InstantiationError::InvalidSparseMatrix { .. },
+                        )) => (),
+                        _ => panic!("expected InvalidSparseMatrix"),
+                    }
+
+                    let overflow = SparseMatrixCsc::<f64, i8>::new(
+                        frame.as_extended_target(),
+                        200,
+                        1,
+                        &[0, 1],
+                        &[150],
+                        vec![1.0],
+                    );
+                    assert!(overflow.is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[cfg(feature = "sprs")]
+    fn sprs_conversions() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    load_sparse_arrays(&mut frame)?;
+
+                    let csr = sprs::CsMat::new((2, 3), vec![0, 2, 3], vec![0, 2, 1], vec![1, 2, 3]);
+                    let mat = SparseMatrixCsc::<i64, i64>::from_sprs(
+                        frame.as_extended_target(),
+                        csr.view(),
+                    )?
+                    .into_jlrs_result()?;
+
+                    assert_eq!(mat.dims(), (2, 3));
+                    assert_eq!(mat.get(0, 0), Some(1));
+                    assert_eq!(mat.get(0, 2), Some(2));
+                    assert_eq!(mat.get(1, 1), Some(3));
+                    assert_eq!(mat.get(1, 0), None);
+
+                    let converted = mat.to_sprs()?;
+                    assert!(converted.is_csc());
+                    assert_eq!(converted, csr.to_csc());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn sparse_matrix_tests() {
+        access_sparse_matrix();
+        create_sparse_matrix();
+        create_invalid_sparse_matrix();
+        #[cfg(feature = "sprs")]
+        sprs_conversions();
+    }
+}
@@ -0,0 +1,199 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        data::managed::array::strided::{PermutedDimsArray, ReshapedArray, SubArray},
+        prelude::*,
+    };
+
+    use crate::util::JULIA;
+
+    fn access_sub_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(
+                        &mut frame,
+                        "view(reshape(collect(1.0:12.0), 3, 4), 2, 2:2:4)",
+                    )
+                    .into_jlrs_result()?
+                    .cast::<SubArray>()?;
+
+                    assert_eq!(arr.rank(), 1);
+                    assert!(arr.element_type().cast::<DataType>()?.is::<f64>());
+
+                    let data = arr.bits_data::<f64>(&mut frame)?;
+                    assert_eq!(data.dimensions().as_slice(), &[2]);
+                    assert_eq!(data.strides(), &[6]);
+                    assert_eq!(data.offset(), 4);
+                    assert_eq!(data[0], 5.0);
+                    assert_eq!(data[1], 11.0);
+                    assert!(data.get(2).is_none());
+                    assert_eq!(data.copy_data().as_slice(), &[5.0, 11.0]);
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn access_reversed_sub_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(&mut frame, "view(collect(1:6), 5:-2:1)")
+                        .into_jlrs_result()?
+                        .cast::<SubArray>()?;
+
+                    let data = arr.bits_data::<i64>(&mut frame)?;
+                    assert_eq!(data.strides(), &[-2]);
+                    assert_eq!(data.offset(), 4);
+                    assert_eq!(data.copy_data().as_slice(), &[5, 3, 1]);
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn mutate_sub_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let parent =
+                        Value::eval_string(&mut frame, "zeros(Int, 3, 3)").into_jlrs_result()?;
+                    let func =
+                        Value::eval_string(&mut frame, "a -> view(a, :, 2)").into_jlrs_result()?;
+                    let mut arr = func
+                        .call1(&mut frame, parent)
+                        .into_jlrs_result()?
+                        .cast::<SubArray>()?;
+
+                    {
+                        let mut data = arr.bits_data_mut::<i64>(&mut frame)?;
+                        data[1] = 3;
+                        data.set(2, 4)?;
+                    }
+
+                    let parent = parent.cast::<Array>()?;
+                    let data = parent.bits_data::<i64>()?;
+                    assert_eq!(data[(1, 1)], 3);
+                    assert_eq!(data[(2, 1)], 4);
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn access_reshaped_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(
+                        &mut frame,
+                        "reshape(view(collect(1.0:12.0), 3:10), 2, 4)",
+                    )
+                    .into_jlrs_result()?
+                    .cast::<ReshapedArray>()?;
+
+                    assert_eq!(arr.rank(), 2);
+
+                    let data = arr.bits_data::<f64>(&mut frame)?;
+                    assert_eq!(data.dimensions().as_slice(), &[2, 4]);
+                    assert_eq!(data.strides(), &[1, 2]);
+                    assert_eq!(data.offset(), 2);
+                    assert_eq!(data[(1, 2)], 8.0);
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn access_permuted_dims_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(
+                        &mut frame,
+                        "PermutedDimsArray(reshape(collect(1:6), 2, 3), (2, 1))",
+                    )
+                    .into_jlrs_result()?
+                    .cast::<PermutedDimsArray>()?;
+
+                    let data = arr.bits_data::<i64>(&mut frame)?;
+                    assert_eq!(data.dimensions().as_slice(), &[3, 2]);
+                    assert_eq!(data.strides(), &[2, 1]);
+                    assert_eq!(data[(2, 0)], 5);
+                    assert_eq!(data.copy_data().as_slice(), &[1, 3, 5, 2, 4, 6]);
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn non_strided_sub_array_is_err() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(&mut frame, "view(collect(1:6), [1, 4, 2])")
+                        .into_jlrs_result()?
+                        .cast::<SubArray>()?;
+
+                    assert!(arr.bits_data::<i64>(&mut frame).is_err());
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn wrong_element_type_is_err() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let arr = Value::eval_string(&mut frame, "view(collect(1:6), 2:3)")
+                        .into_jlrs_result()?
+                        .cast::<SubArray>()?;
+
+                    assert!(arr.bits_data::<f64>(&mut frame).is_err());
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    #[test]
+    fn strided_array_tests() {
+        access_sub_array();
+        access_reversed_sub_array();
+        mutate_sub_array();
+        access_reshaped_array();
+        access_permuted_dims_array();
+        non_strided_sub_array_is_err();
+        wrong_element_type_is_err();
+    }
+}
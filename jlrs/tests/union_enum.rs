@@ -0,0 +1,262 @@
+#[cfg(all(test, feature = "jlrs-derive", feature = "sync-rt"))]
+mod util;
+
+#[cfg(all(test, feature = "jlrs-derive", feature = "sync-rt"))]
+#[cfg(not(feature = "julia-1-6"))]
+mod tests {
+    use jlrs::{
+        data::{
+            layout::union::{Align8, BitsUnion, UnionEnum},
+            managed::union::Union,
+        },
+        prelude::*,
+    };
+
+    use crate::util::JULIA;
+
+    #[derive(Copy, Clone, Debug, PartialEq, UnionEnum)]
+    enum IntOrFloat {
+        Int(i64),
+        Float(f64),
+        Nothing,
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, UnionEnum)]
+    enum Aliased {
+        I64(i64),
+        Isize(isize),
+        Nothing,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Debug, Unbox, ValidLayout, ValidField, Typecheck)]
+    struct WithUnionEnum {
+        a: i8,
+        #[jlrs(bits_union_align)]
+        _b_align: Align8,
+        #[jlrs(bits_union)]
+        b: BitsUnion<8>,
+        #[jlrs(bits_union_flag)]
+        b_flag: u8,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Debug, Unbox, Typecheck)]
+    struct WithUnionEnumField {
+        a: i8,
+        #[jlrs(union_enum)]
+        b: IntOrFloat,
+        c: u8,
+    }
+
+    fn union_enum_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut arr = Value::eval_string(
+                        &mut frame,
+                        "Union{Int64, Float64, Nothing}[1, 2.0, nothing]",
+                    )
+                    .into_jlrs_result()?
+                    .cast::<Array>()?;
+
+                    {
+                        let data = arr.union_data()?;
+                        assert_eq!(data.get_enum::<IntOrFloat, _>(0)?, IntOrFloat::Int(1));
+                        assert_eq!(data.get_enum::<IntOrFloat, _>(1)?, IntOrFloat::Float(2.0));
+                        assert_eq!(data.get_enum::<IntOrFloat, _>(2)?, IntOrFloat::Nothing);
+                    }
+
+                    {
+                        let mut data = arr.union_data_mut()?;
+                        data.set_enum(0, IntOrFloat::Nothing)?;
+                        data.set_enum(2, IntOrFloat::Float(-3.0))?;
+                    }
+
+                    let data = arr.union_data()?;
+                    assert_eq!(data.get_enum::<IntOrFloat, _>(0)?, IntOrFloat::Nothing);
+                    assert_eq!(data.get_enum::<IntOrFloat, _>(2)?, IntOrFloat::Float(-3.0));
+                    assert!(data.get::<f64, _>(2).is_ok());
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn union_enum_incompatible_array() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut arr =
+                        Value::eval_string(&mut frame, "Union{Int32, Nothing}[Int32(1), nothing]")
+                            .into_jlrs_result()?
+                            .cast::<Array>()?;
+
+                    {
+                        let data = arr.union_data()?;
+                        assert!(data.get_enum::<IntOrFloat, _>(0).is_err());
+                        assert_eq!(data.get_enum::<IntOrFloat, _>(1)?, IntOrFloat::Nothing);
+                    }
+
+                    let mut data = arr.union_data_mut()?;
+                    assert!(data.set_enum(0, IntOrFloat::Int(1)).is_err());
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn union_enum_field() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(
+                        &mut frame,
+                        "struct WithUnionEnum
+                            a::Int8
+                            b::Union{Int64, Float64, Nothing}
+                        end",
+                    )
+                    .into_jlrs_result()?;
+
+                    let value = Value::eval_string(&mut frame, "WithUnionEnum(1, 2.0)")
+                        .into_jlrs_result()?;
+                    let b = value
+                        .field_accessor()
+                        .field("b")?
+                        .access_enum::<IntOrFloat>()?;
+                    assert_eq!(b, IntOrFloat::Float(2.0));
+
+                    let union = Value::eval_string(&mut frame, "fieldtype(WithUnionEnum, :b)")
+                        .into_jlrs_result()?
+                        .cast::<Union>()?;
+                    assert!(IntOrFloat::is_compatible(union));
+
+                    let mut unboxed = value.unbox::<WithUnionEnum>()?;
+                    assert_eq!(unboxed.a, 1);
+                    assert_eq!(
+                        unboxed.b.as_enum::<IntOrFloat>(union, unboxed.b_flag)?,
+                        IntOrFloat::Float(2.0)
+                    );
+
+                    unboxed
+                        .b
+                        .set_enum(union, &mut unboxed.b_flag, IntOrFloat::Int(3))?;
+                    assert_eq!(
+                        unboxed.b.as_enum::<IntOrFloat>(union, unboxed.b_flag)?,
+                        IntOrFloat::Int(3)
+                    );
+
+                    let value = Value::eval_string(&mut frame, "WithUnionEnum(1, nothing)")
+                        .into_jlrs_result()?;
+                    let b = value
+                        .field_accessor()
+                        .field("b")?
+                        .access_enum::<IntOrFloat>()?;
+                    assert_eq!(b, IntOrFloat::Nothing);
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    fn union_enum_field_unbox() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(
+                        &mut frame,
+                        "struct WithUnionEnumField
+                            a::Int8
+                            b::Union{Int64, Float64, Nothing}
+                            c::UInt8
+                        end
+
+                        struct WithIncompatibleUnion
+                            a::Int8
+                            b::Union{Int32, Nothing}
+                            c::UInt8
+                        end",
+                    )
+                    .into_jlrs_result()?;
+
+                    let value = Value::eval_string(&mut frame, "WithUnionEnumField(1, 2.0, 3)")
+                        .into_jlrs_result()?;
+                    let unboxed = value.unbox::<WithUnionEnumField>()?;
+                    assert_eq!(unboxed.a, 1);
+                    assert_eq!(unboxed.b, IntOrFloat::Float(2.0));
+                    assert_eq!(unboxed.c, 3);
+
+                    let value =
+                        Value::eval_string(&mut frame, "WithUnionEnumField(-1, nothing, 4)")
+                            .into_jlrs_result()?;
+                    let unboxed = value.unbox::<WithUnionEnumField>()?;
+                    assert_eq!(unboxed.a, -1);
+                    assert_eq!(unboxed.b, IntOrFloat::Nothing);
+                    assert_eq!(unboxed.c, 4);
+
+                    let value =
+                        Value::eval_string(&mut frame, "WithIncompatibleUnion(1, Int32(2), 3)")
+                            .into_jlrs_result()?;
+                    assert!(!value.is::<WithUnionEnumField>());
+                    assert!(value.unbox::<WithUnionEnumField>().is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    fn union_enum_aliased_variants() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut arr =
+                        Value::eval_string(&mut frame, "Union{Int64, Nothing}[1, nothing]")
+                            .into_jlrs_result()?
+                            .cast::<Array>()?;
+
+                    let union = arr.element_type().cast::<Union>()?;
+                    assert!(!Aliased::is_compatible(union));
+
+                    let data = arr.union_data()?;
+                    assert!(data.get_enum::<Aliased, _>(0).is_err());
+                    assert!(data.get_enum::<Aliased, _>(1).is_err());
+
+                    let mut data = arr.union_data_mut()?;
+                    assert!(data.set_enum(1, Aliased::I64(1)).is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        })
+    }
+
+    #[test]
+    fn union_enum_tests() {
+        union_enum_array();
+        union_enum_incompatible_array();
+        union_enum_field();
+        union_enum_field_unbox();
+        #[cfg(target_pointer_width = "64")]
+        union_enum_aliased_variants();
+    }
+}
@@ -1,6 +1,8 @@
+use std::collections::HashSet;
+
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TS2;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{self, punctuated::Punctuated, token::Comma, Expr, Lit, Meta, Token, WherePredicate};
 
 #[derive(Default)]
@@ -103,6 +105,7 @@ enum JlrsFieldAttr {
     BitsUnionAlign,
     BitsUnion,
     BitsUnionFlag,
+    UnionEnum,
 }
 
 impl JlrsFieldAttr {
@@ -122,6 +125,8 @@ impl JlrsFieldAttr {
                     return Some(JlrsFieldAttr::BitsUnionAlign);
                 } else if path.is_ident("bits_union_flag") {
                     return Some(JlrsFieldAttr::BitsUnionFlag);
+                } else if path.is_ident("union_enum") {
+                    return Some(JlrsFieldAttr::UnionEnum);
                 }
             }
         }
@@ -130,8 +135,37 @@ impl JlrsFieldAttr {
     }
 }
 
+fn is_union_enum_field(field: &syn::Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .any(|attr| matches!(JlrsFieldAttr::parse(attr), Some(JlrsFieldAttr::UnionEnum)))
+}
+
+fn has_union_enum_fields(ast: &syn::DeriveInput) -> bool {
+    match &ast.data {
+        syn::Data::Struct(s) => s.fields.iter().any(is_union_enum_field),
+        _ => false,
+    }
+}
+
+// The layout of a struct with a field annotated with #[jlrs(union_enum)] doesn't match the layout
+// of the Julia type, so traits that assume it does can't be derived.
+fn reject_union_enum_fields(ast: &syn::DeriveInput, derive: &str) {
+    if has_union_enum_fields(ast) {
+        panic!("{} can't be derived for types with a field annotated with #[jlrs(union_enum)], derive Unbox and Typecheck instead.", derive);
+    }
+}
+
+// The name of the layout that is generated by the Unbox derive macro for a struct with a field
+// annotated with #[jlrs(union_enum)].
+fn union_enum_layout_ident(name: &syn::Ident) -> syn::Ident {
+    format_ident!("__{}JlrsLayout", name)
+}
+
 pub fn impl_into_julia(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    reject_union_enum_fields(ast, "IntoJulia");
     if !is_repr_c(ast) {
         panic!("IntoJulia can only be derived for types with the attribute #[repr(C)].");
     }
@@ -235,7 +269,12 @@ pub fn impl_unbox(ast: &syn::DeriveInput) -> TokenStream {
         panic!("Unbox can only be derived for types with the attribute #[repr(C)].");
     }
 
+    if has_union_enum_fields(ast) {
+        return impl_unbox_union_enum(ast);
+    }
+
     let generics = &ast.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
     let where_clause = match ast.generics.where_clause.as_ref() {
         Some(wc) => {
             let mut wc = wc.clone();
@@ -263,8 +302,100 @@ pub fn impl_unbox(ast: &syn::DeriveInput) -> TokenStream {
     };
 
     let unbox_impl = quote! {
-        unsafe impl #generics ::jlrs::convert::unbox::Unbox for #name #generics #where_clause {
+        unsafe impl #impl_generics ::jlrs::convert::unbox::Unbox for #name #ty_generics #where_clause {
+            type Output = Self;
+        }
+    };
+
+    unbox_impl.into()
+}
+
+// Generates a layout in which each field annotated with #[jlrs(union_enum)] is replaced with an
+// aligned BitsUnion and its flag, and implements Unbox by converting that layout to Self.
+fn impl_unbox_union_enum(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    if ast.generics.params.iter().next().is_some() {
+        panic!("Unbox cannot be derived for types with generics that have a field annotated with #[jlrs(union_enum)].");
+    }
+
+    let fields = match &ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(n),
+            ..
+        }) => &n.named,
+        _ => panic!("Unbox can only be derived for structs with named fields if a field is annotated with #[jlrs(union_enum)]."),
+    };
+
+    let layout_name = union_enum_layout_ident(name);
+    let mut layout_fields = Vec::with_capacity(fields.len());
+    let mut field_inits = Vec::with_capacity(fields.len());
+
+    for (idx, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+
+        if is_union_enum_field(field) {
+            let align_ident = format_ident!("__{}_align", ident);
+            let flag_ident = format_ident!("__{}_flag", ident);
+            let size = quote! { <#ty as ::jlrs::data::layout::union::UnionEnum>::SIZE };
+
+            layout_fields.push(quote! {
+                #[jlrs(bits_union_align)]
+                #align_ident: <#ty as ::jlrs::data::layout::union::UnionEnum>::Alignment,
+                #[jlrs(bits_union)]
+                #ident: ::jlrs::data::layout::union::BitsUnion<{ #size }>,
+                #[jlrs(bits_union_flag)]
+                #flag_ident: u8,
+            });
+
+            field_inits.push(quote! {
+                #ident: ::jlrs::data::layout::union::union_enum_field::<#ty, { #size }>(
+                    dt,
+                    #idx,
+                    &layout.#ident,
+                    layout.#flag_ident,
+                )
+            });
+        } else {
+            layout_fields.push(quote! { #ident: #ty, });
+            field_inits.push(quote! { #ident: ::std::clone::Clone::clone(&layout.#ident) });
+        }
+    }
+
+    let layout: syn::DeriveInput = syn::parse_quote! {
+        #[repr(C)]
+        struct #layout_name {
+            #(#layout_fields)*
+        }
+    };
+
+    let valid_layout_impl = TS2::from(impl_valid_layout(&layout));
+
+    let mut layout_def = layout;
+    if let syn::Data::Struct(ref mut s) = layout_def.data {
+        for field in s.fields.iter_mut() {
+            field.attrs.clear();
+        }
+    }
+
+    let unbox_impl = quote! {
+        #[allow(dead_code)]
+        #[doc(hidden)]
+        #layout_def
+
+        #valid_layout_impl
+
+        unsafe impl ::jlrs::convert::unbox::Unbox for #name {
             type Output = Self;
+
+            unsafe fn unbox(value: ::jlrs::data::managed::value::Value) -> Self {
+                let dt = value.datatype();
+                let layout = value.data_ptr().cast::<#layout_name>().as_ref();
+
+                #name {
+                    #(#field_inits),*
+                }
+            }
         }
     };
 
@@ -277,7 +408,12 @@ pub fn impl_typecheck(ast: &syn::DeriveInput) -> TokenStream {
         panic!("Typecheck can only be derived for types with the attribute #[repr(C)].");
     }
 
+    if has_union_enum_fields(ast) {
+        return impl_typecheck_union_enum(ast);
+    }
+
     let generics = &ast.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
     let where_clause = match ast.generics.where_clause.as_ref() {
         Some(wc) => {
             let mut wc = wc.clone();
@@ -314,10 +450,68 @@ pub fn impl_typecheck(ast: &syn::DeriveInput) -> TokenStream {
     };
 
     let typecheck_impl = quote! {
-        unsafe impl #generics ::jlrs::data::types::typecheck::Typecheck for #name #generics #where_clause {
+        unsafe impl #impl_generics ::jlrs::data::types::typecheck::Typecheck for #name #ty_generics #where_clause {
             fn typecheck(dt: ::jlrs::data::managed::datatype::DataType) -> bool {
                 <Self as ::jlrs::data::layout::valid_layout::ValidLayout>::valid_layout(dt.as_value())
             }
+
+            fn mismatch(dt: ::jlrs::data::managed::datatype::DataType) -> Option<::jlrs::data::layout::valid_layout::LayoutMismatch> {
+                <Self as ::jlrs::data::layout::valid_layout::ValidLayout>::layout_mismatch(dt.as_value())
+            }
+        }
+    };
+
+    typecheck_impl.into()
+}
+
+// Checks the layout generated by the Unbox derive macro and that the union of each field
+// annotated with #[jlrs(union_enum)] is compatible with the enum.
+fn impl_typecheck_union_enum(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let layout_name = union_enum_layout_ident(name);
+
+    let (union_enum_idxs, union_enum_tys): (Vec<_>, Vec<_>) = match &ast.data {
+        syn::Data::Struct(s) => s
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| is_union_enum_field(field))
+            .map(|(idx, field)| (idx, &field.ty))
+            .unzip(),
+        _ => unreachable!(),
+    };
+
+    let union_enum_idxs_b = union_enum_idxs.iter();
+    let union_enum_tys_b = union_enum_tys.iter();
+
+    let typecheck_impl = quote! {
+        unsafe impl ::jlrs::data::types::typecheck::Typecheck for #name {
+            fn typecheck(dt: ::jlrs::data::managed::datatype::DataType) -> bool {
+                <#layout_name as ::jlrs::data::layout::valid_layout::ValidLayout>::valid_layout(dt.as_value())
+                    #(
+                        && ::jlrs::data::layout::union::union_enum_field_compatible::<#union_enum_tys>(dt, #union_enum_idxs)
+                    )*
+            }
+
+            fn mismatch(dt: ::jlrs::data::managed::datatype::DataType) -> Option<::jlrs::data::layout::valid_layout::LayoutMismatch> {
+                if let Some(mut mismatch) = <#layout_name as ::jlrs::data::layout::valid_layout::ValidLayout>::layout_mismatch(dt.as_value()) {
+                    mismatch.rust_type = ::std::any::type_name::<Self>();
+                    return Some(mismatch);
+                }
+
+                #(
+                    if !::jlrs::data::layout::union::union_enum_field_compatible::<#union_enum_tys_b>(dt, #union_enum_idxs_b) {
+                        return Some(::jlrs::data::layout::valid_layout::LayoutMismatch::new::<Self>(
+                            dt.as_value(),
+                            ::jlrs::data::layout::valid_layout::MismatchReason::Field(
+                                ::jlrs::data::layout::valid_layout::FieldMismatch::new::<#union_enum_tys_b>(dt, #union_enum_idxs_b)
+                            ),
+                        ));
+                    }
+                )*
+
+                None
+            }
         }
     };
 
@@ -368,6 +562,7 @@ pub fn impl_construct_type(ast: &syn::DeriveInput) -> TokenStream {
     let modules_it_d = modules_it.clone();
 
     let generics = &ast.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
     let wc = match ast.generics.where_clause.as_ref() {
         Some(wc) => {
             let mut wc = wc.clone();
@@ -394,17 +589,41 @@ pub fn impl_construct_type(ast: &syn::DeriveInput) -> TokenStream {
         }
     };
 
-    let param_names = ast
+    // Type parameters are constructed, const parameters are converted to Julia values. Const
+    // parameters of type `usize` are converted to `Int`, which is how Julia represents sizes.
+    let params = ast
         .generics
-        .type_params()
-        .map(|p| &p.ident)
+        .params
+        .iter()
+        .filter_map(|p| match p {
+            syn::GenericParam::Type(p) => {
+                let ident = &p.ident;
+                Some(quote! {
+                    <#ident as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
+                })
+            }
+            syn::GenericParam::Const(p) => {
+                let ident = &p.ident;
+                let is_usize = matches!(&p.ty, syn::Type::Path(ty) if ty.path.is_ident("usize"));
+                let value = if is_usize {
+                    quote! { #ident as isize }
+                } else {
+                    quote! { #ident }
+                };
+
+                Some(quote! {
+                    ::jlrs::data::managed::value::Value::new(&mut frame, #value)
+                })
+            }
+            syn::GenericParam::Lifetime(_) => None,
+        })
         .collect::<Vec<_>>();
-    let n_generics = param_names.len();
-    let param_names = param_names.iter();
+    let n_generics = params.len();
+    let params = params.iter();
     let nth_generic = 0..n_generics;
 
     let construct_type_impl = quote! {
-        unsafe impl #generics ::jlrs::data::types::construct_type::ConstructType for #name #generics #wc {
+        unsafe impl #impl_generics ::jlrs::data::types::construct_type::ConstructType for #name #ty_generics #wc {
             fn construct_type<'target, Tgt>(
                 target: ::jlrs::memory::target::ExtendedTarget<'target, '_, '_, Tgt>,
             ) -> ::jlrs::data::managed::value::ValueData<'target, 'static, Tgt>
@@ -432,7 +651,7 @@ pub fn impl_construct_type(ast: &syn::DeriveInput) -> TokenStream {
 
                     let mut types: [Option<::jlrs::data::managed::value::Value>; #n_generics] = [None; #n_generics];
                     #(
-                        types[#nth_generic] = Some(<#param_names as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target()));
+                        types[#nth_generic] = Some(#params);
                     )*
                     unsafe {
                         let types = std::mem::transmute::<&[Option<::jlrs::data::managed::value::Value>; #n_generics], &[::jlrs::data::managed::value::Value; #n_generics]>(&types);
@@ -542,13 +761,37 @@ pub fn impl_construct_type(ast: &syn::DeriveInput) -> TokenStream {
         }
     };
 
-    let param_names = ast
+    // Type parameters are constructed, const parameters are converted to Julia values. Const
+    // parameters of type `usize` are converted to `Int`, which is how Julia represents sizes.
+    let params = ast
         .generics
-        .type_params()
-        .map(|p| &p.ident)
+        .params
+        .iter()
+        .filter_map(|p| match p {
+            syn::GenericParam::Type(p) => {
+                let ident = &p.ident;
+                Some(quote! {
+                    <#ident as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
+                })
+            }
+            syn::GenericParam::Const(p) => {
+                let ident = &p.ident;
+                let is_usize = matches!(&p.ty, syn::Type::Path(ty) if ty.path.is_ident("usize"));
+                let value = if is_usize {
+                    quote! { #ident as isize }
+                } else {
+                    quote! { #ident }
+                };
+
+                Some(quote! {
+                    ::jlrs::data::managed::value::Value::new(&mut frame, #value)
+                })
+            }
+            syn::GenericParam::Lifetime(_) => None,
+        })
         .collect::<Vec<_>>();
-    let n_generics = param_names.len();
-    let param_names = param_names.iter();
+    let n_generics = params.len();
+    let params = params.iter();
     let nth_generic = 0..n_generics;
 
     let construct_type_impl = quote! {
@@ -612,11 +855,13 @@ pub fn impl_construct_type(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_valid_layout(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    reject_union_enum_fields(ast, "ValidLayout");
     if !is_repr_c(ast) {
         panic!("ValidLayout can only be derived for types with the attribute #[repr(C)].");
     }
 
     let generics = &ast.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
     let where_clause = match ast.generics.where_clause.as_ref() {
         Some(wc) => {
             let mut wc = wc.clone();
@@ -664,8 +909,72 @@ pub fn impl_valid_layout(ast: &syn::DeriveInput) -> TokenStream {
     let n_fields = classified_fields.jl_union_field_idxs.len()
         + classified_fields.jl_non_union_field_idxs.len();
 
+    // The fields are checked in the order they're declared in Julia, so the first field that is
+    // incompatible is reported.
+    let mut field_checks: Vec<(usize, TS2)> = classified_fields
+        .rs_non_union_fields
+        .iter()
+        .zip(classified_fields.jl_non_union_field_idxs.iter())
+        .map(|(ty, idx)| {
+            let check = quote! {
+                if !<#ty as ::jlrs::data::layout::valid_layout::ValidField>::valid_field(field_types[#idx].unwrap().as_managed()) {
+                    return Some(::jlrs::data::layout::valid_layout::LayoutMismatch::new::<Self>(
+                        v,
+                        ::jlrs::data::layout::valid_layout::MismatchReason::Field(
+                            ::jlrs::data::layout::valid_layout::FieldMismatch::new::<#ty>(dt, #idx)
+                        ),
+                    ));
+                }
+            };
+            (*idx, check)
+        })
+        .collect();
+
+    for (((align_ty, union_ty), flag_ty), idx) in classified_fields
+        .rs_align_fields
+        .iter()
+        .zip(classified_fields.rs_union_fields.iter())
+        .zip(classified_fields.rs_flag_fields.iter())
+        .zip(classified_fields.jl_union_field_idxs.iter())
+    {
+        let check = quote! {
+            let valid = match field_types[#idx].unwrap().as_managed().cast::<::jlrs::data::managed::union::Union>() {
+                Ok(u) => ::jlrs::data::layout::union::correct_layout_for::<#align_ty, #union_ty, #flag_ty>(u),
+                Err(_) => false,
+            };
+
+            if !valid {
+                return Some(::jlrs::data::layout::valid_layout::LayoutMismatch::new::<Self>(
+                    v,
+                    ::jlrs::data::layout::valid_layout::MismatchReason::Field(
+                        ::jlrs::data::layout::valid_layout::FieldMismatch::new::<#union_ty>(dt, #idx)
+                    ),
+                ));
+            }
+        };
+        field_checks.push((*idx, check));
+    }
+
+    field_checks.sort_by_key(|(idx, _)| *idx);
+    let field_checks = field_checks.into_iter().map(|(_, check)| check);
+
+    // Layouts are registered if their Julia type is known and they have no type parameters.
+    let attrs = JlrsTypeAttrs::parse(ast);
+    let register_layout = match attrs.julia_type {
+        Some(ref julia_type)
+            if generics.type_params().next().is_none()
+                && generics.const_params().next().is_none() =>
+        {
+            let lifetimes = generics.lifetimes().map(|_| quote! { 'static });
+            Some(quote! {
+                ::jlrs::__register_layout!(#name<#(#lifetimes),*>, #name, #julia_type);
+            })
+        }
+        _ => None,
+    };
+
     let valid_layout_impl = quote! {
-        unsafe impl #generics ::jlrs::data::layout::valid_layout::ValidLayout for #name #generics #where_clause {
+        unsafe impl #impl_generics ::jlrs::data::layout::valid_layout::ValidLayout for #name #ty_generics #where_clause {
             fn valid_layout(v: ::jlrs::data::managed::value::Value) -> bool {
                 unsafe {
                     if let Ok(dt) = v.cast::<::jlrs::data::managed::datatype::DataType>() {
@@ -703,8 +1012,47 @@ pub fn impl_valid_layout(ast: &syn::DeriveInput) -> TokenStream {
                 false
             }
 
+            fn layout_mismatch(v: ::jlrs::data::managed::value::Value) -> Option<::jlrs::data::layout::valid_layout::LayoutMismatch> {
+                unsafe {
+                    let dt = match v.cast::<::jlrs::data::managed::datatype::DataType>() {
+                        Ok(dt) => dt,
+                        Err(_) => return Some(::jlrs::data::layout::valid_layout::LayoutMismatch::new::<Self>(
+                            v,
+                            ::jlrs::data::layout::valid_layout::MismatchReason::NotADataType,
+                        )),
+                    };
+
+                    let n_fields = dt.n_fields().unwrap() as usize;
+                    if n_fields != #n_fields {
+                        return Some(::jlrs::data::layout::valid_layout::LayoutMismatch::new::<Self>(
+                            v,
+                            ::jlrs::data::layout::valid_layout::MismatchReason::FieldCount {
+                                expected: #n_fields,
+                                found: n_fields,
+                            },
+                        ));
+                    }
+
+                    let global = v.unrooted_target();
+                    let field_types = dt.field_types(global);
+                    let field_types_svec = field_types.as_managed();
+                    let field_types_data = field_types_svec.data();
+                    let field_types = field_types_data.as_slice();
+
+                    #(
+                        {
+                            #field_checks
+                        }
+                    )*
+
+                    None
+                }
+            }
+
             const IS_REF: bool = false;
         }
+
+        #register_layout
     };
 
     valid_layout_impl.into()
@@ -712,11 +1060,13 @@ pub fn impl_valid_layout(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_valid_field(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    reject_union_enum_fields(ast, "ValidField");
     if !is_repr_c(ast) {
         panic!("ValidLayout can only be derived for types with the attribute #[repr(C)].");
     }
 
     let generics = &ast.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
     let where_clause = match ast.generics.where_clause.as_ref() {
         Some(wc) => {
             let mut wc = wc.clone();
@@ -744,7 +1094,7 @@ pub fn impl_valid_field(ast: &syn::DeriveInput) -> TokenStream {
     };
 
     let valid_field_impl = quote! {
-        unsafe impl #generics ::jlrs::data::layout::valid_layout::ValidField for #name #generics #where_clause {
+        unsafe impl #impl_generics ::jlrs::data::layout::valid_layout::ValidField for #name #ty_generics #where_clause {
             fn valid_field(v: ::jlrs::data::managed::value::Value) -> bool {
                 <Self as ::jlrs::data::layout::valid_layout::ValidLayout>::valid_layout(v)
             }
@@ -756,11 +1106,13 @@ pub fn impl_valid_field(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_ccall_arg(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    reject_union_enum_fields(ast, "CCallArg");
     if !is_repr_c(ast) {
         panic!("ValidLayout can only be derived for types with the attribute #[repr(C)].");
     }
 
     let generics = &ast.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
     let wc = match ast.generics.where_clause.as_ref() {
         Some(wc) => {
             let mut wc = wc.clone();
@@ -788,7 +1140,7 @@ pub fn impl_ccall_arg(ast: &syn::DeriveInput) -> TokenStream {
     };
 
     let ccall_arg_impl = quote! {
-        unsafe impl #generics ::jlrs::convert::ccall_types::CCallArg for #name #generics #wc {
+        unsafe impl #impl_generics ::jlrs::convert::ccall_types::CCallArg for #name #ty_generics #wc {
             type CCallArgType = Self;
             type FunctionArgType = Self;
         }
@@ -799,11 +1151,13 @@ pub fn impl_ccall_arg(ast: &syn::DeriveInput) -> TokenStream {
 
 pub fn impl_ccall_return(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
+    reject_union_enum_fields(ast, "CCallReturn");
     if !is_repr_c(ast) {
         panic!("ValidLayout can only be derived for types with the attribute #[repr(C)].");
     }
 
     let generics = &ast.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
     let wc = match ast.generics.where_clause.as_ref() {
         Some(wc) => {
             let mut wc = wc.clone();
@@ -831,7 +1185,7 @@ pub fn impl_ccall_return(ast: &syn::DeriveInput) -> TokenStream {
     };
 
     let ccall_arg_impl = quote! {
-        unsafe impl #generics ::jlrs::convert::ccall_types::CCallReturn for #name #generics #wc {
+        unsafe impl #impl_generics ::jlrs::convert::ccall_types::CCallReturn for #name #ty_generics #wc {
             type CCallReturnType = Self;
             type FunctionReturnType = Self;
         }
@@ -854,3 +1208,318 @@ fn is_repr_c(ast: &syn::DeriveInput) -> bool {
 
     false
 }
+
+pub fn impl_enum(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let data = match ast.data {
+        syn::Data::Enum(ref data) => data,
+        _ => panic!("Enum can only be derived for enums."),
+    };
+
+    let repr = enum_repr(ast).expect(
+        "Enum can only be derived for enums with an integer representation, e.g. #[repr(i32)].",
+    );
+
+    for variant in data.variants.iter() {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            panic!("Enum can only be derived for enums whose variants have no fields.");
+        }
+    }
+
+    // The Julia type is registered when it's created or registered with `Enum::register_type`,
+    // so its name is only determined by the code that creates it.
+    if JlrsTypeAttrs::parse(ast).julia_type.is_some() {
+        panic!("Enum doesn't use #[jlrs(julia_type = \"...\")], the Julia type is registered when it's created with Enum::create_type or registered with Enum::register_type.");
+    }
+
+    let variant_idents = data.variants.iter().map(|v| &v.ident);
+    let variant_names = data.variants.iter().map(|v| v.ident.to_string());
+
+    let enum_impl = quote! {
+        unsafe impl ::jlrs::data::layout::julia_enum::Enum for #name {
+            type Super = #repr;
+
+            const VARIANTS: &'static [(&'static str, Self)] = &[
+                #(
+                    (#variant_names, #name::#variant_idents),
+                )*
+            ];
+
+            fn as_super(self) -> #repr {
+                self as #repr
+            }
+        }
+
+        unsafe impl ::jlrs::convert::into_julia::IntoJulia for #name {
+            fn julia_type<'scope, T>(target: T) -> ::jlrs::data::managed::datatype::DataTypeData<'scope, T>
+            where
+                T: ::jlrs::memory::target::Target<'scope>,
+            {
+                let ty = <Self as ::jlrs::data::layout::julia_enum::Enum>::registered_type(&target)
+                    .expect(concat!("The Julia type of ", stringify!(#name), " has not been created or registered"));
+                ::jlrs::data::managed::Managed::root(ty, target)
+            }
+        }
+
+        unsafe impl ::jlrs::data::layout::valid_layout::ValidLayout for #name {
+            fn valid_layout(ty: ::jlrs::data::managed::value::Value) -> bool {
+                if let Ok(dt) = ty.cast::<::jlrs::data::managed::datatype::DataType>() {
+                    let unrooted = <::jlrs::data::managed::datatype::DataType as ::jlrs::data::managed::Managed>::unrooted_target(dt);
+                    match <Self as ::jlrs::data::layout::julia_enum::Enum>::registered_type(&unrooted) {
+                        Some(julia_type) => dt == julia_type,
+                        None => false,
+                    }
+                } else {
+                    false
+                }
+            }
+
+            const IS_REF: bool = false;
+        }
+
+        unsafe impl ::jlrs::data::layout::valid_layout::ValidField for #name {
+            fn valid_field(ty: ::jlrs::data::managed::value::Value) -> bool {
+                <Self as ::jlrs::data::layout::valid_layout::ValidLayout>::valid_layout(ty)
+            }
+        }
+
+        unsafe impl ::jlrs::data::types::typecheck::Typecheck for #name {
+            fn typecheck(dt: ::jlrs::data::managed::datatype::DataType) -> bool {
+                <Self as ::jlrs::data::layout::valid_layout::ValidLayout>::valid_layout(dt.as_value())
+            }
+
+            fn mismatch(dt: ::jlrs::data::managed::datatype::DataType) -> Option<::jlrs::data::layout::valid_layout::LayoutMismatch> {
+                <Self as ::jlrs::data::layout::valid_layout::ValidLayout>::layout_mismatch(dt.as_value())
+            }
+        }
+
+        unsafe impl ::jlrs::convert::unbox::Unbox for #name {
+            type Output = Self;
+        }
+
+        unsafe impl ::jlrs::data::types::construct_type::ConstructType for #name {
+            fn construct_type<'target, Tgt>(
+                target: ::jlrs::memory::target::ExtendedTarget<'target, '_, '_, Tgt>,
+            ) -> ::jlrs::data::managed::value::ValueData<'target, 'static, Tgt>
+            where
+                Tgt: ::jlrs::memory::target::Target<'target>,
+            {
+                let (target, _) = target.split();
+                let ty = <Self as ::jlrs::data::layout::julia_enum::Enum>::registered_type(&target)
+                    .expect(concat!("The Julia type of ", stringify!(#name), " has not been created or registered"));
+                ::jlrs::data::managed::Managed::root(::jlrs::data::managed::Managed::as_value(ty), target)
+            }
+
+            fn base_type<'target, Tgt>(
+                target: &Tgt
+            ) -> Option<::jlrs::data::managed::value::Value<'target, 'static>>
+            where
+                Tgt: ::jlrs::memory::target::Target<'target>,
+            {
+                <Self as ::jlrs::data::layout::julia_enum::Enum>::registered_type(target)
+                    .map(::jlrs::data::managed::Managed::as_value)
+            }
+        }
+
+        unsafe impl ::jlrs::convert::ccall_types::CCallArg for #name {
+            type CCallArgType = Self;
+            type FunctionArgType = Self;
+        }
+
+        unsafe impl ::jlrs::convert::ccall_types::CCallReturn for #name {
+            type CCallReturnType = Self;
+            type FunctionReturnType = Self;
+        }
+    };
+
+    enum_impl.into()
+}
+
+pub fn impl_union_enum(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let data = match ast.data {
+        syn::Data::Enum(ref data) => data,
+        _ => panic!("UnionEnum can only be derived for enums."),
+    };
+
+    if ast.generics.params.iter().next().is_some() {
+        panic!("UnionEnum cannot be derived for enums with generics.");
+    }
+
+    let mut n_unit_variants = 0;
+    let variant_types: Vec<syn::Type> = data
+        .variants
+        .iter()
+        .map(|variant| match variant.fields {
+            syn::Fields::Unit => {
+                n_unit_variants += 1;
+                syn::parse_quote! { ::jlrs::data::layout::nothing::Nothing }
+            }
+            syn::Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+                fields.unnamed[0].ty.clone()
+            }
+            _ => panic!("UnionEnum can only be derived for enums whose variants have no fields or a single unnamed field."),
+        })
+        .collect();
+
+    if n_unit_variants > 1 {
+        panic!("UnionEnum can only be derived for enums with at most one variant without fields.");
+    }
+
+    // Each type can only occur once in a union, so two variants with the same type would be
+    // indistinguishable. A unit variant has the same type as a variant that contains `Nothing`.
+    // Only identical types are detected here, aliases like `i64` and `isize` are detected at
+    // runtime: a type that matches several variants isn't a variant type.
+    let mut seen_types = HashSet::new();
+    for ty in variant_types.iter() {
+        let key = match ty {
+            syn::Type::Path(path) => match path.path.segments.last() {
+                Some(s) if s.ident == "Nothing" && s.arguments.is_empty() => "Nothing".to_string(),
+                _ => quote!(#ty).to_string(),
+            },
+            _ => quote!(#ty).to_string(),
+        };
+
+        if !seen_types.insert(key) {
+            panic!("UnionEnum can only be derived for enums whose variants have different types, {} is used more than once.", quote!(#ty));
+        }
+    }
+
+    let variants = data.variants.iter().zip(variant_types.iter());
+
+    let mut is_variant_checks = Vec::with_capacity(data.variants.len());
+    let mut variant_type_arms = Vec::with_capacity(data.variants.len());
+    let mut read_checks = Vec::with_capacity(data.variants.len());
+    let mut write_arms = Vec::with_capacity(data.variants.len());
+
+    for (variant, ty) in variants {
+        let ident = &variant.ident;
+        let is_unit = matches!(variant.fields, syn::Fields::Unit);
+
+        is_variant_checks.push(quote! {
+            if !<#ty as ::jlrs::data::layout::valid_layout::ValidLayout>::IS_REF
+                && <#ty as ::jlrs::data::layout::valid_layout::ValidLayout>::valid_layout(ty)
+            {
+                n_matches += 1;
+            }
+        });
+
+        let pattern = if is_unit {
+            quote! { #name::#ident }
+        } else {
+            quote! { #name::#ident(_) }
+        };
+
+        variant_type_arms.push(quote! {
+            #pattern => <#ty as ::jlrs::convert::into_julia::IntoJulia>::julia_type(target.unrooted()).as_managed(),
+        });
+
+        let read = if is_unit {
+            quote! { #name::#ident }
+        } else {
+            quote! { #name::#ident(ptr.cast::<#ty>().read_unaligned()) }
+        };
+
+        read_checks.push(quote! {
+            if !<#ty as ::jlrs::data::layout::valid_layout::ValidLayout>::IS_REF
+                && <#ty as ::jlrs::data::layout::valid_layout::ValidLayout>::valid_layout(ty)
+            {
+                return Some(#read);
+            }
+        });
+
+        write_arms.push(if is_unit {
+            quote! { #name::#ident => (), }
+        } else {
+            quote! { #name::#ident(data) => ptr.cast::<#ty>().write_unaligned(data), }
+        });
+    }
+
+    let size_checks = variant_types.iter().map(|ty| {
+        quote! {
+            if ::std::mem::size_of::<#ty>() > size {
+                size = ::std::mem::size_of::<#ty>();
+            }
+        }
+    });
+
+    let align_checks = variant_types.iter().map(|ty| {
+        quote! {
+            if ::std::mem::align_of::<#ty>() > align {
+                align = ::std::mem::align_of::<#ty>();
+            }
+        }
+    });
+
+    let union_enum_impl = quote! {
+        unsafe impl ::jlrs::data::layout::union::UnionEnum for #name {
+            const SIZE: usize = {
+                let mut size = 0;
+                #(#size_checks)*
+                size
+            };
+
+            type Alignment = <::jlrs::data::layout::union::AlignTo<{
+                let mut align = 1;
+                #(#align_checks)*
+                align
+            }> as ::jlrs::data::layout::union::SelectAlign>::Align;
+
+            fn is_variant_type(ty: ::jlrs::data::managed::value::Value) -> bool {
+                let mut n_matches = 0;
+                #(#is_variant_checks)*
+
+                n_matches == 1
+            }
+
+            fn variant_type<'target, Tgt>(&self, target: &Tgt) -> ::jlrs::data::managed::datatype::DataType<'target>
+            where
+                Tgt: ::jlrs::memory::target::Target<'target>,
+            {
+                unsafe {
+                    match self {
+                        #(#variant_type_arms)*
+                    }
+                }
+            }
+
+            unsafe fn read_variant(
+                ty: ::jlrs::data::managed::datatype::DataType,
+                ptr: *const u8,
+            ) -> Option<Self> {
+                let ty = ::jlrs::data::managed::Managed::as_value(ty);
+
+                #(#read_checks)*
+
+                None
+            }
+
+            unsafe fn write_variant(self, ptr: *mut u8) {
+                match self {
+                    #(#write_arms)*
+                }
+            }
+        }
+    };
+
+    union_enum_impl.into()
+}
+
+fn enum_repr(ast: &syn::DeriveInput) -> Option<syn::Ident> {
+    const INT_TYPES: &[&str] = &[
+        "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize",
+    ];
+
+    for attr in &ast.attrs {
+        if attr.path().is_ident("repr") {
+            let p: Result<syn::Ident, _> = attr.parse_args();
+            if let Ok(p) = p {
+                if INT_TYPES.iter().any(|ty| p == ty) {
+                    return Some(p);
+                }
+            }
+        }
+    }
+
+    None
+}
@@ -2,6 +2,8 @@
 mod derive;
 #[cfg(feature = "ccall")]
 mod module;
+#[cfg(feature = "derive")]
+mod reflect;
 mod version;
 
 use proc_macro::TokenStream;
@@ -10,6 +12,8 @@ use proc_macro::TokenStream;
 use self::derive::*;
 #[cfg(feature = "ccall")]
 use self::module::*;
+#[cfg(feature = "derive")]
+use self::reflect::ReflectInput;
 use self::version::emit_if_compatible;
 
 /// Export functions, types and constants defined in Rust as a Julia module.
@@ -29,6 +33,14 @@ use self::version::emit_if_compatible;
 ///     // `foo_jl_init`.
 ///     become init_function_name;
 ///
+///     // Alternatively, a function that returns the executor that is used to poll the futures
+///     // returned by exported async functions can be provided. This function is called once
+///     // when the module is initialized, its return type must implement `FutureExecutor`. If
+///     // no executor is provided, a `DefaultExecutor` is used.
+///     //
+///     // Only one init function can be declared.
+///     // become init_function_name with create_executor;
+///
 ///     // Exports the function `foo` as `bar` with documentation.
 ///     //
 ///     // The `unsafe extern "C" part of the signature must be elided, the signature is verified
@@ -51,6 +63,18 @@ use self::version::emit_if_compatible;
 ///     // This syntax can be used to extend existing functions.
 ///     fn foo(arr: Array) -> usize as Base.bar!;
 ///
+///     // Exports the function `solve` with the keyword argument `tol`, whose default value is
+///     // `1e-6`.
+///     //
+///     // Arguments annotated with `#[kw]` are keyword arguments in Julia, arguments can have a
+///     // default value which must be a literal. Positional arguments with a default value must
+///     // come after all positional arguments without one. The exported function takes all
+///     // arguments positionally in their original order, additional Julia methods are generated
+///     // that accept the keyword arguments and fill in the default values. If the argument is a
+///     // primitive type, `Bool`, `Char`, `JuliaString` or a `TypedValue` of such a type, the
+///     // keyword argument is typed and the default value is converted to that type.
+///     fn solve(x: Array, #[kw] tol: f64 = 1e-6) -> f64;
+///
 ///     // Exports the struct `MyType` as `MyForeignType`. `MyType` must implement `OpaqueType`
 ///     // or `ForeignType`.
 ///     struct MyType as MyForeignType;
@@ -68,6 +92,86 @@ use self::version::emit_if_compatible;
 ///     // `self` is taken by value, it's cloned after being tracked.
 ///     in MyType fn add(&mut self, incr: u32) -> RustResultRet<u32>  as increment!;
 ///
+///     // Exports `MyIterator` as an iterable type.
+///     //
+///     // `MyIterator` must implement `Iterator` and `OpaqueType`, its items must implement
+///     // `IntoJulia`. Methods for `Base.iterate`, `Base.IteratorSize` and `Base.eltype` are
+///     // generated. If `MyIterator` implements `ExactSizeIterator`, `#[jlrs(iterator, exact_size)]`
+///     // can be used to generate a method for `Base.length` too.
+///     #[jlrs(iterator)]
+///     struct MyIterator;
+///
+///     // Exports `MyArray` as a subtype of `AbstractArray`.
+///     //
+///     // `MyArray` must implement `ForeignArray`, and its super-type must be the
+///     // `AbstractArray{T, N}` returned by `abstract_array_super_type`. Methods for `Base.size`,
+///     // `Base.getindex`, `Base.setindex!` and `Base.IndexStyle` are generated. If the data is
+///     // strided, `#[jlrs(array, strided)]` can be used to generate methods for `Base.strides`,
+///     // `Base.elsize` and `Base.unsafe_convert` too.
+///     #[jlrs(array)]
+///     struct MyArray;
+///
+///     // Exports `MyOrderedType` and generates methods for `Base.show`, `Base.:(==)`,
+///     // `Base.hash` and `Base.isless` that call its `Debug`, `PartialEq`, `Hash` and `Ord`
+///     // implementations.
+///     //
+///     // `Display` and `Clone` can be listed too, they're used to generate methods for
+///     // `Base.print`, and `Base.copy` and `Base.deepcopy_internal` respectively.
+///     #[jlrs(derive(Debug, PartialEq, Hash, Ord))]
+///     struct MyOrderedType;
+///
+///     // Exports `MySerializableType` and generates methods for `Serialization.serialize` and
+///     // `Serialization.deserialize`.
+///     //
+///     // `MySerializableType` must implement `SerializableForeign`, which converts the data to
+///     // and from bytes.
+///     #[jlrs(serializable)]
+///     struct MySerializableType;
+///
+///     // Exports the trait `MyTrait` as the abstract type `MyTrait`, and its methods as the
+///     // functions `value` and `reset!` that take an instance of this abstract type.
+///     //
+///     // Types annotated with `#[jlrs(implements(MyTrait))]` must implement `MyTrait` and their
+///     // super-type must be `trait_abstract_type::<dyn MyTrait, _>(target)`. The receiver of a
+///     // trait method must be `&self` or `&mut self`.
+///     trait MyTrait {
+///         fn value(&self) -> f64;
+///         fn reset(&mut self) as reset!;
+///     }
+///
+///     #[jlrs(implements(MyTrait))]
+///     struct MyTraitImpl;
+///
+///     // Exports `MyType::count` and `MyType::set_count` as the getter and setter of the property
+///     // `count` of `MyForeignType`.
+///     //
+///     // Methods annotated with `#[jlrs(property)]` are exported with a hidden name, and
+///     // methods for `Base.getproperty`, `Base.setproperty!` and `Base.propertynames` are
+///     // generated that call them. A getter must only take `&self`, a setter must take
+///     // `&mut self` and the new value and its name must start with `set_`. The new value is
+///     // converted to the type expected by the setter.
+///     #[jlrs(property)]
+///     in MyType fn count(&self) -> RustResultRet<u32>;
+///     #[jlrs(property)]
+///     in MyType fn set_count(&mut self, count: u32) -> RustResultRet<Nothing>;
+///
+///     // Exports the generic type `MyGenericType<T>` as the parametric type `MyGenericType{T}`
+///     // with the variants `MyGenericType{Float32}` and `MyGenericType{Float64}`.
+///     //
+///     // The items in a `for` block are exported once for each of the listed types, with the
+///     // parameter replaced by that type. `MyGenericType<T>` must implement `ParametricBase`,
+///     // `ParametricVariant` and `OpaqueType`.
+///     for T in [f32, f64] {
+///         struct MyGenericType<T>;
+///         in MyGenericType<T> fn new(value: T) -> TypedValueRet<MyGenericType<T>> as MyGenericType;
+///     };
+///
+///     // Exports the enum `MyEnum` as `MyJuliaEnum`, a Julia enum created with the `@enum` macro
+///     // whose instances have the same names and values as the variants of `MyEnum`.
+///     //
+///     // `MyEnum` must implement `Enum`, which can be derived.
+///     enum MyEnum as MyJuliaEnum;
+///
 ///     // Exports the function `long_running_func`, the returned closure is executed on another
 ///     // thread.
 ///     //
@@ -85,6 +189,15 @@ use self::version::emit_if_compatible;
 ///         array: ArrayUnbound
 ///     ) -> JlrsResult<impl AsyncCallback<i32>>;
 ///
+///     // Exports the function `async_io`, the returned future is polled on the executor.
+///     //
+///     // Unlike the previous function, the generated Julia function doesn't wait for the result
+///     // but immediately returns a `Task` that completes when the future resolves. Like the
+///     // closure returned by `long_running_func`, the future must not call into Julia.
+///     async fn async_io(
+///         array: ArrayUnbound
+///     ) -> JlrsResult<impl RustFuture<i32>>;
+///
 ///     // Exports `MY_CONST` as the constant `MY_CONST`, its type must implement `IntoJulia`.
 ///     // `MY_CONST` can be defined in Rust as either static or constant data, i.e. both
 ///     // `static MY_CONST: u8 = 1` and `const MY_CONST: u8 = 1` can be exposed this way.
@@ -111,6 +224,46 @@ use self::version::emit_if_compatible;
 /// end
 /// ```
 ///
+/// If Julia is embedded in the same binary that defines the module, the module can be installed
+/// without building a library by calling `Module::install_rust_module` with the init function:
+///
+/// ```ignore
+/// let module = Value::eval_string(&mut frame, "module MyRustModule end")
+///     .into_jlrs_result()?
+///     .cast::<Module>()?;
+/// module.install_rust_module(frame.as_extended_target(), init_function_name)?;
+/// ```
+///
+/// `@wrapmodule` generates the exported functions when the library is loaded, which makes them
+/// invisible to tools that analyze Julia code. Instead, the source code of a module that defines
+/// these functions and their documentation can be generated with
+/// `Module::generate_rust_module_bindings`, including the keyword, future and property
+/// wrappers. The generated module still calls the init function to create the exported types,
+/// constants and globals. `julia_module` also generates a function
+/// named `<init_function_name>_abi_fingerprint`, the generated module checks that the fingerprint
+/// returned by the library that is loaded matches the fingerprint of the bindings. The
+/// fingerprint is computed from the names and signatures of the exported items, the size and
+/// alignment of the exported types and of the argument and return types of non-generic exported
+/// functions, and the version of jlrs:
+///
+/// ```ignore
+/// let module = Value::eval_string(&mut frame, "module MyRustModule end")
+///     .into_jlrs_result()?
+///     .cast::<Module>()?;
+/// let bindings = module.generate_rust_module_bindings(
+///     &mut frame,
+///     "path/to/lib",
+///     "init_function_name",
+///     init_function_name,
+///     init_function_name_abi_fingerprint(),
+/// )?;
+/// std::fs::write("MyRustModule.jl", bindings)?;
+/// ```
+///
+/// `jlrs::ccall::write_rust_module_bindings` starts Julia and writes the bindings to a file, it
+/// can be called from a build script or a binary that depends on the crate that defines the
+/// module.
+///
 /// It can be rather tricky to figure out how data is passed from Julia to Rust when `ccall`ing
 /// a function written in Rust. Primitive and `isbits` types are passed by value, managed types
 /// provided directly by jlrs are guaranteed to be boxed, all other types might be passed by
@@ -161,7 +314,10 @@ pub fn into_julia_derive(input: TokenStream) -> TokenStream {
 
 /// Derive `Unbox`.
 ///
-/// Should only be used in combination with layouts generated by JlrsReflect.jl
+/// Should only be used in combination with layouts generated by JlrsReflect.jl, or with structs
+/// that have a field annotated with `#[jlrs(union_enum)]` whose type implements `UnionEnum`. The
+/// layout of such a struct is generated, the field is converted to the enum when the data is
+/// unboxed.
 #[cfg(feature = "derive")]
 #[proc_macro_derive(Unbox, attributes(jlrs))]
 pub fn unbox_derive(input: TokenStream) -> TokenStream {
@@ -171,7 +327,8 @@ pub fn unbox_derive(input: TokenStream) -> TokenStream {
 
 /// Derive `Typecheck`.
 ///
-/// Should only be used in combination with layouts generated by JlrsReflect.jl
+/// Should only be used in combination with layouts generated by JlrsReflect.jl. If a field is
+/// annotated with `#[jlrs(union_enum)]`, `Unbox` must be derived too.
 #[cfg(feature = "derive")]
 #[proc_macro_derive(Typecheck, attributes(jlrs))]
 pub fn typecheck_derive(input: TokenStream) -> TokenStream {
@@ -228,3 +385,71 @@ pub fn ccall_return_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse(input).unwrap();
     impl_ccall_return(&ast)
 }
+
+/// Derive `Enum`.
+///
+/// Can only be derived for C-like enums with an integer representation, e.g. `#[repr(i32)]`.
+/// The corresponding Julia type is registered when it's created with `Enum::create_type` or by
+/// exporting the enum with `julia_module`, an enum defined in Julia must be registered with
+/// `Enum::register_type`. Besides `Enum`, this derive implements `IntoJulia`, `Unbox`,
+/// `ValidLayout`, `ValidField`, `Typecheck`, `ConstructType`, `CCallArg` and `CCallReturn`.
+#[cfg(feature = "derive")]
+#[proc_macro_derive(Enum, attributes(jlrs))]
+pub fn enum_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    impl_enum(&ast)
+}
+
+/// Derive `UnionEnum`.
+///
+/// Can only be derived for enums whose variants either have a single unnamed field or no fields
+/// at all. A variant with a field of type `T` corresponds to the Julia type of `T`, at most one
+/// variant can have no fields, this variant corresponds to `Nothing`. Two variants can't have the
+/// same type, if two types are aliases of the same Julia type a union that contains it is
+/// incompatible with the enum.
+#[cfg(feature = "derive")]
+#[proc_macro_derive(UnionEnum, attributes(jlrs))]
+pub fn union_enum_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    impl_union_enum(&ast)
+}
+
+/// Generate layouts for Julia types at compile time.
+///
+/// This macro starts Julia, evaluates each argument as a type and expands to the layouts that
+/// `JlrsCore.Reflect.reflect` generates for these types and the types they depend on:
+///
+/// ```ignore
+/// reflect!("MyPkg.MyStruct{Float64}", "MyPkg.OtherStruct");
+/// ```
+///
+/// The root modules of the paths in these types are imported before the types are evaluated, so
+/// the packages must be installed in the active environment together with JlrsCore. Julia is
+/// found in `JULIA_DIR/bin`, or on the `PATH` if `JULIA_DIR` isn't set. The traits derived by
+/// the generated layouts must be in scope, e.g. by importing the prelude.
+///
+/// A file in which the layouts are stored can be provided with `check`, the path is relative to
+/// the crate's manifest directory:
+///
+/// ```ignore
+/// reflect!("MyPkg.MyStruct{Float64}"; check = "layouts/my_struct.rs");
+/// ```
+///
+/// Compilation fails if the generated layouts don't match the layouts in that file, or if the
+/// file doesn't exist; the error contains the generated layouts. This catches changes to the
+/// Julia types that require the Rust code that uses these layouts to be updated. The file is
+/// never written to unless the `JLRS_UPDATE_LAYOUTS` environment variable is set, in which case
+/// the generated layouts are written to it instead of being checked:
+///
+/// ```text
+/// JLRS_UPDATE_LAYOUTS=1 cargo build
+/// ```
+#[cfg(feature = "derive")]
+#[proc_macro]
+pub fn reflect(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as ReflectInput);
+    match input.generate() {
+        Ok(generated) => generated.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -1,16 +1,18 @@
-use std::iter::FromIterator;
+use std::{collections::HashSet, iter::FromIterator};
 
 use proc_macro::TokenStream;
-use proc_macro2::Span;
+use proc_macro2::{Group, Span, TokenStream as TokenStream2, TokenTree};
 use quote::{format_ident, ToTokens};
 use syn::{
-    parse::{Parse, ParseStream},
+    braced, bracketed, parenthesized,
+    parse::{Parse, ParseStream, Parser},
     parse_quote, parse_quote_spanned,
     punctuated::Punctuated,
     spanned::Spanned,
     token::Comma,
-    AttrStyle, Attribute, Error, Expr, ExprLit, FnArg, GenericArgument, Ident, ItemFn, Lit, Meta,
-    Path, PathArguments, Result, ReturnType, Signature, Token, Type, TypeImplTrait, TypeParamBound,
+    Abi, AttrStyle, Attribute, Error, Expr, ExprLit, FnArg, GenericArgument, Generics, Ident,
+    ItemFn, ItemImpl, Lit, Meta, Pat, Path, PathArguments, Result, ReturnType, Signature, Token,
+    Type, TypeImplTrait, TypeParamBound, UnOp,
 };
 
 type RenameFragments = Punctuated<Ident, Token![.]>;
@@ -18,6 +20,7 @@ type RenameFragments = Punctuated<Ident, Token![.]>;
 struct InitFn {
     _become_token: Token![become],
     init_fn: Ident,
+    executor_fn: Option<Path>,
 }
 
 impl Parse for InitFn {
@@ -25,9 +28,21 @@ impl Parse for InitFn {
         let init_fn_token = input.parse()?;
         let init_fn = input.parse()?;
 
+        let executor_fn = if input.peek(Ident) {
+            let with: Ident = input.parse()?;
+            if with != "with" {
+                Err(Error::new(with.span(), "Expected `with` or `;`."))?;
+            }
+
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
         Ok(InitFn {
             _become_token: init_fn_token,
             init_fn,
+            executor_fn,
         })
     }
 }
@@ -35,14 +50,35 @@ impl Parse for InitFn {
 struct ExportedType {
     _struct_token: Token![struct],
     name: Ident,
+    ty: Type,
+    is_parametric: bool,
     _as_token: Option<Token![as]>,
     name_override: Option<RenameFragments>,
+    // `Some(exact_size)` if the type is exported as an iterator.
+    iterator: Option<bool>,
+    // `Some(strided)` if the type is exported as an array.
+    array: Option<bool>,
+    // The traits listed in `#[jlrs(derive(...))]`.
+    derives: Vec<Ident>,
+    // `true` if the type implements `SerializableForeign`.
+    serializable: bool,
+    // The exported traits listed in `#[jlrs(implements(...))]`.
+    implements: Vec<Ident>,
 }
 
 impl Parse for ExportedType {
     fn parse(input: ParseStream) -> Result<Self> {
         let struct_token = input.parse()?;
-        let name = input.parse()?;
+        let ty: Type = input.parse()?;
+
+        let (name, is_parametric) = match ty {
+            Type::Path(ref path) if path.qself.is_none() => {
+                let last = path.path.segments.last().unwrap();
+                let is_parametric = !last.arguments.is_empty();
+                (last.ident.clone(), is_parametric)
+            }
+            _ => Err(Error::new(ty.span(), "Expected a type name."))?,
+        };
 
         let lookahead = input.lookahead1();
         if lookahead.peek(Token![as]) {
@@ -52,13 +88,71 @@ impl Parse for ExportedType {
             Ok(ExportedType {
                 _struct_token: struct_token,
                 name,
+                ty,
+                is_parametric,
                 _as_token: Some(as_token),
                 name_override: Some(name_override),
+                iterator: None,
+                array: None,
+                derives: Vec::new(),
+                serializable: false,
+                implements: Vec::new(),
             })
         } else {
             Ok(ExportedType {
                 _struct_token: struct_token,
                 name,
+                ty,
+                is_parametric,
+                _as_token: None,
+                name_override: None,
+                iterator: None,
+                array: None,
+                derives: Vec::new(),
+                serializable: false,
+                implements: Vec::new(),
+            })
+        }
+    }
+}
+
+struct ExportedEnum {
+    _enum_token: Token![enum],
+    name: Ident,
+    ty: Type,
+    _as_token: Option<Token![as]>,
+    name_override: Option<RenameFragments>,
+}
+
+impl Parse for ExportedEnum {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let enum_token = input.parse()?;
+        let ty: Type = input.parse()?;
+
+        let name = match ty {
+            Type::Path(ref path) if path.qself.is_none() => {
+                path.path.segments.last().unwrap().ident.clone()
+            }
+            _ => Err(Error::new(ty.span(), "Expected a type name."))?,
+        };
+
+        let lookahead = input.lookahead1();
+        if lookahead.peek(Token![as]) {
+            let as_token = input.parse()?;
+            let name_override = RenameFragments::parse_separated_nonempty(input)?;
+
+            Ok(ExportedEnum {
+                _enum_token: enum_token,
+                name,
+                ty,
+                _as_token: Some(as_token),
+                name_override: Some(name_override),
+            })
+        } else {
+            Ok(ExportedEnum {
+                _enum_token: enum_token,
+                name,
+                ty,
                 _as_token: None,
                 name_override: None,
             })
@@ -66,16 +160,164 @@ impl Parse for ExportedType {
     }
 }
 
+struct ExportedTrait {
+    _trait_token: Token![trait],
+    name: Ident,
+    methods: Vec<ExportedFunction>,
+}
+
+impl Parse for ExportedTrait {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let trait_token = input.parse()?;
+        let name = input.parse()?;
+
+        let content;
+        braced!(content in input);
+
+        let mut methods = Vec::new();
+        while !content.is_empty() {
+            let method: ExportedFunction = content.parse()?;
+
+            match method.func.inputs.first() {
+                Some(FnArg::Receiver(r)) if r.reference.is_some() => (),
+                _ => Err(Error::new(
+                    method.func.ident.span(),
+                    "trait methods must take `&self` or `&mut self`",
+                ))?,
+            }
+
+            methods.push(method);
+            content.parse::<Token![;]>()?;
+        }
+
+        Ok(ExportedTrait {
+            _trait_token: trait_token,
+            name,
+            methods,
+        })
+    }
+}
+
+// Keyword and default argument options of an argument of an exported function or method.
+#[derive(Default)]
+struct ArgOptions {
+    keyword: Option<Ident>,
+    default: Option<Expr>,
+}
+
+// Parses the signature of an exported function or method. Unlike `Signature::parse`, arguments
+// can be annotated with `#[kw]` and can have a default value, e.g. `#[kw] tol: f64 = 1e-6`.
+fn parse_signature(input: ParseStream) -> Result<(Signature, Vec<ArgOptions>)> {
+    let unsafety = input.parse()?;
+    let abi: Option<Abi> = input.parse()?;
+    let fn_token = input.parse()?;
+    let ident = input.parse()?;
+    let mut generics: Generics = input.parse()?;
+
+    let content;
+    let paren_token = parenthesized!(content in input);
+    let mut inputs = Punctuated::new();
+    let mut arg_options = Vec::new();
+
+    while !content.is_empty() {
+        let mut attrs = content.call(Attribute::parse_outer)?;
+        let n_attrs = attrs.len();
+        attrs.retain(|attr| !attr.path().is_ident("kw"));
+        let is_keyword = attrs.len() != n_attrs;
+
+        let mut arg: FnArg = content.parse()?;
+        let mut options = ArgOptions::default();
+
+        match arg {
+            FnArg::Receiver(ref mut r) => {
+                if is_keyword {
+                    Err(Error::new(r.span(), "`self` can't be a keyword argument"))?;
+                }
+                r.attrs = attrs;
+            }
+            FnArg::Typed(ref mut ty) => {
+                if is_keyword {
+                    match &*ty.pat {
+                        Pat::Ident(pat) => options.keyword = Some(pat.ident.clone()),
+                        pat => Err(Error::new(
+                            pat.span(),
+                            "keyword arguments must be named with an identifier",
+                        ))?,
+                    }
+                }
+                ty.attrs = attrs;
+            }
+        }
+
+        if content.peek(Token![=]) {
+            let eq_token: Token![=] = content.parse()?;
+            if let FnArg::Receiver(_) = arg {
+                Err(Error::new(
+                    eq_token.span(),
+                    "`self` can't have a default value",
+                ))?;
+            }
+            options.default = Some(content.parse()?);
+        }
+
+        inputs.push_value(arg);
+        arg_options.push(options);
+
+        if content.is_empty() {
+            break;
+        }
+
+        inputs.push_punct(content.parse()?);
+    }
+
+    let output = input.parse()?;
+    generics.where_clause = input.parse()?;
+
+    let mut has_default = false;
+    for (arg, options) in inputs.iter().zip(arg_options.iter()) {
+        if options.keyword.is_some() {
+            continue;
+        }
+
+        if options.default.is_some() {
+            has_default = true;
+        } else if has_default {
+            Err(Error::new(
+                arg.span(),
+                "positional arguments without a default value can't follow arguments with a default value",
+            ))?;
+        }
+    }
+
+    let func = Signature {
+        constness: None,
+        asyncness: None,
+        unsafety,
+        abi,
+        fn_token,
+        ident,
+        generics,
+        paren_token,
+        inputs,
+        variadic: None,
+        output,
+    };
+
+    Ok((func, arg_options))
+}
+
 struct ExportedFunction {
     func: Signature,
+    arg_options: Vec<ArgOptions>,
     _as_token: Option<Token![as]>,
     name_override: Option<RenameFragments>,
     exclamation_mark_token: Option<Token![!]>,
+    hidden: bool,
 }
 
 impl Parse for ExportedFunction {
     fn parse(input: ParseStream) -> Result<Self> {
-        let func = input.parse()?;
+        let (func, arg_options) = parse_signature(input)?;
 
         let lookahead = input.lookahead1();
         if lookahead.peek(Token![as]) {
@@ -85,16 +327,20 @@ impl Parse for ExportedFunction {
 
             Ok(ExportedFunction {
                 func,
+                arg_options,
                 _as_token: Some(as_token),
                 name_override: Some(name_override),
                 exclamation_mark_token,
+                hidden: false,
             })
         } else {
             Ok(ExportedFunction {
                 func,
+                arg_options,
                 _as_token: None,
                 name_override: None,
                 exclamation_mark_token: None,
+                hidden: false,
             })
         }
     }
@@ -104,16 +350,18 @@ struct ExportedMethod {
     _in_token: Token![in],
     parent: Type,
     func: Signature,
+    arg_options: Vec<ArgOptions>,
     _as_token: Option<Token![as]>,
     name_override: Option<RenameFragments>,
     exclamation_mark_token: Option<Token![!]>,
+    property: bool,
 }
 
 impl Parse for ExportedMethod {
     fn parse(input: ParseStream) -> Result<Self> {
         let in_token = input.parse()?;
         let parent = input.parse()?;
-        let func = input.parse()?;
+        let (func, arg_options) = parse_signature(input)?;
 
         let lookahead = input.lookahead1();
         if lookahead.peek(Token![as]) {
@@ -125,18 +373,22 @@ impl Parse for ExportedMethod {
                 _in_token: in_token,
                 parent,
                 func,
+                arg_options,
                 _as_token: Some(as_token),
                 name_override: Some(name_override),
                 exclamation_mark_token,
+                property: false,
             })
         } else {
             Ok(ExportedMethod {
                 _in_token: in_token,
                 parent,
                 func,
+                arg_options,
                 _as_token: None,
                 name_override: None,
                 exclamation_mark_token: None,
+                property: false,
             })
         }
     }
@@ -145,6 +397,7 @@ impl Parse for ExportedMethod {
 struct ExportedAsyncCallback {
     _async_token: Token![async],
     func: Signature,
+    arg_options: Vec<ArgOptions>,
     _as_token: Option<Token![as]>,
     name_override: Option<RenameFragments>,
     exclamation_mark_token: Option<Token![!]>,
@@ -153,7 +406,7 @@ struct ExportedAsyncCallback {
 impl Parse for ExportedAsyncCallback {
     fn parse(input: ParseStream) -> Result<Self> {
         let async_token = input.parse()?;
-        let func = input.parse()?;
+        let (func, arg_options) = parse_signature(input)?;
 
         let lookahead = input.lookahead1();
         if lookahead.peek(Token![as]) {
@@ -164,6 +417,7 @@ impl Parse for ExportedAsyncCallback {
             Ok(ExportedAsyncCallback {
                 _async_token: async_token,
                 func,
+                arg_options,
                 _as_token: Some(as_token),
                 name_override: Some(name_override),
                 exclamation_mark_token,
@@ -172,6 +426,7 @@ impl Parse for ExportedAsyncCallback {
             Ok(ExportedAsyncCallback {
                 _async_token: async_token,
                 func,
+                arg_options,
                 _as_token: None,
                 name_override: None,
                 exclamation_mark_token: None,
@@ -319,20 +574,173 @@ impl ItemWithAttrs {
     }
 }
 
-impl Parse for ItemWithAttrs {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let attr: Vec<Attribute> = input.call(Attribute::parse_outer)?;
-        let item: ModuleItem = input.parse()?;
-        Ok(ItemWithAttrs {
-            attrs: attr,
+// The traits that can be used in `#[jlrs(derive(...))]`, in the order their functions are passed
+// to `init_base_methods`.
+const DERIVABLE_TRAITS: [&str; 6] = ["Debug", "Display", "PartialEq", "Hash", "Clone", "Ord"];
+
+// The arguments that can be used in `#[jlrs(...)]` in addition to `derive(...)` and
+// `implements(...)`.
+const JLRS_ATTRIBUTE_ARGS: [&str; 6] = [
+    "property",
+    "iterator",
+    "exact_size",
+    "array",
+    "strided",
+    "serializable",
+];
+
+// Parses an item with attributes. `#[jlrs(property)]` turns an exported method into a property
+// getter or setter, `#[jlrs(iterator)]` and `#[jlrs(iterator, exact_size)]` export a type as an
+// iterator, `#[jlrs(array)]` and `#[jlrs(array, strided)]` export a type as an array,
+// `#[jlrs(serializable)]` generates serialization methods for a type, `#[jlrs(derive(...))]`
+// generates methods for functions in `Base` from the listed traits, and
+// `#[jlrs(implements(...))]` lists the exported traits a type implements. The remaining
+// attributes must be docs.
+fn parse_item_with_attrs(input: ParseStream) -> Result<ModuleItem> {
+    let attrs: Vec<Attribute> = input.call(Attribute::parse_outer)?;
+    let mut jlrs_attrs = Vec::new();
+    let mut docs = Vec::with_capacity(attrs.len());
+
+    for attr in attrs {
+        if attr.path().is_ident("jlrs") {
+            let metas = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            let mut args = Vec::with_capacity(metas.len());
+            let mut derives = Vec::new();
+            let mut implements = Vec::new();
+
+            for meta in metas {
+                match meta {
+                    Meta::Path(ref path) if path.get_ident().is_some() => {
+                        args.push(path.get_ident().unwrap().clone())
+                    }
+                    Meta::List(ref list) if list.path.is_ident("derive") => derives.extend(
+                        list.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?,
+                    ),
+                    Meta::List(ref list) if list.path.is_ident("implements") => implements.extend(
+                        list.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?,
+                    ),
+                    _ => Err(syn::Error::new_spanned(
+                        meta.to_token_stream(),
+                        "expected an identifier, `derive(...)` or `implements(...)`",
+                    ))?,
+                }
+            }
+
+            jlrs_attrs.push((attr, args, derives, implements));
+        } else {
+            docs.push(attr);
+        }
+    }
+
+    let mut item: ModuleItem = input.parse()?;
+    for (attr, args, derives, implements) in jlrs_attrs {
+        if let Some(derive) = derives
+            .iter()
+            .find(|derive| !DERIVABLE_TRAITS.iter().any(|t| *derive == t))
+        {
+            Err(Error::new(
+                derive.span(),
+                "expected `Debug`, `Display`, `PartialEq`, `Hash`, `Clone` or `Ord`",
+            ))?;
+        }
+
+        if !derives.is_empty() || !implements.is_empty() {
+            match item {
+                ModuleItem::ExportedType(ref mut ty) => {
+                    ty.derives.extend(derives);
+                    ty.implements.extend(implements);
+                }
+                _ => Err(syn::Error::new_spanned(
+                    attr.to_token_stream(),
+                    "only exported types can derive methods or implement traits",
+                ))?,
+            }
+
+            if args.is_empty() {
+                continue;
+            }
+        }
+
+        let is_property = args.iter().any(|arg| arg == "property");
+        let is_iterator = args.iter().any(|arg| arg == "iterator");
+        let is_exact_size = args.iter().any(|arg| arg == "exact_size");
+        let is_array = args.iter().any(|arg| arg == "array");
+        let is_strided = args.iter().any(|arg| arg == "strided");
+        let is_serializable = args.iter().any(|arg| arg == "serializable");
+
+        if let Some(arg) = args
+            .iter()
+            .find(|arg| !JLRS_ATTRIBUTE_ARGS.iter().any(|known| *arg == known))
+        {
+            Err(Error::new(
+                arg.span(),
+                "expected `property`, `iterator`, `exact_size`, `array`, `strided`, `serializable`, `derive(...)` or `implements(...)`",
+            ))?;
+        }
+
+        if (is_exact_size && !is_iterator) || (is_strided && !is_array) {
+            Err(syn::Error::new_spanned(
+                attr.to_token_stream(),
+                "`exact_size` requires `iterator`, `strided` requires `array`",
+            ))?;
+        }
+
+        if is_property {
+            if let Some(arg) = args.iter().find(|arg| *arg != "property") {
+                Err(Error::new(
+                    arg.span(),
+                    format!("`property` can't be combined with `{}`", arg),
+                ))?;
+            }
+        }
+
+        match item {
+            ModuleItem::ExportedMethod(ref mut method) if is_property => method.property = true,
+            ModuleItem::ExportedType(ref mut ty) if is_iterator && is_array => {
+                Err(syn::Error::new_spanned(
+                    ty.ty.to_token_stream(),
+                    "a type can't be exported both as an iterator and as an array",
+                ))?
+            }
+            ModuleItem::ExportedType(ref mut ty) if !is_property => {
+                if is_iterator {
+                    ty.iterator = Some(is_exact_size);
+                }
+
+                if is_array {
+                    ty.array = Some(is_strided);
+                }
+
+                if is_serializable {
+                    ty.serializable = true;
+                }
+            }
+            _ if is_property => Err(syn::Error::new_spanned(
+                attr.to_token_stream(),
+                "only exported methods can be properties",
+            ))?,
+            _ => Err(syn::Error::new_spanned(
+                attr.to_token_stream(),
+                "only exported types can be iterators, arrays or serializable",
+            ))?,
+        }
+    }
+
+    if docs.is_empty() {
+        Ok(item)
+    } else {
+        Ok(ModuleItem::ItemWithAttrs(ItemWithAttrs {
+            attrs: docs,
             item: Box::new(item),
-        })
+        }))
     }
 }
 
 enum ModuleItem {
     InitFn(InitFn),
     ExportedType(ExportedType),
+    ExportedEnum(ExportedEnum),
+    ExportedTrait(ExportedTrait),
     ExportedFunction(ExportedFunction),
     ExportedMethod(ExportedMethod),
     ExportedAsyncCallback(ExportedAsyncCallback),
@@ -440,6 +848,46 @@ impl ModuleItem {
         }
     }
 
+    fn is_exported_enum(&self) -> bool {
+        match self {
+            ModuleItem::ExportedEnum(_) => true,
+            ModuleItem::ItemWithAttrs(ItemWithAttrs { item, .. }) if item.is_exported_enum() => {
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn get_exported_enum(&self) -> &ExportedEnum {
+        match self {
+            ModuleItem::ExportedEnum(ref exported_enum) => exported_enum,
+            ModuleItem::ItemWithAttrs(ItemWithAttrs { item, .. }) if item.is_exported_enum() => {
+                item.get_exported_enum()
+            }
+            _ => panic!(),
+        }
+    }
+
+    fn is_exported_trait(&self) -> bool {
+        match self {
+            ModuleItem::ExportedTrait(_) => true,
+            ModuleItem::ItemWithAttrs(ItemWithAttrs { item, .. }) if item.is_exported_trait() => {
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn get_exported_trait(&self) -> &ExportedTrait {
+        match self {
+            ModuleItem::ExportedTrait(ref exported_trait) => exported_trait,
+            ModuleItem::ItemWithAttrs(ItemWithAttrs { item, .. }) if item.is_exported_trait() => {
+                item.get_exported_trait()
+            }
+            _ => panic!(),
+        }
+    }
+
     fn is_exported_const(&self) -> bool {
         match self {
             ModuleItem::ExportedConst(_) => true,
@@ -502,6 +950,10 @@ impl Parse for ModuleItem {
             input.parse().map(ModuleItem::InitFn)
         } else if lookahead.peek(Token![struct]) {
             input.parse().map(ModuleItem::ExportedType)
+        } else if lookahead.peek(Token![enum]) {
+            input.parse().map(ModuleItem::ExportedEnum)
+        } else if lookahead.peek(Token![trait]) {
+            input.parse().map(ModuleItem::ExportedTrait)
         } else if lookahead.peek(Token![fn]) {
             input.parse().map(ModuleItem::ExportedFunction)
         } else if lookahead.peek(Token![in]) {
@@ -513,109 +965,726 @@ impl Parse for ModuleItem {
         } else if lookahead.peek(Token![static]) {
             input.parse().map(ModuleItem::ExportedGlobal)
         } else if lookahead.peek(Token![#]) {
-            input.parse().map(ModuleItem::ItemWithAttrs)
+            parse_item_with_attrs(input)
         } else {
             Err(Error::new(
                 input.span(),
-                "Expected `become`, `fn`, `in`, `struct`, `const`, or `static`.",
+                "Expected `become`, `fn`, `in`, `struct`, `enum`, `trait`, `const`, or `static`.",
             ))
         }
     }
 }
 
-pub(crate) struct JuliaModule {
-    items: Punctuated<ModuleItem, Token![;]>,
-}
+// Parses a sequence of items separated by semicolons. The items in a `for` block are parsed once
+// for each of the listed types, with the parameter replaced by that type.
+fn parse_module_items(input: ParseStream) -> Result<Vec<ModuleItem>> {
+    let mut items = Vec::new();
 
-impl Parse for JuliaModule {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let content = input;
-        let items = content.parse_terminated(ModuleItem::parse, Token![;])?;
+    while !input.is_empty() {
+        if input.peek(Token![for]) {
+            items.extend(parse_items_with_params(input)?);
+            if input.peek(Token![;]) {
+                input.parse::<Token![;]>()?;
+            }
+            continue;
+        }
 
-        Ok(JuliaModule { items: items })
+        let item: ModuleItem = input.parse()?;
+        let is_trait = item.is_exported_trait();
+        items.push(item);
+        if input.is_empty() {
+            break;
+        }
+
+        // Like `for` blocks, traits end with a block so the semicolon is optional.
+        if is_trait && !input.peek(Token![;]) {
+            continue;
+        }
+
+        input.parse::<Token![;]>()?;
     }
+
+    Ok(items)
 }
 
-impl JuliaModule {
-    pub(crate) fn generate_init_code(self) -> Result<TokenStream> {
-        let init_fn = self.get_init_fn()?;
-        let init_fn_ident = &init_fn.init_fn;
+fn parse_items_with_params(input: ParseStream) -> Result<Vec<ModuleItem>> {
+    input.parse::<Token![for]>()?;
+    let param: Ident = input.parse()?;
+    input.parse::<Token![in]>()?;
 
-        let fn_fragments = FunctionFragments::generate(&self, init_fn)?;
-        let method_fragments = MethodFragments::generate(&self, init_fn);
-        let async_callback_fragments = AsyncCallbackFragments::generate(&self, init_fn)?;
-        let type_fragments = TypeFragments::generate(&self, init_fn);
-        let const_fragments = ConstFragments::generate(&self, init_fn);
-        let global_fragments = GlobalFragments::generate(&self, init_fn);
-        let doc_fragments = DocFragments::generate(&self, init_fn)?;
+    let types;
+    bracketed!(types in input);
+    let types = types.parse_terminated(Type::parse, Token![,])?;
 
-        let type_init_fn = type_fragments.type_init_fn;
-        let type_init_fn_ident = type_fragments.type_init_ident;
-        let type_reinit_fn = type_fragments.type_reinit_fn;
-        let type_reinit_fn_ident = type_fragments.type_reinit_ident;
-        let function_init_fn = fn_fragments.init_functions_fn;
-        let function_init_fn_ident = fn_fragments.init_functions_fn_ident;
-        let method_init_fn = method_fragments.init_methods_fn;
-        let method_init_fn_ident = method_fragments.init_methods_fn_ident;
-        let async_callback_init_fn = async_callback_fragments.init_async_callbacks_fn;
-        let async_callback_init_fn_ident = async_callback_fragments.init_async_callbacks_fn_ident;
-        let const_init_fn = const_fragments.const_init_fn;
-        let const_init_fn_ident = const_fragments.const_init_ident;
-        let global_init_fn = global_fragments.global_init_fn;
-        let global_init_fn_ident = global_fragments.global_init_ident;
-        let doc_init_fn = doc_fragments.init_docs_fn;
-        let doc_init_fn_ident = doc_fragments.init_docs_fn_ident;
+    let content;
+    braced!(content in input);
+    let content: TokenStream2 = content.parse()?;
 
-        let invoke_type_init: Expr = if type_reinit_fn_ident.is_none() {
-            parse_quote! {
-                {
-                    #type_init_fn_ident(&mut frame, module);
-                }
-            }
-        } else {
-            parse_quote! {
-                if precompiling == 1 {
-                    #type_init_fn_ident(&mut frame, module);
-                } else {
-                    #type_reinit_fn_ident(&mut frame, module);
-                }
-            }
-        };
+    let mut items = Vec::new();
+    for ty in types.iter() {
+        let substituted = substitute_param(content.clone(), &param, &ty.to_token_stream());
+        items.extend(parse_module_items.parse2(substituted)?);
+    }
 
-        let invoke_const_init: Expr = parse_quote! {
-            if precompiling == 1 {
-                #const_init_fn_ident(&mut frame, module);
-            }
-        };
+    Ok(items)
+}
 
-        let invoke_global_init: Expr = parse_quote! {
-            if precompiling == 1 {
-                #global_init_fn_ident(&mut frame, module);
+fn substitute_param(tokens: TokenStream2, param: &Ident, ty: &TokenStream2) -> TokenStream2 {
+    tokens
+        .into_iter()
+        .map(|tt| match tt {
+            TokenTree::Ident(ref ident) if ident == param => ty.clone(),
+            TokenTree::Group(group) => {
+                let stream = substitute_param(group.stream(), param, ty);
+                let mut substituted = Group::new(group.delimiter(), stream);
+                substituted.set_span(group.span());
+                TokenTree::Group(substituted).into()
             }
-        };
+            tt => tt.into(),
+        })
+        .collect()
+}
 
-        let generated = quote::quote! {
+pub(crate) struct JuliaModule {
+    items: Vec<ModuleItem>,
+    generated_fns: Vec<ItemFn>,
+    generated_impls: Vec<ItemImpl>,
+}
 
-            #[no_mangle]
-            pub unsafe extern "C" fn #init_fn_ident(
-                module: ::jlrs::data::managed::module::Module,
-                precompiling: u8,
-            ) -> ::jlrs::data::managed::value::ValueRet {
-                #type_init_fn
+impl Parse for JuliaModule {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut items = parse_module_items(input)?;
+        let mut generated_fns = add_iterator_fns(&mut items)?;
+        generated_fns.extend(add_array_fns(&mut items)?);
+        generated_fns.extend(add_base_fns(&mut items)?);
+        generated_fns.extend(add_serialization_fns(&mut items)?);
+
+        let (trait_fns, generated_impls) = add_trait_items(&mut items)?;
+        generated_fns.extend(trait_fns);
+
+        Ok(JuliaModule {
+            items,
+            generated_fns,
+            generated_impls,
+        })
+    }
+}
 
-                #type_reinit_fn
+// Marks a generated helper function as hidden, it's defined with a hidden name and isn't
+// exported.
+fn hidden_fn(mut exported_fn: ExportedFunction) -> ModuleItem {
+    exported_fn.hidden = true;
+    ModuleItem::ExportedFunction(exported_fn)
+}
 
-                #function_init_fn
+// The names of the generated Rust and Julia functions that advance an exported iterator and
+// return its length.
+struct IteratorFnNames {
+    next_ident: Ident,
+    length_ident: Ident,
+    next_name: Ident,
+    length_name: Ident,
+}
 
-                #method_init_fn
+impl IteratorFnNames {
+    fn new(idx: usize, ty: &ExportedType) -> Self {
+        let name = julia_name(&ty.name, &ty.name_override, &None);
 
-                #async_callback_init_fn
+        IteratorFnNames {
+            next_ident: format_ident!("__jlrs_iterator_next_{}", idx),
+            length_ident: format_ident!("__jlrs_iterator_length_{}", idx),
+            next_name: format_ident!("iterator_next_{}", name),
+            length_name: format_ident!("iterator_length_{}", name),
+        }
+    }
+}
 
-                #const_init_fn
+// Generates the functions that advance exported iterators and return their length, and exports
+// them as hidden functions.
+fn add_iterator_fns(items: &mut Vec<ModuleItem>) -> Result<Vec<ItemFn>> {
+    let mut iterator_fns = Vec::new();
+    let mut exported_fns = Vec::new();
 
-                #global_init_fn
+    let iterators = items
+        .iter()
+        .filter(|it| it.is_exported_type())
+        .map(|it| it.get_exported_type())
+        .filter(|ty| ty.iterator.is_some());
+
+    for (idx, info) in iterators.enumerate() {
+        let ty = &info.ty;
+        let IteratorFnNames {
+            next_ident,
+            length_ident,
+            next_name,
+            length_name,
+        } = IteratorFnNames::new(idx, info);
+
+        iterator_fns.push(parse_quote! {
+            unsafe extern "C" fn #next_ident(
+                iter: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+            ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> {
+                ::jlrs::data::types::foreign_iterator::iterator_next(iter)
+            }
+        });
 
-                #doc_init_fn
+        exported_fns.push(syn::parse2(quote::quote! {
+            fn #next_ident(
+                iter: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+            ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> as #next_name
+        })?);
+
+        if info.iterator == Some(true) {
+            iterator_fns.push(parse_quote! {
+                unsafe extern "C" fn #length_ident(
+                    iter: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+                ) -> ::jlrs::data::managed::rust_result::RustResultRet<usize> {
+                    ::jlrs::data::types::foreign_iterator::iterator_length(iter)
+                }
+            });
+
+            exported_fns.push(syn::parse2(quote::quote! {
+                fn #length_ident(
+                    iter: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+                ) -> ::jlrs::data::managed::rust_result::RustResultRet<usize> as #length_name
+            })?);
+        }
+    }
+
+    items.extend(exported_fns.into_iter().map(hidden_fn));
+    Ok(iterator_fns)
+}
+
+// The names of the generated Rust and Julia functions that access an exported array.
+struct ArrayFnNames {
+    idents: [Ident; 5],
+    names: [Ident; 5],
+}
+
+impl ArrayFnNames {
+    const KINDS: [&'static str; 5] = ["size", "getindex", "setindex", "strides", "pointer"];
+
+    fn new(idx: usize, ty: &ExportedType) -> Self {
+        let name = julia_name(&ty.name, &ty.name_override, &None);
+        let kinds = Self::KINDS;
+
+        ArrayFnNames {
+            idents: kinds.map(|kind| format_ident!("__jlrs_array_{}_{}", kind, idx)),
+            names: kinds.map(|kind| format_ident!("array_{}_{}", kind, name)),
+        }
+    }
+}
+
+// Generates the functions that access exported arrays, and exports them as hidden functions.
+fn add_array_fns(items: &mut Vec<ModuleItem>) -> Result<Vec<ItemFn>> {
+    let mut array_fns = Vec::new();
+    let mut exported_fns = Vec::new();
+
+    let arrays = items
+        .iter()
+        .filter(|it| it.is_exported_type())
+        .map(|it| it.get_exported_type())
+        .filter(|ty| ty.array.is_some());
+
+    for (idx, info) in arrays.enumerate() {
+        let ty = &info.ty;
+        let ArrayFnNames { idents, names } = ArrayFnNames::new(idx, info);
+        let n_fns = if info.array == Some(true) { 5 } else { 3 };
+
+        let signatures: [TokenStream2; 5] = [
+            quote::quote! {
+                (arr: ::jlrs::data::managed::value::typed::TypedValue<#ty>)
+                    -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType>
+            },
+            quote::quote! {
+                (
+                    arr: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+                    index: ::jlrs::data::managed::value::Value,
+                ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType>
+            },
+            quote::quote! {
+                (
+                    arr: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+                    value: ::jlrs::data::managed::value::Value,
+                    index: ::jlrs::data::managed::value::Value,
+                ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::layout::nothing::Nothing>
+            },
+            quote::quote! {
+                (arr: ::jlrs::data::managed::value::typed::TypedValue<#ty>)
+                    -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType>
+            },
+            quote::quote! {
+                (arr: ::jlrs::data::managed::value::typed::TypedValue<#ty>)
+                    -> ::jlrs::data::managed::rust_result::RustResultRet<usize>
+            },
+        ];
+
+        let bodies: [TokenStream2; 5] = [
+            quote::quote! { ::jlrs::data::types::foreign_array::array_size(arr) },
+            quote::quote! { ::jlrs::data::types::foreign_array::array_getindex(arr, index) },
+            quote::quote! { ::jlrs::data::types::foreign_array::array_setindex(arr, value, index) },
+            quote::quote! { ::jlrs::data::types::foreign_array::array_strides(arr) },
+            quote::quote! { ::jlrs::data::types::foreign_array::array_pointer(arr) },
+        ];
+
+        for ((ident, name), (signature, body)) in idents
+            .iter()
+            .zip(names.iter())
+            .zip(signatures.iter().zip(bodies.iter()))
+            .take(n_fns)
+        {
+            array_fns.push(syn::parse2(quote::quote! {
+                unsafe extern "C" fn #ident #signature {
+                    #body
+                }
+            })?);
+
+            exported_fns.push(syn::parse2(quote::quote! {
+                fn #ident #signature as #name
+            })?);
+        }
+    }
+
+    items.extend(exported_fns.into_iter().map(hidden_fn));
+    Ok(array_fns)
+}
+
+// The names of the generated Rust and Julia functions that call the traits listed in
+// `#[jlrs(derive(...))]`, in the same order as `DERIVABLE_TRAITS`.
+struct BaseFnNames {
+    idents: [Ident; 6],
+    names: [Ident; 6],
+}
+
+impl BaseFnNames {
+    const KINDS: [&'static str; 6] = ["debug", "display", "eq", "hash", "clone", "cmp"];
+
+    fn new(idx: usize, ty: &ExportedType) -> Self {
+        let name = julia_name(&ty.name, &ty.name_override, &None);
+        let kinds = Self::KINDS;
+
+        BaseFnNames {
+            idents: kinds.map(|kind| format_ident!("__jlrs_base_{}_{}", kind, idx)),
+            names: kinds.map(|kind| format_ident!("base_{}_{}", kind, name)),
+        }
+    }
+}
+
+// Generates the functions that call the traits listed in `#[jlrs(derive(...))]`, and exports
+// them as hidden functions.
+fn add_base_fns(items: &mut Vec<ModuleItem>) -> Result<Vec<ItemFn>> {
+    let mut base_fns = Vec::new();
+    let mut exported_fns = Vec::new();
+
+    let types = items
+        .iter()
+        .filter(|it| it.is_exported_type())
+        .map(|it| it.get_exported_type())
+        .filter(|ty| !ty.derives.is_empty());
+
+    for (idx, info) in types.enumerate() {
+        let ty = &info.ty;
+        let BaseFnNames { idents, names } = BaseFnNames::new(idx, info);
+
+        let value = quote::quote! {
+            (value: ::jlrs::data::managed::value::typed::TypedValue<#ty>)
+        };
+        let pair = quote::quote! {
+            (
+                a: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+                b: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+            )
+        };
+        let any_type = quote::quote! { ::jlrs::data::types::abstract_types::AnyType };
+
+        let signatures: [(&TokenStream2, TokenStream2); 6] = [
+            (&value, any_type.clone()),
+            (&value, any_type),
+            (&pair, quote::quote! { bool }),
+            (&value, quote::quote! { u64 }),
+            (&value, quote::quote! { #ty }),
+            (&pair, quote::quote! { bool }),
+        ];
+
+        let bodies: [TokenStream2; 6] = [
+            quote::quote! { ::jlrs::data::types::foreign_traits::debug_string(value) },
+            quote::quote! { ::jlrs::data::types::foreign_traits::display_string(value) },
+            quote::quote! { ::jlrs::data::types::foreign_traits::partial_eq(a, b) },
+            quote::quote! { ::jlrs::data::types::foreign_traits::hash(value) },
+            quote::quote! { ::jlrs::data::types::foreign_traits::clone(value) },
+            quote::quote! { ::jlrs::data::types::foreign_traits::is_less(a, b) },
+        ];
+
+        for (i, derivable) in DERIVABLE_TRAITS.iter().enumerate() {
+            if !info.derives.iter().any(|derive| derive == derivable) {
+                continue;
+            }
+
+            let ident = &idents[i];
+            let name = &names[i];
+            let (args, ret) = &signatures[i];
+            let body = &bodies[i];
+
+            base_fns.push(syn::parse2(quote::quote! {
+                unsafe extern "C" fn #ident #args -> ::jlrs::data::managed::rust_result::RustResultRet<#ret> {
+                    #body
+                }
+            })?);
+
+            exported_fns.push(syn::parse2(quote::quote! {
+                fn #ident #args -> ::jlrs::data::managed::rust_result::RustResultRet<#ret> as #name
+            })?);
+        }
+    }
+
+    items.extend(exported_fns.into_iter().map(hidden_fn));
+    Ok(base_fns)
+}
+
+// The names of the generated Rust and Julia functions that serialize and deserialize an exported
+// type.
+struct SerializationFnNames {
+    serialize_ident: Ident,
+    deserialize_ident: Ident,
+    serialize_name: Ident,
+    deserialize_name: Ident,
+}
+
+impl SerializationFnNames {
+    fn new(idx: usize, ty: &ExportedType) -> Self {
+        let name = julia_name(&ty.name, &ty.name_override, &None);
+
+        SerializationFnNames {
+            serialize_ident: format_ident!("__jlrs_serialize_{}", idx),
+            deserialize_ident: format_ident!("__jlrs_deserialize_{}", idx),
+            serialize_name: format_ident!("serialize_{}", name),
+            deserialize_name: format_ident!("deserialize_{}", name),
+        }
+    }
+}
+
+// Generates the functions that serialize and deserialize exported types, and exports them as
+// hidden functions.
+fn add_serialization_fns(items: &mut Vec<ModuleItem>) -> Result<Vec<ItemFn>> {
+    let mut serialization_fns = Vec::new();
+    let mut exported_fns = Vec::new();
+
+    let types = items
+        .iter()
+        .filter(|it| it.is_exported_type())
+        .map(|it| it.get_exported_type())
+        .filter(|ty| ty.serializable);
+
+    for (idx, info) in types.enumerate() {
+        let ty = &info.ty;
+        let SerializationFnNames {
+            serialize_ident,
+            deserialize_ident,
+            serialize_name,
+            deserialize_name,
+        } = SerializationFnNames::new(idx, info);
+
+        serialization_fns.push(parse_quote! {
+            unsafe extern "C" fn #serialize_ident(
+                value: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+            ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> {
+                ::jlrs::data::types::foreign_serialization::serialize_foreign(value)
+            }
+        });
+
+        exported_fns.push(syn::parse2(quote::quote! {
+            fn #serialize_ident(
+                value: ::jlrs::data::managed::value::typed::TypedValue<#ty>,
+            ) -> ::jlrs::data::managed::rust_result::RustResultRet<::jlrs::data::types::abstract_types::AnyType> as #serialize_name
+        })?);
+
+        serialization_fns.push(parse_quote! {
+            unsafe extern "C" fn #deserialize_ident(
+                bytes: ::jlrs::data::managed::array::TypedArray<u8>,
+            ) -> ::jlrs::data::managed::rust_result::RustResultRet<#ty> {
+                ::jlrs::data::types::foreign_serialization::deserialize_foreign(bytes)
+            }
+        });
+
+        exported_fns.push(syn::parse2(quote::quote! {
+            fn #deserialize_ident(
+                bytes: ::jlrs::data::managed::array::TypedArray<u8>,
+            ) -> ::jlrs::data::managed::rust_result::RustResultRet<#ty> as #deserialize_name
+        })?);
+    }
+
+    items.extend(exported_fns.into_iter().map(hidden_fn));
+    Ok(serialization_fns)
+}
+
+// Generates the functions that call the methods of exported traits and exports them as
+// functions that take an instance of the trait's abstract type, and implements `ImplementsTrait`
+// for the types that implement these traits.
+fn add_trait_items(items: &mut Vec<ModuleItem>) -> Result<(Vec<ItemFn>, Vec<ItemImpl>)> {
+    let mut trait_fns = Vec::new();
+    let mut trait_impls = Vec::new();
+    let mut exported_fns = Vec::new();
+
+    let traits = items
+        .iter()
+        .filter(|it| it.is_exported_trait())
+        .map(|it| it.get_exported_trait());
+
+    for (trait_idx, info) in traits.enumerate() {
+        let trait_name = &info.name;
+        let this_ty: Type = parse_quote! {
+            ::jlrs::data::types::trait_object::TraitObject<dyn #trait_name>
+        };
+
+        for (method_idx, method) in info.methods.iter().enumerate() {
+            let method_name = &method.func.ident;
+            let ident = format_ident!("__jlrs_trait_{}_{}", trait_idx, method_idx);
+
+            let is_mut = match method.func.inputs.first() {
+                Some(FnArg::Receiver(r)) => r.mutability.is_some(),
+                _ => unreachable!(),
+            };
+
+            let mut args = Vec::with_capacity(method.func.inputs.len());
+            let mut arg_names = Vec::with_capacity(method.func.inputs.len());
+            for arg in method.func.inputs.iter().skip(1) {
+                match arg {
+                    FnArg::Typed(ty) => match &*ty.pat {
+                        Pat::Ident(pat) => {
+                            args.push(ty.clone());
+                            arg_names.push(pat.ident.clone());
+                        }
+                        pat => Err(Error::new(
+                            pat.span(),
+                            "arguments of trait methods must be named with an identifier",
+                        ))?,
+                    },
+                    FnArg::Receiver(r) => Err(Error::new(r.span(), "unexpected receiver"))?,
+                }
+            }
+
+            let (ret_ty, call): (Type, Expr) = match method.func.output {
+                ReturnType::Default => (
+                    parse_quote! { ::jlrs::data::layout::nothing::Nothing },
+                    parse_quote! {
+                        {
+                            #trait_name::#method_name(this, #(#arg_names),*);
+                            ::jlrs::data::layout::nothing::Nothing
+                        }
+                    },
+                ),
+                ReturnType::Type(_, ref ty) => (
+                    ty.as_ref().clone(),
+                    parse_quote! { #trait_name::#method_name(this, #(#arg_names),*) },
+                ),
+            };
+
+            let call_fn = if is_mut {
+                quote::quote! { ::jlrs::data::types::trait_object::call_trait_method_mut }
+            } else {
+                quote::quote! { ::jlrs::data::types::trait_object::call_trait_method }
+            };
+
+            let func: ItemFn = parse_quote! {
+                unsafe extern "C" fn #ident(
+                    this: #this_ty,
+                    #(#args),*
+                ) -> ::jlrs::data::managed::rust_result::RustResultRet<#ret_ty> {
+                    #call_fn(this, |this| #call)
+                }
+            };
+
+            let mut sig = func.sig.clone();
+            sig.unsafety = None;
+            sig.abi = None;
+
+            // The method is exported under its own name unless it has been renamed.
+            let name_override = match method.name_override {
+                Some(ref name_override) => name_override.clone(),
+                None => RenameFragments::from_iter([method_name.clone()]),
+            };
+
+            trait_fns.push(func);
+            exported_fns.push(ExportedFunction {
+                func: sig,
+                arg_options: method
+                    .arg_options
+                    .iter()
+                    .map(|options| ArgOptions {
+                        keyword: options.keyword.clone(),
+                        default: options.default.clone(),
+                    })
+                    .collect(),
+                _as_token: Some(Default::default()),
+                name_override: Some(name_override),
+                exclamation_mark_token: method.exclamation_mark_token,
+                hidden: false,
+            });
+        }
+    }
+
+    let trait_names = items
+        .iter()
+        .filter(|it| it.is_exported_trait())
+        .map(|it| it.get_exported_trait().name.clone())
+        .collect::<Vec<_>>();
+
+    let types = items
+        .iter()
+        .filter(|it| it.is_exported_type())
+        .map(|it| it.get_exported_type());
+
+    for info in types {
+        let ty = &info.ty;
+        for trait_name in info.implements.iter() {
+            if !trait_names.contains(trait_name) {
+                Err(Error::new(
+                    trait_name.span(),
+                    format!("trait `{}` is not exported by this module", trait_name),
+                ))?;
+            }
+
+            trait_impls.push(parse_quote! {
+                impl ::jlrs::data::types::trait_object::ImplementsTrait<dyn #trait_name> for #ty {
+                    fn as_trait_object(&self) -> &(dyn #trait_name + 'static) {
+                        self
+                    }
+
+                    fn as_trait_object_mut(&mut self) -> &mut (dyn #trait_name + 'static) {
+                        self
+                    }
+                }
+            });
+        }
+    }
+
+    items.extend(exported_fns.into_iter().map(ModuleItem::ExportedFunction));
+    Ok((trait_fns, trait_impls))
+}
+
+impl JuliaModule {
+    pub(crate) fn generate_init_code(self) -> Result<TokenStream> {
+        let init_fn = self.get_init_fn()?;
+        let init_fn_ident = &init_fn.init_fn;
+        let fingerprint_fn_ident = format_ident!("{}_abi_fingerprint", init_fn_ident);
+        let fingerprint = self.abi_fingerprint();
+
+        let fn_fragments = FunctionFragments::generate(&self, init_fn)?;
+        let method_fragments = MethodFragments::generate(&self, init_fn);
+        let async_callback_fragments = AsyncCallbackFragments::generate(&self, init_fn)?;
+        let type_fragments = TypeFragments::generate(&self, init_fn);
+        let const_fragments = ConstFragments::generate(&self, init_fn);
+        let global_fragments = GlobalFragments::generate(&self, init_fn);
+        let doc_fragments = DocFragments::generate(&self, init_fn)?;
+        let julia_code_fragments = JuliaCodeFragments::generate(&self, init_fn)?;
+
+        let type_init_fn = type_fragments.type_init_fn;
+        let type_init_fn_ident = type_fragments.type_init_ident;
+        let type_reinit_fn = type_fragments.type_reinit_fn;
+        let type_reinit_fn_ident = type_fragments.type_reinit_ident;
+        let function_init_fn = fn_fragments.init_functions_fn;
+        let function_init_fn_ident = fn_fragments.init_functions_fn_ident;
+        let method_init_fn = method_fragments.init_methods_fn;
+        let method_init_fn_ident = method_fragments.init_methods_fn_ident;
+        let async_callback_init_fn = async_callback_fragments.init_async_callbacks_fn;
+        let async_callback_init_fn_ident = async_callback_fragments.init_async_callbacks_fn_ident;
+        let const_init_fn = const_fragments.const_init_fn;
+        let const_init_fn_ident = const_fragments.const_init_ident;
+        let global_init_fn = global_fragments.global_init_fn;
+        let global_init_fn_ident = global_fragments.global_init_ident;
+        let doc_init_fn = doc_fragments.init_docs_fn;
+        let doc_init_fn_ident = doc_fragments.init_docs_fn_ident;
+        let julia_code_init_fn = julia_code_fragments.init_julia_code_fn;
+        let julia_code_init_fn_ident = julia_code_fragments.init_julia_code_fn_ident;
+        let generated_fns = &self.generated_fns;
+        let generated_impls = &self.generated_impls;
+
+        let invoke_type_init: Expr = if type_reinit_fn_ident.is_none() {
+            parse_quote! {
+                {
+                    #type_init_fn_ident(&mut frame, module);
+                }
+            }
+        } else {
+            parse_quote! {
+                if precompiling != 0 {
+                    #type_init_fn_ident(&mut frame, module);
+                } else {
+                    #type_reinit_fn_ident(&mut frame, module);
+                }
+            }
+        };
+
+        let invoke_const_init: Expr = parse_quote! {
+            if precompiling != 0 {
+                #const_init_fn_ident(&mut frame, module);
+            }
+        };
+
+        let invoke_global_init: Expr = parse_quote! {
+            if precompiling != 0 {
+                #global_init_fn_ident(&mut frame, module);
+            }
+        };
+
+        let invoke_julia_code_init: Expr = parse_quote! {
+            if precompiling != 0 {
+                #julia_code_init_fn_ident(&mut frame, module, precompiling)
+            } else {
+                None
+            }
+        };
+
+        // The executor can only be set once, a warning is shown if it has already been set.
+        let set_executor = init_fn.executor_fn.as_ref().map(|executor_fn| {
+            let warning = format!(
+                "@warn \"Cannot set the executor returned by `{}`, an executor has already been set\"",
+                executor_fn.to_token_stream().to_string().replace(' ', "")
+            );
+
+            quote::quote! {
+                if !::jlrs::ccall::CCall::set_executor(#executor_fn()) {
+                    let _ = ::jlrs::data::managed::value::Value::eval_string(&frame, #warning);
+                }
+            }
+        });
+
+        let generated = quote::quote! {
+            #(
+                #generated_impls
+            )*
+
+            #[no_mangle]
+            pub unsafe extern "C" fn #fingerprint_fn_ident() -> u64 {
+                #fingerprint
+            }
+
+            #[no_mangle]
+            pub unsafe extern "C" fn #init_fn_ident(
+                module: ::jlrs::data::managed::module::Module,
+                precompiling: u8,
+            ) -> ::jlrs::data::managed::value::ValueRet {
+                #type_init_fn
+
+                #type_reinit_fn
+
+                #function_init_fn
+
+                #method_init_fn
+
+                #async_callback_init_fn
+
+                #const_init_fn
+
+                #global_init_fn
+
+                #doc_init_fn
+
+                #julia_code_init_fn
+
+                #(
+                    #generated_fns
+                )*
 
                 static IS_INIT: ::std::sync::atomic::AtomicBool = ::std::sync::atomic::AtomicBool::new(false);
                 if IS_INIT.compare_exchange(false, true, ::std::sync::atomic::Ordering::Relaxed, ::std::sync::atomic::Ordering::Relaxed).is_err() {
@@ -629,6 +1698,8 @@ impl JuliaModule {
                 ccall.init_jlrs(&::jlrs::InstallJlrsCore::Default, Some(module));
 
                 ccall.scope(|mut frame| {
+                    #set_executor
+
                     let wrap_mod = ::jlrs::data::managed::module::Module::main(&frame)
                         .submodule(&frame, "JlrsCore")
                         .unwrap()
@@ -658,6 +1729,7 @@ impl JuliaModule {
                     #invoke_type_init;
                     #invoke_const_init;
                     #invoke_global_init;
+                    let julia_code = #invoke_julia_code_init;
 
                     let mut arr = ::jlrs::data::managed::array::Array::new_for_unchecked(frame.as_extended_target(), 0, function_info_ty.as_value());
                     #function_init_fn_ident(&mut frame, &mut arr, module, function_info_ty);
@@ -665,10 +1737,27 @@ impl JuliaModule {
                     #async_callback_init_fn_ident(&mut frame, &mut arr, module, function_info_ty);
 
                     let mut doc_items = ::jlrs::data::managed::array::Array::new_for_unchecked(frame.as_extended_target(), 0, doc_item_ty.as_value());
-                    if precompiling == 1 {
+                    if precompiling != 0 {
                         #doc_init_fn_ident(&mut frame, &mut doc_items, module, doc_item_ty);
                     }
-                    Ok(module_info_ty.instantiate_unchecked(&frame, [arr.as_value(), doc_items.as_value()]).leak())
+
+                    let module_info = module_info_ty.instantiate_unchecked(&frame, [arr.as_value(), doc_items.as_value()]);
+                    match julia_code {
+                        Some(julia_code) => {
+                            use ::jlrs::{call::Call, convert::into_jlrs_result::IntoJlrsResult};
+
+                            let module_info = module_info.root(&mut frame);
+                            let res = ::jlrs::data::managed::module::Module::core(&frame)
+                                .function(&frame, "tuple")
+                                .unwrap()
+                                .as_managed()
+                                .call2(&mut frame, module_info, julia_code)
+                                .into_jlrs_result()
+                                .expect("Cannot create tuple");
+                            Ok(res.leak())
+                        }
+                        None => Ok(module_info.leak()),
+                    }
                 }).unwrap()
             }
         };
@@ -693,6 +1782,105 @@ impl JuliaModule {
         Ok(init_fn)
     }
 
+    // Generates the body of the function that returns the ABI fingerprint of the module. The
+    // fingerprint is a hash of the names and signatures of the exported items, the size and
+    // alignment of the exported types and of the argument and return types of exported
+    // functions, and the version of jlrs. Docs and formatting don't affect it.
+    fn abi_fingerprint(&self) -> TokenStream2 {
+        let mut description = String::new();
+        let mut layout_tys = Vec::new();
+
+        for func in self.get_exported_functions() {
+            description.push_str("fn ");
+            describe_signature(
+                &mut description,
+                &func.func,
+                &func.arg_options,
+                &func.name_override,
+                &func.exclamation_mark_token,
+            );
+            signature_layouts(&mut layout_tys, &func.func);
+        }
+
+        for method in self.get_exported_methods() {
+            description.push_str("in ");
+            description.push_str(&method.parent.to_token_stream().to_string());
+            if method.property {
+                description.push_str(" property");
+            }
+            description.push(' ');
+            describe_signature(
+                &mut description,
+                &method.func,
+                &method.arg_options,
+                &method.name_override,
+                &method.exclamation_mark_token,
+            );
+            signature_layouts(&mut layout_tys, &method.func);
+        }
+
+        for callback in self.get_exported_async_callbacks() {
+            description.push_str("async ");
+            describe_signature(
+                &mut description,
+                &callback.func,
+                &callback.arg_options,
+                &callback.name_override,
+                &callback.exclamation_mark_token,
+            );
+            signature_layouts(&mut layout_tys, &callback.func);
+        }
+
+        for ty in self.get_exported_types() {
+            description.push_str("struct ");
+            description.push_str(&ty.ty.to_token_stream().to_string());
+            description.push_str(&ty.name_override.to_token_stream().to_string());
+            description.push(';');
+            layout_tys.push(&ty.ty);
+        }
+
+        for ty in self.get_exported_enums() {
+            description.push_str("enum ");
+            description.push_str(&ty.ty.to_token_stream().to_string());
+            description.push_str(&ty.name_override.to_token_stream().to_string());
+            description.push(';');
+            layout_tys.push(&ty.ty);
+        }
+
+        for exported_trait in self.get_exported_traits() {
+            description.push_str("trait ");
+            description.push_str(&exported_trait.name.to_string());
+            description.push(';');
+        }
+
+        for exported_const in self.get_exported_consts() {
+            description.push_str("const ");
+            description.push_str(&exported_const.name.to_string());
+            description.push_str(&exported_const.ty.to_token_stream().to_string());
+            description.push_str(&exported_const.name_override.to_token_stream().to_string());
+            description.push(';');
+            layout_tys.push(&exported_const.ty);
+        }
+
+        for global in self.get_exported_globals() {
+            description.push_str("static ");
+            description.push_str(&global.name.to_string());
+            description.push_str(&global.ty.to_token_stream().to_string());
+            description.push_str(&global.name_override.to_token_stream().to_string());
+            description.push(';');
+            layout_tys.push(&global.ty);
+        }
+
+        quote::quote! {
+            let fingerprint = ::jlrs::ccall::abi_fingerprint(::jlrs::ccall::ABI_FINGERPRINT_SEED, #description.as_bytes());
+            let fingerprint = ::jlrs::ccall::abi_fingerprint(fingerprint, ::jlrs::ccall::JLRS_VERSION.as_bytes());
+            #(
+                let fingerprint = ::jlrs::ccall::layout_fingerprint::<#layout_tys>(fingerprint);
+            )*
+            fingerprint
+        }
+    }
+
     fn get_exported_functions(&self) -> impl Iterator<Item = &ExportedFunction> {
         self.items
             .iter()
@@ -721,6 +1909,20 @@ impl JuliaModule {
             .map(|it| it.get_exported_type())
     }
 
+    fn get_exported_traits(&self) -> impl Iterator<Item = &ExportedTrait> {
+        self.items
+            .iter()
+            .filter(|it| it.is_exported_trait())
+            .map(|it| it.get_exported_trait())
+    }
+
+    fn get_exported_enums(&self) -> impl Iterator<Item = &ExportedEnum> {
+        self.items
+            .iter()
+            .filter(|it| it.is_exported_enum())
+            .map(|it| it.get_exported_enum())
+    }
+
     fn get_exported_consts(&self) -> impl Iterator<Item = &ExportedConst> {
         self.items
             .iter()
@@ -790,6 +1992,258 @@ impl DocFragments {
     }
 }
 
+struct JuliaCodeFragments {
+    init_julia_code_fn_ident: Ident,
+    init_julia_code_fn: ItemFn,
+}
+
+impl JuliaCodeFragments {
+    fn generate(module: &JuliaModule, init_fn: &InitFn) -> Result<Self> {
+        let init_julia_code_fn_ident = format_ident!("{}_julia_code", init_fn.init_fn);
+
+        let mut julia_code = module
+            .get_exported_async_callbacks()
+            .filter(|info| returns_future(&info.func.output))
+            .map(future_wrapper_code)
+            .collect::<Vec<_>>();
+
+        let (property_getters, property_setters) = property_code(module)?;
+        julia_code.extend(property_getters);
+
+        let mut keyword_wrappers = property_setters;
+        for info in module.get_exported_functions() {
+            if let Some(code) = keyword_wrapper_code(
+                &info.func,
+                &info.name_override,
+                &info.exclamation_mark_token,
+                &info.arg_options,
+            )? {
+                keyword_wrappers.push((code, wrapper_arg_type_fragments(&info.func, None)));
+            }
+        }
+
+        for info in module.get_exported_methods() {
+            if let Some(code) = keyword_wrapper_code(
+                &info.func,
+                &info.name_override,
+                &info.exclamation_mark_token,
+                &info.arg_options,
+            )? {
+                let arg_types = wrapper_arg_type_fragments(&info.func, Some(&info.parent));
+                keyword_wrappers.push((code, arg_types));
+            }
+        }
+
+        for info in module.get_exported_async_callbacks() {
+            if let Some(code) = keyword_wrapper_code(
+                &info.func,
+                &info.name_override,
+                &info.exclamation_mark_token,
+                &info.arg_options,
+            )? {
+                keyword_wrappers.push((code, wrapper_arg_type_fragments(&info.func, None)));
+            }
+        }
+
+        // The generated code is collected in a single function that returns the expressions that
+        // define the wrappers, it's called with the types the keyword wrappers depend on.
+        let mut exprs_code = String::from("function (types...)\n    exprs = Any[]\n");
+        for code in julia_code {
+            exprs_code.push_str(&format!(
+                "    push!(exprs, Meta.parseall({}))\n",
+                julia_string_literal(&code)
+            ));
+        }
+
+        let mut wrapper_arg_types = Vec::new();
+        for (code, arg_types) in keyword_wrappers {
+            let offset = wrapper_arg_types.len();
+            let args = (1..=arg_types.len())
+                .map(|idx| format!("types[{}]", offset + idx))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            exprs_code.push_str(&format!("    push!(exprs, ({})({}))\n", code, args));
+            wrapper_arg_types.extend(arg_types);
+        }
+        exprs_code.push_str("    exprs\nend");
+        let n_wrapper_arg_types = wrapper_arg_types.len();
+
+        let init_iterators = module
+            .get_exported_types()
+            .filter(|ty| ty.iterator.is_some())
+            .enumerate()
+            .map(|(idx, info)| -> Expr {
+                let ty = &info.ty;
+                let names = IteratorFnNames::new(idx, info);
+                let next_name = hidden_julia_name(&names.next_name.to_string());
+                let length_name = match info.iterator {
+                    Some(true) => {
+                        let length_name = hidden_julia_name(&names.length_name.to_string());
+                        quote::quote! { Some(#length_name) }
+                    }
+                    _ => quote::quote! { None },
+                };
+
+                parse_quote! {
+                    ::jlrs::data::types::foreign_iterator::init_iterator_methods::<#ty>(
+                        &mut frame,
+                        module,
+                        #next_name,
+                        #length_name,
+                    ).expect("Cannot define iterator methods")
+                }
+            });
+
+        let init_arrays = module
+            .get_exported_types()
+            .filter(|ty| ty.array.is_some())
+            .enumerate()
+            .map(|(idx, info)| -> Expr {
+                let ty = &info.ty;
+                let names = ArrayFnNames::new(idx, info)
+                    .names
+                    .map(|name| hidden_julia_name(&name.to_string()));
+                let [size_name, getindex_name, setindex_name, strides_name, pointer_name] = names;
+                let strided_names = match info.array {
+                    Some(true) => quote::quote! { Some((#strides_name, #pointer_name)) },
+                    _ => quote::quote! { None },
+                };
+
+                parse_quote! {
+                    ::jlrs::data::types::foreign_array::init_array_methods::<#ty>(
+                        &mut frame,
+                        module,
+                        #size_name,
+                        #getindex_name,
+                        #setindex_name,
+                        #strided_names,
+                    ).expect("Cannot define array methods")
+                }
+            });
+
+        let init_base_methods = module
+            .get_exported_types()
+            .filter(|ty| !ty.derives.is_empty())
+            .enumerate()
+            .map(|(idx, info)| -> Expr {
+                let ty = &info.ty;
+                let mut derivables = DERIVABLE_TRAITS.iter();
+                let names = BaseFnNames::new(idx, info).names;
+                let [debug, display, partial_eq, hash, clone, ord] = names.map(|name| {
+                    let derivable = derivables.next().unwrap();
+                    if info.derives.iter().any(|derive| derive == derivable) {
+                        let name = hidden_julia_name(&name.to_string());
+                        quote::quote! { Some(#name) }
+                    } else {
+                        quote::quote! { None }
+                    }
+                });
+
+                parse_quote! {
+                    ::jlrs::data::types::foreign_traits::init_base_methods::<#ty>(
+                        &mut frame,
+                        module,
+                        ::jlrs::data::types::foreign_traits::BaseMethodFns {
+                            debug: #debug,
+                            display: #display,
+                            partial_eq: #partial_eq,
+                            hash: #hash,
+                            clone: #clone,
+                            ord: #ord,
+                        },
+                    ).expect("Cannot define Base methods")
+                }
+            });
+
+        let init_serialization = module
+            .get_exported_types()
+            .filter(|ty| ty.serializable)
+            .enumerate()
+            .map(|(idx, info)| -> Expr {
+                let ty = &info.ty;
+                let names = SerializationFnNames::new(idx, info);
+                let serialize_name = hidden_julia_name(&names.serialize_name.to_string());
+                let deserialize_name = hidden_julia_name(&names.deserialize_name.to_string());
+
+                parse_quote! {
+                    ::jlrs::data::types::foreign_serialization::init_serialization_methods::<#ty>(
+                        &mut frame,
+                        module,
+                        #serialize_name,
+                        #deserialize_name,
+                    ).expect("Cannot define serialization methods")
+                }
+            });
+
+        // The expressions that define the keyword, future and property wrappers are evaluated if
+        // precompiling is 1, and returned if it's 2.
+        let init_julia_code_fn = parse_quote! {
+            unsafe fn #init_julia_code_fn_ident<'scope>(
+                frame: &mut ::jlrs::memory::target::frame::GcFrame<'scope>,
+                module: ::jlrs::data::managed::module::Module,
+                precompiling: u8,
+            ) -> Option<::jlrs::data::managed::value::Value<'scope, 'static>> {
+                use ::jlrs::{
+                    call::Call,
+                    convert::into_jlrs_result::IntoJlrsResult,
+                    data::managed::Managed,
+                };
+
+                let types: [::jlrs::data::managed::value::Value; #n_wrapper_arg_types] = [#(#wrapper_arg_types),*];
+                let exprs = ::jlrs::data::managed::value::Value::eval_string(&mut *frame, #exprs_code)
+                    .into_jlrs_result()
+                    .expect("Cannot create generated Julia code")
+                    .call(&mut *frame, types)
+                    .into_jlrs_result()
+                    .expect("Cannot create generated Julia code");
+
+                if precompiling == 1 {
+                    frame.scope(|mut frame| {
+                        ::jlrs::data::managed::value::Value::eval_string(&mut frame, "(mod, exprs) -> foreach(ex -> Core.eval(mod, ex), exprs)")
+                            .into_jlrs_result()?
+                            .call2(&mut frame, module.as_value(), exprs)
+                            .into_jlrs_result()?;
+
+                        Ok(())
+                    }).expect("Cannot evaluate generated Julia code");
+                }
+
+                frame.scope(|mut frame| {
+                    #(
+                        #init_iterators;
+                    )*
+
+                    #(
+                        #init_arrays;
+                    )*
+
+                    #(
+                        #init_base_methods;
+                    )*
+
+                    #(
+                        #init_serialization;
+                    )*
+
+                    Ok(())
+                }).unwrap();
+
+                if precompiling == 2 {
+                    Some(exprs)
+                } else {
+                    None
+                }
+            }
+        };
+
+        Ok(JuliaCodeFragments {
+            init_julia_code_fn_ident,
+            init_julia_code_fn,
+        })
+    }
+}
+
 struct FunctionFragments {
     init_functions_fn_ident: Ident,
     init_functions_fn: ItemFn,
@@ -937,41 +2391,92 @@ struct TypeFragments {
 
 impl TypeFragments {
     fn generate(info: &JuliaModule, init_fn: &InitFn) -> Self {
+        let has_parametric = info.get_exported_types().any(|ty| ty.is_parametric);
+        let has_enums = info.get_exported_enums().next().is_some();
+        let frame_pat = if has_parametric || has_enums {
+            quote::quote! { mut frame }
+        } else {
+            quote::quote! { frame }
+        };
+
         let init_types_fn_ident = format_ident!("{}_types", init_fn.init_fn);
-        let init_types_fragments = info.get_exported_types().map(init_type_fragment);
+        let mut created_bases = HashSet::new();
+        let init_traits_fragments = info
+            .get_exported_traits()
+            .map(init_trait_fragment)
+            .collect::<Vec<_>>();
+        let init_types_fragments = info
+            .get_exported_types()
+            .map(|ty| init_type_fragment(ty, &mut created_bases))
+            .collect::<Vec<_>>();
+        let init_enums_fragments = info.get_exported_enums().map(init_enum_fragment);
+        let register_impls_fragments = info
+            .get_exported_types()
+            .filter_map(register_trait_impls_fragment)
+            .collect::<Vec<_>>();
 
         let type_init_fn = parse_quote! {
             unsafe fn #init_types_fn_ident(
                 frame: &mut ::jlrs::memory::target::frame::GcFrame,
                 module: ::jlrs::data::managed::module::Module,
             ) {
-                frame.scope(|frame| {
+                frame.scope(|#frame_pat| {
                     let mut output = frame.output();
 
+                    #(
+                        #init_traits_fragments
+                    )*
+
+                    #(
+                        #init_enums_fragments
+                    )*
+
                     #(
                         #init_types_fragments
                     )*
 
+                    #(
+                        #register_impls_fragments
+                    )*
+
                     Ok(())
                 }).unwrap();
             }
         };
 
         let reinit_types_fn_ident = format_ident!("{}_reinittypes", init_fn.init_fn);
-        let reinit_types_fragments = info.get_exported_types().map(reinit_type_fragment);
+        let mut reinit_bases = HashSet::new();
+        let reinit_traits_fragments = info.get_exported_traits().map(reinit_trait_fragment);
+        let reinit_enums_fragments = info.get_exported_enums().map(reinit_enum_fragment);
+        let reinit_types_fragments = info
+            .get_exported_types()
+            .map(|ty| reinit_type_fragment(ty, &mut reinit_bases))
+            .collect::<Vec<_>>();
 
         let type_reinit_fn = parse_quote! {
             unsafe fn #reinit_types_fn_ident(
                 frame: &mut ::jlrs::memory::target::frame::GcFrame,
                 module: jlrs::data::managed::module::Module
             ) {
-                frame.scope(|frame| {
+                frame.scope(|#frame_pat| {
                     let mut output = frame.output();
 
+                    #(
+                        #reinit_traits_fragments
+                    )*
+
+                    #(
+                        #reinit_enums_fragments
+                    )*
+
                     #(
                         #reinit_types_fragments
                     )*
 
+                    #(
+                        #register_impls_fragments
+                    )*
+
                     Ok(())
                 }).unwrap();
             }
@@ -1050,12 +2555,20 @@ fn doc_info_fragment((index, info): (usize, &ItemWithAttrs)) -> Result<Expr> {
             i.init_fn.to_token_stream(),
             "init function cannot be documented",
         ))?,
-        ModuleItem::ExportedType(ty) => {
-            let override_module_fragment = override_module_fragment(&ty.name_override);
-            let name_ident = &ty.name;
-
-            let rename = ty
-                .name_override
+        ModuleItem::ExportedType(ExportedType {
+            name,
+            name_override,
+            ..
+        })
+        | ModuleItem::ExportedEnum(ExportedEnum {
+            name,
+            name_override,
+            ..
+        }) => {
+            let override_module_fragment = override_module_fragment(name_override);
+            let name_ident = name;
+
+            let rename = name_override
                 .as_ref()
                 .map(|parts| parts.last())
                 .flatten()
@@ -1084,6 +2597,29 @@ fn doc_info_fragment((index, info): (usize, &ItemWithAttrs)) -> Result<Expr> {
 
             Ok(q)
         }
+        ModuleItem::ExportedTrait(ExportedTrait { name, .. }) => {
+            let rename = name.to_string();
+            let doc = info.get_docstr()?;
+
+            let q = parse_quote! {
+                {
+                    frame.scope(|mut frame| {
+                        unsafe {
+                            let item = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
+                            let signature = ::jlrs::data::managed::value::Value::bottom_type(&frame);
+                            let doc = ::jlrs::data::managed::string::JuliaString::new(&mut frame, #doc);
+
+                            let doc_it = doc_item_ty.instantiate_unchecked(&mut frame, [module.as_value(), item.as_value(), signature, doc.as_value()]);
+                            accessor.set_value_unchecked(#index, Some(doc_it)).unwrap();
+                        }
+
+                        Ok(())
+                    }).unwrap();
+                }
+            };
+
+            Ok(q)
+        }
         ModuleItem::ExportedFunction(func) => {
             let name_ident = &func.func.ident;
 
@@ -1139,6 +2675,10 @@ fn doc_info_fragment((index, info): (usize, &ItemWithAttrs)) -> Result<Expr> {
                 rename.push('!')
             }
 
+            if func.property {
+                rename = hidden_julia_name(&rename);
+            }
+
             let doc = info.get_docstr()?;
 
             let q = parse_quote! {
@@ -1272,6 +2812,10 @@ fn function_info_fragment((index, info): (usize, &ExportedFunction)) -> Result<E
         rename.push('!')
     }
 
+    if info.hidden {
+        rename = hidden_julia_name(&rename);
+    }
+
     let tys = info.func.inputs.iter().map(|x| match x {
         FnArg::Typed(pat) => &pat.ty,
         _ => unreachable!(),
@@ -1336,140 +2880,684 @@ fn function_info_fragment((index, info): (usize, &ExportedFunction)) -> Result<E
                     accessor.set(#index, Some(instance)).unwrap();
                 }
 
-                Ok(())
-            }).unwrap();
-        }
-    };
+                Ok(())
+            }).unwrap();
+        }
+    };
+
+    Ok(expr)
+}
+
+fn override_module_fragment(name_override: &Option<RenameFragments>) -> Expr {
+    let name_override = name_override.as_ref();
+    if name_override.is_none() {
+        return parse_quote! { { module } };
+    }
+    let name_override = name_override.unwrap();
+    let n_parts = name_override.len();
+    if n_parts == 1 {
+        return parse_quote! { { module } };
+    }
+
+    let modules = name_override
+        .iter()
+        .take(n_parts - 1)
+        .map(|ident| ident.to_string());
+
+    let parsed = parse_quote! {
+        {
+            let mut module = Module::main(&frame);
+
+            #(
+                module = module
+                .submodule(&frame, #modules)
+                .expect("Submodule does not exist")
+                .as_managed();
+            )*
+
+            module
+        }
+    };
+
+    parsed
+}
+
+fn return_type_fragments(ret_ty: &ReturnType) -> (Expr, Expr) {
+    match ret_ty {
+        ReturnType::Default => {
+            let ccall_ret_type: Expr = parse_quote! {
+                ::jlrs::data::managed::datatype::DataType::nothing_type(&frame).as_value()
+            };
+
+            let julia_ret_type = ccall_ret_type.clone();
+            (ccall_ret_type, julia_ret_type)
+        }
+        ReturnType::Type(_, ref ty) => {
+            let span = ty.span();
+            let ccall_ret_type = parse_quote_spanned! {
+                span=> <<#ty as ::jlrs::convert::ccall_types::CCallReturn>::CCallReturnType as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
+            };
+            let julia_ret_type = parse_quote_spanned! {
+                span=> <<#ty as ::jlrs::convert::ccall_types::CCallReturn>::FunctionReturnType as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
+            };
+
+            (ccall_ret_type, julia_ret_type)
+        }
+    }
+}
+
+// FIXME
+
+fn extract_return_type_from_impl_trait(timplt: &TypeImplTrait) -> Option<Type> {
+    let bounds_iter = timplt.bounds.iter();
+    for bound in bounds_iter {
+        match bound {
+            TypeParamBound::Trait(t) => {
+                let segment = t.path.segments.last();
+                if segment.is_none() {
+                    continue;
+                }
+
+                match &segment.unwrap().arguments {
+                    PathArguments::AngleBracketed(p) => {
+                        let arg = p.args.iter().last();
+                        if arg.is_none() {
+                            continue;
+                        }
+
+                        match arg.unwrap() {
+                            GenericArgument::Type(ref t) => return Some(t.clone()),
+                            _ => continue,
+                        }
+                    }
+                    _ => continue,
+                }
+            }
+            _ => continue,
+        }
+    }
+
+    None
+}
+
+fn extract_inner_return_type_from_path(path: &Path) -> Option<Type> {
+    if let Some(segment) = path.segments.last() {
+        match segment.arguments {
+            PathArguments::AngleBracketed(ref args) => {
+                if let Some(arg) = args.args.first() {
+                    match arg {
+                        GenericArgument::Type(t) => match t {
+                            &Type::ImplTrait(ref timplt) => {
+                                return extract_return_type_from_impl_trait(timplt)
+                            }
+                            _ => (),
+                        },
+                        _ => (),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+fn extract_inner_return_type(ty: &Type) -> Option<Type> {
+    match ty {
+        Type::Path(ref p) => extract_inner_return_type_from_path(&p.path),
+        _ => None,
+    }
+}
+
+fn async_callbackreturn_type_fragments(ret_ty: &ReturnType) -> Option<Type> {
+    match ret_ty {
+        ReturnType::Type(_, ty) => extract_inner_return_type(&*ty),
+        _ => None,
+    }
+}
+
+fn returns_future(ret_ty: &ReturnType) -> bool {
+    let ty = match ret_ty {
+        ReturnType::Type(_, ty) => ty,
+        _ => return false,
+    };
+
+    let path = match ty.as_ref() {
+        Type::Path(p) => &p.path,
+        _ => return false,
+    };
+
+    let args = match path.segments.last().map(|segment| &segment.arguments) {
+        Some(PathArguments::AngleBracketed(args)) => args,
+        _ => return false,
+    };
+
+    match args.args.first() {
+        Some(GenericArgument::Type(Type::ImplTrait(timplt))) => {
+            timplt.bounds.iter().any(|bound| match bound {
+                TypeParamBound::Trait(t) => t
+                    .path
+                    .segments
+                    .last()
+                    .map(|segment| segment.ident == "RustFuture")
+                    .unwrap_or(false),
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+// Appends the name, signature, and keyword and default arguments of an exported function to
+// `description`.
+fn describe_signature(
+    description: &mut String,
+    func: &Signature,
+    arg_options: &[ArgOptions],
+    name_override: &Option<RenameFragments>,
+    exclamation_mark_token: &Option<Token![!]>,
+) {
+    description.push_str(&func.to_token_stream().to_string());
+    if let Some(name_override) = name_override {
+        description.push_str(" as ");
+        description.push_str(&name_override.to_token_stream().to_string());
+        if exclamation_mark_token.is_some() {
+            description.push('!');
+        }
+    }
+
+    for options in arg_options {
+        if let Some(keyword) = &options.keyword {
+            description.push_str(" kw ");
+            description.push_str(&keyword.to_string());
+        }
+
+        if let Some(default) = &options.default {
+            description.push_str(" = ");
+            description.push_str(&default.to_token_stream().to_string());
+        }
+    }
+
+    description.push(';');
+}
+
+// Adds the argument and return types of an exported function to `layout_tys`. The layouts of
+// these types can only be checked if the function isn't generic and doesn't use `Self` or
+// `impl Trait`.
+fn signature_layouts<'a>(layout_tys: &mut Vec<&'a Type>, func: &'a Signature) {
+    if !func.generics.params.is_empty() {
+        return;
+    }
+
+    fn is_concrete(tokens: TokenStream2) -> bool {
+        tokens.into_iter().all(|tt| match tt {
+            TokenTree::Ident(ident) => ident != "Self" && ident != "impl",
+            TokenTree::Group(group) => is_concrete(group.stream()),
+            _ => true,
+        })
+    }
+
+    let arg_tys = func.inputs.iter().filter_map(|arg| match arg {
+        FnArg::Typed(pat) => Some(&*pat.ty),
+        FnArg::Receiver(_) => None,
+    });
+    let ret_ty = match &func.output {
+        ReturnType::Type(_, ty) => Some(&**ty),
+        ReturnType::Default => None,
+    };
+
+    layout_tys.extend(
+        arg_tys
+            .chain(ret_ty)
+            .filter(|ty| is_concrete(ty.to_token_stream())),
+    );
+}
+
+fn future_hidden_name(name: &str) -> String {
+    hidden_julia_name(&format!("future_{}", name))
+}
+
+fn future_wrapper_code(info: &ExportedAsyncCallback) -> String {
+    let rename = julia_name(
+        &info.func.ident,
+        &info.name_override,
+        &info.exclamation_mark_token,
+    );
+    let hidden_name = future_hidden_name(&rename);
+    let qualified_name = qualified_julia_name(rename, &info.name_override);
+
+    format!(
+        "function {qualified_name}(args...)\n    \
+            applicable(var\"{hidden_name}\", args...) || throw(MethodError({qualified_name}, args))\n    \
+            Base.@async var\"{hidden_name}\"(args...)\n\
+        end",
+        qualified_name = qualified_name,
+        hidden_name = hidden_name
+    )
+}
+
+fn julia_name(
+    name_ident: &Ident,
+    name_override: &Option<RenameFragments>,
+    exclamation_mark_token: &Option<Token![!]>,
+) -> String {
+    let mut rename = name_override
+        .as_ref()
+        .map(|parts| parts.last())
+        .flatten()
+        .unwrap_or(name_ident)
+        .to_string();
+
+    if exclamation_mark_token.is_some() {
+        rename.push('!')
+    }
+
+    rename
+}
+
+// Generated helper functions and property methods are defined with a name that starts with
+// `#`, like the functions Julia generates itself, so they don't show up in tab completion.
+fn hidden_julia_name(name: &str) -> String {
+    format!("#jlrs_{}", name)
+}
+
+// Returns Julia code that refers to the function `name`, which may be qualified and hidden.
+fn julia_function_ref(name: &str) -> String {
+    let (path, name) = match name.rfind('.') {
+        Some(idx) => name.split_at(idx + 1),
+        None => ("", name),
+    };
+
+    format!("{}var\"{}\"", path, name)
+}
+
+fn qualified_julia_name(rename: String, name_override: &Option<RenameFragments>) -> String {
+    match name_override.as_ref() {
+        Some(parts) if parts.len() > 1 => {
+            let mut path = String::from("Main.");
+            for part in parts.iter().take(parts.len() - 1) {
+                path.push_str(&part.to_string());
+                path.push('.');
+            }
+            path.push_str(&rename);
+            path
+        }
+        _ => rename,
+    }
+}
+
+// Generates a Julia function that defines methods that accept keyword arguments and omit
+// trailing positional arguments with a default value, and call the exported method with all
+// arguments in their original order. No code is generated if no argument is a keyword argument
+// or has a default value.
+//
+// The generated function takes the Julia type of each argument, which are constructed the same
+// way as the argument types of the exported method, and returns the expression that defines
+// these methods. Every parameter is typed and default values are converted to the type of their
+// argument, e.g. `#[kw] x: i32 = 1` becomes `x::Int32=Base.convert(Int32, 1)`. Because the
+// parameters are typed, overloads and variants of parametric types generate distinct methods.
+fn keyword_wrapper_code(
+    func: &Signature,
+    name_override: &Option<RenameFragments>,
+    exclamation_mark_token: &Option<Token![!]>,
+    arg_options: &[ArgOptions],
+) -> Result<Option<String>> {
+    if arg_options
+        .iter()
+        .all(|opt| opt.keyword.is_none() && opt.default.is_none())
+    {
+        return Ok(None);
+    }
+
+    let rename = julia_name(&func.ident, name_override, exclamation_mark_token);
+    let qualified_name = qualified_julia_name(rename, name_override);
+
+    let mut positional = Vec::new();
+    let mut keywords = Vec::new();
+    for (idx, opt) in arg_options.iter().enumerate() {
+        let default = match &opt.default {
+            Some(default) => Some(format!(
+                "Base.convert($T{}, {})",
+                idx,
+                julia_default_value(default)?
+            )),
+            None => None,
+        };
+
+        match (&opt.keyword, default) {
+            (Some(kw), default) => {
+                let mut param = format!("{}::$T{}", kw, idx);
+                if let Some(default) = default {
+                    param.push('=');
+                    param.push_str(&default);
+                }
+                keywords.push(param)
+            }
+            (None, default) => positional.push((idx, default)),
+        }
+    }
+
+    let n_required = positional.iter().filter(|(_, d)| d.is_none()).count();
+    let kw_params = if keywords.is_empty() {
+        String::new()
+    } else {
+        format!("; {}", keywords.join(", "))
+    };
+
+    let mut methods = String::new();
+    for n_provided in n_required..=positional.len() {
+        // This method would have the same signature as the exported method
+        if keywords.is_empty() && n_provided == positional.len() {
+            continue;
+        }
+
+        let params = positional[..n_provided]
+            .iter()
+            .map(|(idx, _)| format!("arg{}::$T{}", idx, idx))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut positional_args = positional.iter().enumerate();
+        let args = arg_options
+            .iter()
+            .map(|opt| match opt.keyword {
+                Some(ref kw) => kw.to_string(),
+                None => {
+                    let (n, (idx, default)) = positional_args.next().unwrap();
+                    if n < n_provided {
+                        format!("arg{}", idx)
+                    } else {
+                        default.clone().unwrap()
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        methods.push_str(&format!(
+            "    {name}({params}{kw_params}) = {name}({args})\n",
+            name = qualified_name,
+            params = params,
+            kw_params = kw_params,
+            args = args
+        ));
+    }
+
+    let type_params = (0..arg_options.len())
+        .map(|idx| format!("T{}", idx))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Ok(Some(format!("({}) -> quote\n{}end", type_params, methods)))
+}
 
-    Ok(expr)
+// Returns the expressions that construct the Julia type of each argument of an exported function
+// or method, `parent` is the type that `self` refers to.
+fn wrapper_arg_type_fragments(func: &Signature, parent: Option<&Type>) -> Vec<Expr> {
+    func.inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Typed(ty) => {
+                let ty = &ty.ty;
+                parse_quote! {
+                    <<#ty as ::jlrs::convert::ccall_types::CCallArg>::FunctionArgType as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
+                }
+            }
+            FnArg::Receiver(_) => {
+                let parent = parent.expect("receiver without parent type");
+                parse_quote! {
+                    <<::jlrs::data::managed::value::typed::TypedValue<#parent> as ::jlrs::convert::ccall_types::CCallArg>::FunctionArgType as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
+                }
+            }
+        })
+        .collect()
 }
 
-fn override_module_fragment(name_override: &Option<RenameFragments>) -> Expr {
-    let name_override = name_override.as_ref();
-    if name_override.is_none() {
-        return parse_quote! { { module } };
-    }
-    let name_override = name_override.unwrap();
-    let n_parts = name_override.len();
-    if n_parts == 1 {
-        return parse_quote! { { module } };
+// A function that returns the expression that defines a wrapper, and the expressions that
+// construct the Julia types it must be called with.
+type WrapperCode = (String, Vec<Expr>);
+
+// Generates `Base.getproperty`, `Base.setproperty!` and `Base.propertynames` methods for the
+// exported types that have methods annotated with `#[jlrs(property)]`. A getter must only take
+// `&self`, a setter must take `&mut self` and the new value and its name must start with `set_`.
+// Property methods are defined with a hidden name, so they can only be called through these
+// methods.
+//
+// The getters and property names are returned as Julia code. The setters convert the new value
+// to the type of the argument of the setter like `setfield!` converts it to the type of the
+// field, so they're returned as a function that takes the Julia type of the receiver and of the
+// new value of each setter and returns the expression that defines `Base.setproperty!`, together
+// with the expressions that construct these types. A variant of a parametric type gets its own
+// `Base.setproperty!` method.
+fn property_code(module: &JuliaModule) -> Result<(Vec<String>, Vec<WrapperCode>)> {
+    struct Properties {
+        type_name: String,
+        getters: Vec<(String, String)>,
+        names: Vec<String>,
     }
 
-    let modules = name_override
-        .iter()
-        .take(n_parts - 1)
-        .map(|ident| ident.to_string());
+    struct Setters {
+        parent: String,
+        receiver_ty: Expr,
+        setters: Vec<(String, String, Expr)>,
+    }
 
-    let parsed = parse_quote! {
-        {
-            let mut module = Module::main(&frame);
+    let mut properties: Vec<Properties> = Vec::new();
+    let mut setters: Vec<Setters> = Vec::new();
+    let mut seen = HashSet::new();
+
+    for info in module.get_exported_methods().filter(|info| info.property) {
+        let parent = info.parent.to_token_stream().to_string();
+        let ty = module
+            .get_exported_types()
+            .find(|ty| ty.ty.to_token_stream().to_string() == parent)
+            .ok_or_else(|| {
+                Error::new(
+                    info.parent.span(),
+                    "properties can only be defined for exported types",
+                )
+            })?;
+
+        let type_name = qualified_julia_name(
+            julia_name(&ty.name, &ty.name_override, &None),
+            &ty.name_override,
+        );
+
+        let fn_name = qualified_julia_name(
+            hidden_julia_name(&julia_name(
+                &info.func.ident,
+                &info.name_override,
+                &info.exclamation_mark_token,
+            )),
+            &info.name_override,
+        );
+
+        let inputs = &info.func.inputs;
+        let (prop_name, is_setter) = match inputs.first() {
+            Some(FnArg::Receiver(r)) if inputs.len() == 1 && r.mutability.is_none() => {
+                (info.func.ident.to_string(), false)
+            }
+            Some(FnArg::Receiver(r)) if inputs.len() == 2 && r.mutability.is_some() => {
+                match info.func.ident.to_string().strip_prefix("set_") {
+                    Some(name) => (name.to_string(), true),
+                    None => Err(Error::new(
+                        info.func.ident.span(),
+                        "the name of a property setter must start with `set_`",
+                    ))?,
+                }
+            }
+            _ => Err(Error::new(
+                info.func.span(),
+                "a property getter must only take `&self`, a setter must take `&mut self` and the new value",
+            ))?,
+        };
 
-            #(
-                module = module
-                .submodule(&frame, #modules)
-                .expect("Submodule does not exist")
-                .as_managed();
-            )*
+        if is_setter {
+            // Every variant of a parametric type gets its own setter because the type of the new
+            // value can depend on the type parameters.
+            let mut arg_types = wrapper_arg_type_fragments(&info.func, Some(&info.parent));
+            let value_ty = arg_types.pop().unwrap();
+            let receiver_ty = arg_types.pop().unwrap();
+
+            let idx = match setters.iter().position(|s| s.parent == parent) {
+                Some(idx) => idx,
+                None => {
+                    setters.push(Setters {
+                        parent,
+                        receiver_ty,
+                        setters: Vec::new(),
+                    });
+                    setters.len() - 1
+                }
+            };
 
-            module
+            setters[idx]
+                .setters
+                .push((prop_name.clone(), fn_name.clone(), value_ty));
         }
-    };
 
-    parsed
-}
+        // Methods in a `for` block are exported once for each variant, but the getters and
+        // property names are defined for the parametric type.
+        if !seen.insert((type_name.clone(), prop_name.clone(), is_setter)) {
+            continue;
+        }
 
-fn return_type_fragments(ret_ty: &ReturnType) -> (Expr, Expr) {
-    match ret_ty {
-        ReturnType::Default => {
-            let ccall_ret_type: Expr = parse_quote! {
-                ::jlrs::data::managed::datatype::DataType::nothing_type(&frame).as_value()
-            };
+        let idx = match properties.iter().position(|p| p.type_name == type_name) {
+            Some(idx) => idx,
+            None => {
+                properties.push(Properties {
+                    type_name,
+                    getters: Vec::new(),
+                    names: Vec::new(),
+                });
+                properties.len() - 1
+            }
+        };
 
-            let julia_ret_type = ccall_ret_type.clone();
-            (ccall_ret_type, julia_ret_type)
+        let name = format!("Symbol(\"{}\")", prop_name);
+        if !properties[idx].names.contains(&name) {
+            properties[idx].names.push(name);
         }
-        ReturnType::Type(_, ref ty) => {
-            let span = ty.span();
-            let ccall_ret_type = parse_quote_spanned! {
-                span=> <<#ty as ::jlrs::convert::ccall_types::CCallReturn>::CCallReturnType as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
-            };
-            let julia_ret_type = parse_quote_spanned! {
-                span=> <<#ty as ::jlrs::convert::ccall_types::CCallReturn>::FunctionReturnType as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
-            };
 
-            (ccall_ret_type, julia_ret_type)
+        if !is_setter {
+            properties[idx].getters.push((prop_name, fn_name));
         }
     }
-}
-
-// FIXME
 
-fn extract_return_type_from_impl_trait(timplt: &TypeImplTrait) -> Option<Type> {
-    let bounds_iter = timplt.bounds.iter();
-    for bound in bounds_iter {
-        match bound {
-            TypeParamBound::Trait(t) => {
-                let segment = t.path.segments.last();
-                if segment.is_none() {
-                    continue;
+    let getters = properties
+        .iter()
+        .map(|props| {
+            let mut code = String::new();
+            let type_name = &props.type_name;
+
+            if !props.getters.is_empty() {
+                code.push_str(&format!(
+                    "function Base.getproperty(x::{}, name::Symbol)\n",
+                    type_name
+                ));
+                for (prop_name, fn_name) in props.getters.iter() {
+                    code.push_str(&format!(
+                        "    name === Symbol(\"{}\") && return {}(x)\n",
+                        prop_name,
+                        julia_function_ref(fn_name)
+                    ));
                 }
+                code.push_str("    getfield(x, name)\nend\n");
+            }
 
-                match &segment.unwrap().arguments {
-                    PathArguments::AngleBracketed(p) => {
-                        let arg = p.args.iter().last();
-                        if arg.is_none() {
-                            continue;
-                        }
+            code.push_str(&format!(
+                "Base.propertynames(::{}, private::Bool=false) = ({},)\n",
+                type_name,
+                props.names.join(", ")
+            ));
 
-                        match arg.unwrap() {
-                            GenericArgument::Type(ref t) => return Some(t.clone()),
-                            _ => continue,
-                        }
-                    }
-                    _ => continue,
-                }
+            code
+        })
+        .collect();
+
+    let setters = setters
+        .into_iter()
+        .map(|props| {
+            let mut code = String::from(
+                "(T0, T...) -> quote\n    function Base.setproperty!(x::$T0, name::Symbol, v)\n",
+            );
+
+            let mut arg_types = vec![props.receiver_ty];
+            for (idx, (prop_name, fn_name, value_ty)) in props.setters.into_iter().enumerate() {
+                code.push_str(&format!(
+                    "        if name === Symbol(\"{}\")\n            v = Base.convert($(T[{}]), v)\n            {}(x, v)\n            return v\n        end\n",
+                    prop_name,
+                    idx + 1,
+                    julia_function_ref(&fn_name)
+                ));
+                arg_types.push(value_ty);
             }
-            _ => continue,
-        }
-    }
 
-    None
+            code.push_str("        setfield!(x, name, v)\n    end\nend");
+            (code, arg_types)
+        })
+        .collect();
+
+    Ok((getters, setters))
 }
 
-fn extract_inner_return_type_from_path(path: &Path) -> Option<Type> {
-    if let Some(segment) = path.segments.last() {
-        match segment.arguments {
-            PathArguments::AngleBracketed(ref args) => {
-                if let Some(arg) = args.args.first() {
-                    match arg {
-                        GenericArgument::Type(t) => match t {
-                            &Type::ImplTrait(ref timplt) => {
-                                return extract_return_type_from_impl_trait(timplt)
-                            }
-                            _ => (),
-                        },
-                        _ => (),
-                    }
-                }
-            }
-            _ => (),
+// Converts the default value of an argument to Julia code. Only literals are supported, a
+// suffix is converted to the matching Julia type.
+fn julia_default_value(expr: &Expr) -> Result<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Int(i) => match i.suffix() {
+                "" => Ok(i.base10_digits().to_string()),
+                suffix => Ok(format!(
+                    "{}({})",
+                    julia_number_type(suffix, i.span())?,
+                    i.base10_digits()
+                )),
+            },
+            Lit::Float(f) => match f.suffix() {
+                "" | "f64" => Ok(f.base10_digits().to_string()),
+                _ => Ok(format!("Float32({})", f.base10_digits())),
+            },
+            Lit::Bool(b) => Ok(b.value.to_string()),
+            Lit::Str(s) => Ok(julia_string_literal(&s.value())),
+            _ => Err(Error::new(
+                lit.span(),
+                "default value must be an integer, float, bool or string literal",
+            )),
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => {
+            Ok(format!("-{}", julia_default_value(&unary.expr)?))
         }
+        _ => Err(Error::new(
+            expr.span(),
+            "default value must be an integer, float, bool or string literal",
+        )),
     }
-
-    None
 }
 
-fn extract_inner_return_type(ty: &Type) -> Option<Type> {
-    match ty {
-        Type::Path(ref p) => extract_inner_return_type_from_path(&p.path),
-        _ => None,
-    }
+// Converts a string to a Julia string literal.
+fn julia_string_literal(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$");
+    format!("\"{}\"", escaped)
 }
 
-fn async_callbackreturn_type_fragments(ret_ty: &ReturnType) -> Option<Type> {
-    match ret_ty {
-        ReturnType::Type(_, ty) => extract_inner_return_type(&*ty),
-        _ => None,
+fn julia_number_type(suffix: &str, span: Span) -> Result<&'static str> {
+    match suffix {
+        "i8" => Ok("Int8"),
+        "i16" => Ok("Int16"),
+        "i32" => Ok("Int32"),
+        "i64" => Ok("Int64"),
+        "isize" => Ok("Int"),
+        "u8" => Ok("UInt8"),
+        "u16" => Ok("UInt16"),
+        "u32" => Ok("UInt32"),
+        "u64" => Ok("UInt64"),
+        "usize" => Ok("UInt"),
+        _ => Err(Error::new(span, "unsupported integer suffix")),
     }
 }
 
@@ -1518,7 +3606,7 @@ fn arg_type_fragments<'a>(
     Ok((ccall_arg_types, julia_arg_types))
 }
 
-fn init_type_fragment(info: &ExportedType) -> Expr {
+fn init_type_fragment(info: &ExportedType, created_bases: &mut HashSet<String>) -> Expr {
     let override_module_fragment = override_module_fragment(&info.name_override);
     let name_ident = &info.name;
 
@@ -1530,7 +3618,31 @@ fn init_type_fragment(info: &ExportedType) -> Expr {
         .unwrap_or(name_ident)
         .to_string();
 
-    let ty = format_ident!("{}", info.name);
+    let ty = &info.ty;
+
+    if info.is_parametric {
+        // The base type is created when the first of its variants is encountered.
+        let base_key = base_type_key(info);
+        let create_base: Option<Expr> = if created_bases.insert(base_key) {
+            Some(parse_quote! {
+                {
+                    let sym = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
+                    let module = #override_module_fragment;
+                    let ty = <#ty as ::jlrs::data::types::foreign_type::ParametricBase>::create_type(frame.as_extended_target(), sym, module);
+                    module.set_const_unchecked(sym, ty);
+                }
+            })
+        } else {
+            None
+        };
+
+        return parse_quote! {
+            {
+                #create_base
+                <#ty as ::jlrs::data::types::foreign_type::ParametricVariant>::create_variant(frame.as_extended_target());
+            }
+        };
+    }
 
     parse_quote! {
         {
@@ -1542,7 +3654,113 @@ fn init_type_fragment(info: &ExportedType) -> Expr {
     }
 }
 
-fn reinit_type_fragment(info: &ExportedType) -> Expr {
+// The abstract types of traits are created before the types that implement them.
+fn init_trait_fragment(info: &ExportedTrait) -> Expr {
+    let name = &info.name;
+    let rename = name.to_string();
+
+    parse_quote! {
+        {
+            let sym = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
+            let ty = ::jlrs::data::types::trait_object::create_trait_type::<dyn #name, _>(&mut output, sym, module);
+            module.set_const_unchecked(sym, <::jlrs::data::managed::datatype::DataType as ::jlrs::data::managed::Managed>::as_value(ty));
+        }
+    }
+}
+
+fn reinit_trait_fragment(info: &ExportedTrait) -> Expr {
+    let name = &info.name;
+    let rename = name.to_string();
+
+    parse_quote! {
+        {
+            let dt = module
+                .global(&frame, #rename)
+                .unwrap()
+                .as_value()
+                .cast::<::jlrs::data::managed::datatype::DataType>()
+                .unwrap();
+
+            ::jlrs::data::types::trait_object::reinit_trait_type::<dyn #name>(dt);
+        }
+    }
+}
+
+// Implementations of traits are registered after all types have been created.
+fn register_trait_impls_fragment(info: &ExportedType) -> Option<Expr> {
+    if info.implements.is_empty() {
+        return None;
+    }
+
+    let ty = &info.ty;
+    let trait_names = info.implements.iter();
+
+    Some(parse_quote! {
+        {
+            #(
+                ::jlrs::data::types::trait_object::register_trait_impl::<dyn #trait_names, #ty, _>(&frame)
+                    .expect("Cannot register trait implementation");
+            )*
+        }
+    })
+}
+
+// Enums are created when the module is defined, the Julia type is registered when it's created.
+fn init_enum_fragment(info: &ExportedEnum) -> Expr {
+    let override_module_fragment = override_module_fragment(&info.name_override);
+    let name_ident = &info.name;
+
+    let rename = info
+        .name_override
+        .as_ref()
+        .map(|parts| parts.last())
+        .flatten()
+        .unwrap_or(name_ident)
+        .to_string();
+
+    let ty = &info.ty;
+
+    parse_quote! {
+        {
+            let sym = ::jlrs::data::managed::symbol::Symbol::new(&frame, #rename);
+            let module = #override_module_fragment;
+            <#ty as ::jlrs::data::layout::julia_enum::Enum>::create_type(frame.as_extended_target(), sym, module).unwrap();
+        }
+    }
+}
+
+// When a precompiled module is loaded the enum already exists, but it must be registered again.
+fn reinit_enum_fragment(info: &ExportedEnum) -> Expr {
+    let override_module_fragment = override_module_fragment(&info.name_override);
+    let name_ident = &info.name;
+
+    let rename = info
+        .name_override
+        .as_ref()
+        .map(|parts| parts.last())
+        .flatten()
+        .unwrap_or(name_ident)
+        .to_string();
+
+    let ty = &info.ty;
+
+    parse_quote! {
+        {
+            let module = #override_module_fragment;
+
+            let dt = module
+                .global(&frame, #rename)
+                .unwrap()
+                .as_value()
+                .cast::<::jlrs::data::managed::datatype::DataType>()
+                .unwrap();
+
+            <#ty as ::jlrs::data::layout::julia_enum::Enum>::register_type(dt).unwrap();
+        }
+    }
+}
+
+fn reinit_type_fragment(info: &ExportedType, reinit_bases: &mut HashSet<String>) -> Expr {
     {
         let override_module_fragment = override_module_fragment(&info.name_override);
         let name_ident = &info.name;
@@ -1555,7 +3773,34 @@ fn reinit_type_fragment(info: &ExportedType) -> Expr {
             .unwrap_or(name_ident)
             .to_string();
 
-        let ty = format_ident!("{}", info.name);
+        let ty = &info.ty;
+
+        if info.is_parametric {
+            let base_key = base_type_key(info);
+            let reinit_base: Option<Expr> = if reinit_bases.insert(base_key) {
+                Some(parse_quote! {
+                    {
+                        let module = #override_module_fragment;
+
+                        let ty = module
+                            .global(&frame, #rename)
+                            .unwrap()
+                            .as_value();
+
+                        <#ty as ::jlrs::data::types::foreign_type::ParametricBase>::reinit_type(ty);
+                    }
+                })
+            } else {
+                None
+            };
+
+            return parse_quote! {
+                {
+                    #reinit_base
+                    <#ty as ::jlrs::data::types::foreign_type::ParametricVariant>::create_variant(frame.as_extended_target());
+                }
+            };
+        }
 
         parse_quote! {
             {
@@ -1574,6 +3819,17 @@ fn reinit_type_fragment(info: &ExportedType) -> Expr {
     }
 }
 
+// Identifies the base type of a parametric type, variants of the same type share the same key.
+fn base_type_key(info: &ExportedType) -> String {
+    let name_override = info
+        .name_override
+        .as_ref()
+        .map(|parts| parts.to_token_stream().to_string())
+        .unwrap_or_default();
+
+    format!("{}::{}", info.name, name_override)
+}
+
 fn method_info_fragment((index, info): (usize, &ExportedMethod)) -> Expr {
     let n_args = info.func.inputs.len();
     let name_ident = &info.func.ident;
@@ -1591,6 +3847,11 @@ fn method_info_fragment((index, info): (usize, &ExportedMethod)) -> Expr {
         rename.push('!')
     }
 
+    // Property methods are only called by the generated property methods.
+    if info.property {
+        rename = hidden_julia_name(&rename);
+    }
+
     let ret_ty = &info.func.output;
     let (ccall_ret_type, julia_ret_type) = return_type_fragments(ret_ty);
 
@@ -1678,11 +3939,20 @@ fn async_callback_info_fragment((index, info): (usize, &ExportedAsyncCallback))
     if inner_ret_ty.is_none() {
         return Ok(parse_quote_spanned! {
             name_ident.span() => {
-                compile_error!("Async callback must return JlrsResult<impl AsyncCallback<T>> where T is some type that implements IntoJulia");
+                compile_error!("Async callback must return JlrsResult<impl AsyncCallback<T>> or JlrsResult<impl RustFuture<T>> where T is some type that implements IntoJulia");
             }
         });
     }
 
+    // Futures are exported with a hidden name, the function exported with the original name is
+    // evaluated as Julia code and wraps the call to the hidden function in a task.
+    let (rename, override_module_fragment) = if returns_future(ret_ty) {
+        let module_fragment: Expr = parse_quote! { { module } };
+        (future_hidden_name(&rename), module_fragment)
+    } else {
+        (rename, override_module_fragment)
+    };
+
     let inner_ret_ty = inner_ret_ty.unwrap();
     let ccall_ret_type: Expr = parse_quote! {
         <::jlrs::ccall::AsyncCCall as ::jlrs::data::types::construct_type::ConstructType>::construct_type(frame.as_extended_target())
@@ -1946,9 +4216,30 @@ fn invoke_async_callback(info: &ExportedAsyncCallback, ret_ty: &Type) -> ItemFn
 
     let names = Punctuated::<_, Comma>::from_iter(names);
 
-    parse_quote_spanned! {
-        span=> unsafe extern "C" fn invoke(#extended_args) -> ::jlrs::ccall::AsyncCCall {
-            let join_handle: ::std::sync::Arc<::jlrs::ccall::DispatchHandle<#ret_ty>> = match #name(#names) {
+    let dispatch: Expr = if returns_future(&info.func.output) {
+        parse_quote_spanned! {
+            span=> match #name(#names) {
+                Ok(future) => {
+                    ::jlrs::ccall::CCall::spawn_future(move |dispatch_handle| async move {
+                        let handle = jlrs_async_condition_handle;
+                        let res = future.await;
+                        unsafe { dispatch_handle.set(res); }
+                        ::jlrs::ccall::CCall::uv_async_send(handle.0);
+                    })
+                },
+                Err(e) => {
+                    ::jlrs::ccall::CCall::spawn_future(move |dispatch_handle| async move {
+                        let handle = jlrs_async_condition_handle;
+                        let res: ::jlrs::error::JlrsResult<#ret_ty> = Err(e);
+                        unsafe { dispatch_handle.set(res); }
+                        ::jlrs::ccall::CCall::uv_async_send(handle.0);
+                    })
+                }
+            }
+        }
+    } else {
+        parse_quote_spanned! {
+            span=> match #name(#names) {
                 Ok(callback) => {
                     ::jlrs::ccall::CCall::dispatch_to_pool(move |dispatch_handle| {
                         let handle = jlrs_async_condition_handle;
@@ -1965,7 +4256,13 @@ fn invoke_async_callback(info: &ExportedAsyncCallback, ret_ty: &Type) -> ItemFn
                         ::jlrs::ccall::CCall::uv_async_send(handle.0);
                     })
                 }
-            };
+            }
+        }
+    };
+
+    parse_quote_spanned! {
+        span=> unsafe extern "C" fn invoke(#extended_args) -> ::jlrs::ccall::AsyncCCall {
+            let join_handle: ::std::sync::Arc<::jlrs::ccall::DispatchHandle<#ret_ty>> = #dispatch;
 
             let join_handle = ::std::sync::Arc::into_raw(join_handle);
 
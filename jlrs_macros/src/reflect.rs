@@ -0,0 +1,368 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Error, Ident, Item, LitStr, Result, Token,
+};
+
+// Imports the root modules of the paths used in each argument, evaluates the arguments as types
+// in `Main`, and prints the layouts generated by `JlrsCore.Reflect.reflect`.
+const REFLECT_SCRIPT: &str = "
+try
+    import JlrsCore
+catch
+    println(stderr, \"JlrsCore is not installed in the active environment\")
+    exit(1)
+end
+
+function roots!(roots, ex)
+    if ex isa Expr
+        if ex.head === :.
+            root = ex
+            while root isa Expr && root.head === :.
+                root = root.args[1]
+            end
+            root isa Symbol && push!(roots, root)
+        else
+            foreach(arg -> roots!(roots, arg), ex.args)
+        end
+    end
+    roots
+end
+
+types = map(ARGS) do arg
+    ex = Meta.parse(arg)
+    for root in roots!(Symbol[], ex)
+        isdefined(Main, root) || Core.eval(Main, :(import $root))
+    end
+    Core.eval(Main, ex)
+end
+
+print(JlrsCore.Reflect.reflect(types))
+";
+
+// If this environment variable is set, the layouts generated by `reflect!` are written to the
+// file provided with `check` instead of being compared with its contents.
+const UPDATE_LAYOUTS_VAR: &str = "JLRS_UPDATE_LAYOUTS";
+
+pub(crate) struct ReflectInput {
+    types: Punctuated<LitStr, Token![,]>,
+    check: Option<LitStr>,
+}
+
+impl Parse for ReflectInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut types = Punctuated::new();
+        while !input.is_empty() && !input.peek(Token![;]) {
+            types.push_value(input.parse()?);
+            if !input.peek(Token![,]) {
+                break;
+            }
+
+            types.push_punct(input.parse()?);
+        }
+
+        if types.is_empty() {
+            Err(input.error("expected at least one type"))?;
+        }
+
+        let check = if input.peek(Token![;]) {
+            let _: Token![;] = input.parse()?;
+            let option: Ident = input.parse()?;
+            if option != "check" {
+                Err(Error::new(option.span(), "expected `check`"))?;
+            }
+
+            let _: Token![=] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        if !input.is_empty() {
+            Err(input.error("unexpected tokens"))?;
+        }
+
+        Ok(ReflectInput { types, check })
+    }
+}
+
+impl ReflectInput {
+    pub(crate) fn generate(self) -> Result<TokenStream> {
+        let generated = run_reflect(&self.types)?;
+        let mut items = parse_layouts(&generated, Span::call_site())?;
+
+        if let Some(check) = self.check {
+            let path =
+                Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(check.value());
+            let update = !env::var_os(UPDATE_LAYOUTS_VAR)
+                .unwrap_or_default()
+                .is_empty();
+            check_layouts(&path, &generated, &items, &check, update)?;
+
+            // Including the file ensures the crate is rebuilt when the file changes.
+            let path = path.to_string_lossy();
+            items.push(syn::parse_quote! {
+                const _: &[u8] = include_bytes!(#path);
+            });
+        }
+
+        Ok(items.into_iter().map(Item::into_token_stream).collect())
+    }
+}
+
+fn julia_executable() -> PathBuf {
+    match env::var("JULIA_DIR") {
+        Ok(dir) => Path::new(&dir).join("bin").join("julia"),
+        Err(_) => PathBuf::from("julia"),
+    }
+}
+
+fn run_reflect(types: &Punctuated<LitStr, Token![,]>) -> Result<String> {
+    let julia = julia_executable();
+    let output = Command::new(&julia)
+        .args([
+            "--startup-file=no",
+            "--history-file=no",
+            "-e",
+            REFLECT_SCRIPT,
+        ])
+        .args(types.iter().map(LitStr::value))
+        .output()
+        .map_err(|e| {
+            Error::new(
+                Span::call_site(),
+                format!("cannot run {}: {}", julia.display(), e),
+            )
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(Error::new(
+            types.first().unwrap().span(),
+            format!("cannot reflect types:\n{}", stderr.trim()),
+        ))?;
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|_| Error::new(Span::call_site(), "generated layouts are not valid UTF-8"))
+}
+
+fn parse_layouts(code: &str, span: Span) -> Result<Vec<Item>> {
+    syn::parse_file(code)
+        .map(|file| file.items)
+        .map_err(|e| Error::new(span, format!("cannot parse generated layouts: {}", e)))
+}
+
+// Compares the generated layouts with the layouts in `path`. The generated layouts are only
+// written to `path` if `update` is true, i.e. if `UPDATE_LAYOUTS_VAR` is set, otherwise they're
+// included in the error.
+fn check_layouts(
+    path: &Path,
+    generated: &str,
+    items: &[Item],
+    check: &LitStr,
+    update: bool,
+) -> Result<()> {
+    if update {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                Error::new(
+                    check.span(),
+                    format!("cannot create {}: {}", dir.display(), e),
+                )
+            })?;
+        }
+
+        return fs::write(path, generated).map_err(|e| {
+            Error::new(
+                check.span(),
+                format!("cannot write {}: {}", path.display(), e),
+            )
+        });
+    }
+
+    if !path.exists() {
+        Err(Error::new(
+            check.span(),
+            format!(
+                "{} doesn't exist, set {}=1 to write the generated layouts to it:\n{}",
+                path.display(),
+                UPDATE_LAYOUTS_VAR,
+                generated
+            ),
+        ))?;
+    }
+
+    let expected = fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            check.span(),
+            format!("cannot read {}: {}", path.display(), e),
+        )
+    })?;
+    let expected = parse_layouts(&expected, check.span())?;
+
+    let mut changed = Vec::new();
+    for item in items {
+        let name = item_name(item);
+        match expected.iter().find(|exp| item_name(exp) == name) {
+            Some(exp) if same_tokens(exp, item) => (),
+            Some(_) => changed.push(format!("the layout of `{}` has changed", name)),
+            None => changed.push(format!("`{}` has been added", name)),
+        }
+    }
+
+    for exp in expected.iter() {
+        let name = item_name(exp);
+        if !items.iter().any(|item| item_name(item) == name) {
+            changed.push(format!("`{}` has been removed", name));
+        }
+    }
+
+    if !changed.is_empty() {
+        Err(Error::new(
+            check.span(),
+            format!(
+                "the reflected layouts don't match {}:\n{}\nset {}=1 to accept the new layouts:\n{}",
+                path.display(),
+                changed.join("\n"),
+                UPDATE_LAYOUTS_VAR,
+                generated
+            ),
+        ))?;
+    }
+
+    Ok(())
+}
+
+fn same_tokens(a: &Item, b: &Item) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
+
+fn item_name(item: &Item) -> String {
+    match item {
+        Item::Struct(s) => s.ident.to_string(),
+        Item::Enum(e) => e.ident.to_string(),
+        Item::Type(t) => t.ident.to_string(),
+        item => item.to_token_stream().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        process,
+    };
+
+    use proc_macro2::Span;
+    use syn::LitStr;
+
+    use super::{check_layouts, parse_layouts, ReflectInput};
+
+    const LAYOUT: &str = "pub struct Foo { pub a: i32, }\npub struct Bar { pub b: f64, }\n";
+
+    // A new, empty directory in the temporary directory that's unique to a test. The directory is
+    // removed when it's dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(test: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("jlrs-reflect-{}-{}", process::id(), test));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+            self.0.join(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn check(path: &Path, generated: &str, update: bool) -> syn::Result<()> {
+        let items = parse_layouts(generated, Span::call_site())?;
+        let check = LitStr::new("layouts.rs", Span::call_site());
+        check_layouts(path, generated, &items, &check, update)
+    }
+
+    #[test]
+    fn parse_types() {
+        let input: ReflectInput = syn::parse_str(r#""Foo", "Bar{Int32}""#).unwrap();
+        let types: Vec<_> = input.types.iter().map(LitStr::value).collect();
+        assert_eq!(types, ["Foo", "Bar{Int32}"]);
+        assert!(input.check.is_none());
+    }
+
+    #[test]
+    fn parse_check() {
+        let input: ReflectInput = syn::parse_str(r#""Foo"; check = "layouts.rs""#).unwrap();
+        assert_eq!(input.types.len(), 1);
+        assert_eq!(input.check.unwrap().value(), "layouts.rs");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(syn::parse_str::<ReflectInput>("").is_err());
+        assert!(syn::parse_str::<ReflectInput>(r#"; check = "layouts.rs""#).is_err());
+        assert!(syn::parse_str::<ReflectInput>(r#""Foo"; verify = "layouts.rs""#).is_err());
+        assert!(syn::parse_str::<ReflectInput>(r#""Foo" "Bar""#).is_err());
+    }
+
+    #[test]
+    fn unchanged_layouts() {
+        let dir = TempDir::new("unchanged");
+        let path = dir.join("layouts.rs");
+        fs::write(&path, LAYOUT).unwrap();
+        assert!(check(&path, LAYOUT, false).is_ok());
+    }
+
+    #[test]
+    fn missing_file() {
+        let dir = TempDir::new("missing");
+        let path = dir.join("layouts.rs");
+        let err = check(&path, LAYOUT, false).unwrap_err().to_string();
+        assert!(err.contains("doesn't exist"));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn changed_layouts() {
+        let dir = TempDir::new("changed");
+        let path = dir.join("layouts.rs");
+        fs::write(&path, LAYOUT).unwrap();
+
+        let generated = "pub struct Foo { pub a: i64, }\npub struct Baz { pub c: u8, }\n";
+        let err = check(&path, generated, false).unwrap_err().to_string();
+        assert!(err.contains("the layout of `Foo` has changed"));
+        assert!(err.contains("`Baz` has been added"));
+        assert!(err.contains("`Bar` has been removed"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), LAYOUT);
+    }
+
+    #[test]
+    fn update_layouts() {
+        let dir = TempDir::new("update");
+        let path = dir.join("layouts").join("layouts.rs");
+        check(&path, LAYOUT, true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), LAYOUT);
+
+        let generated = "pub struct Foo { pub a: i64, }\n";
+        check(&path, generated, true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), generated);
+        assert!(check(&path, generated, false).is_ok());
+    }
+}
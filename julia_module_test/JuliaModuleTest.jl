@@ -7,6 +7,7 @@ catch e
 end
 
 using JlrsCore.Ledger
+using Serialization
 using Test
 
 module JuliaModuleTest
@@ -50,6 +51,22 @@ end
     @test JuliaModuleTest.freestanding_func_ret_rust_result(false) == 3
     @inferred JuliaModuleTest.freestanding_func_ret_rust_result(false)
     @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_ret_rust_result(true)
+
+    @test JuliaModuleTest.freestanding_func_kwargs(1; b=2) == 123.5
+    @test JuliaModuleTest.freestanding_func_kwargs(1, 4; b=2, d=0.25) == 124.25
+    @test JuliaModuleTest.freestanding_func_kwargs(1, 2, 3, 0.0) == 123.0
+    @test_throws UndefKeywordError JuliaModuleTest.freestanding_func_kwargs(1)
+
+    @test JuliaModuleTest.freestanding_func_typed_defaults() == 12.5
+    @test JuliaModuleTest.freestanding_func_typed_defaults(UInt8(2); b=Int32(3)) == 23.5
+    @test JuliaModuleTest.freestanding_func_typed_defaults(; c=Float32(0.25)) == 12.25
+    @test_throws TypeError JuliaModuleTest.freestanding_func_typed_defaults(; b=3)
+
+    @test JuliaModuleTest.scale(Int32(3)) === Int32(6)
+    @test JuliaModuleTest.scale(Int32(3); by=Int32(3)) === Int32(9)
+    @test JuliaModuleTest.scale(3.0) === 1.5
+    @test JuliaModuleTest.scale(3.0; by=2.0) === 6.0
+    @test_throws TypeError JuliaModuleTest.scale(Int32(3); by=2.0)
 end
 
 @testset "OpaqueInt" begin
@@ -71,6 +88,19 @@ end
 
     @test JuliaModuleTest.unbox_opaque(opaque_int) == Int32(1)
     @inferred JuliaModuleTest.unbox_opaque(opaque_int)
+
+    @test JuliaModuleTest.add_opaque(opaque_int) == Int32(2)
+    @test JuliaModuleTest.add_opaque(opaque_int; incr=Int32(2)) == Int32(3)
+
+    @test propertynames(opaque_int) == (:value,)
+    @test opaque_int.value == Int32(1)
+    opaque_int.value = Int32(5)
+    @test opaque_int.value == Int32(5)
+    opaque_int.value = 6
+    @test opaque_int.value === Int32(6)
+    @test_throws Exception opaque_int.other
+    @test !isdefined(JuliaModuleTest, :value)
+    @test !isdefined(JuliaModuleTest, :set_value)
 end
 
 @testset "ForeignThing" begin
@@ -88,6 +118,125 @@ end
     @test JuliaModuleTest.extract_inner(foreign_thing) == UInt32(1)
 end
 
+@testset "Parametric opaque type" begin
+    @test JuliaModuleTest.POpaque isa UnionAll
+
+    p32 = JuliaModuleTest.POpaque(Float32(1.0))
+    @test p32 isa JuliaModuleTest.POpaque{Float32}
+    @test JuliaModuleTest.popaque_get(p32) === Float32(1.0)
+    @inferred JuliaModuleTest.popaque_get(p32)
+
+    p64 = JuliaModuleTest.POpaque(2.0)
+    @test p64 isa JuliaModuleTest.POpaque{Float64}
+    @test JuliaModuleTest.popaque_get(p64) === 2.0
+
+    @test_throws MethodError JuliaModuleTest.POpaque(1)
+end
+
+@testset "Enum" begin
+    @test JuliaModuleTest.Color <: Enum{Int32}
+    @test Int32(JuliaModuleTest.Green) == 1
+    @test JuliaModuleTest.next_color(JuliaModuleTest.Red) === JuliaModuleTest.Green
+    @test JuliaModuleTest.next_color(JuliaModuleTest.Blue) === JuliaModuleTest.Red
+    @inferred JuliaModuleTest.next_color(JuliaModuleTest.Red)
+end
+
+@testset "Iterators" begin
+    @test Base.IteratorSize(JuliaModuleTest.Countdown) == Base.HasLength()
+    @test eltype(JuliaModuleTest.Countdown) == UInt32
+    countdown = JuliaModuleTest.Countdown(UInt32(3))
+    @test length(countdown) == 3
+    @test collect(countdown) == UInt32[2, 1, 0]
+    @test length(countdown) == 0
+
+    @test Base.IteratorSize(JuliaModuleTest.Fibonacci) == Base.SizeUnknown()
+    @test eltype(JuliaModuleTest.Fibonacci) == UInt64
+    fibs = UInt64[]
+    for x in JuliaModuleTest.Fibonacci()
+        x > 20 && break
+        push!(fibs, x)
+    end
+    @test fibs == UInt64[0, 1, 1, 2, 3, 5, 8, 13]
+end
+
+@testset "Arrays" begin
+    @test JuliaModuleTest.Grid <: AbstractMatrix{Float64}
+    @test IndexStyle(JuliaModuleTest.Grid) == IndexCartesian()
+    grid = JuliaModuleTest.Grid(UInt(2), UInt(3))
+    @test size(grid) == (2, 3)
+    @test grid[1, 1] == 0.0
+    @test grid[2, 3] == 5.0
+    @test grid[4] == 3.0
+    @test_throws BoundsError grid[3, 1]
+    @test sum(grid) == 15.0
+    @test collect(grid) == [0.0 2.0 4.0; 1.0 3.0 5.0]
+
+    grid[1, 2] = 7
+    @test grid[1, 2] == 7.0
+    @test_throws InexactError grid[1, 1] = 1.5im
+
+    @test strides(grid) == (1, 2)
+    @test unsafe_load(pointer(grid), 3) == 7.0
+end
+
+@testset "Derived Base methods" begin
+    v1 = JuliaModuleTest.Version(UInt32(1), UInt32(2), UInt32(3))
+    v2 = JuliaModuleTest.Version(UInt32(1), UInt32(2), UInt32(3))
+    v3 = JuliaModuleTest.Version(UInt32(0), UInt32(9), UInt32(0))
+
+    @test repr(v1) == "Version { major: 1, minor: 2, patch: 3 }"
+    @test string(v1) == "v1.2.3"
+
+    @test v1 == v2
+    @test isequal(v1, v2)
+    @test v1 != v3
+    @test hash(v1) == hash(v2)
+
+    d = Dict(v1 => 1)
+    @test d[v2] == 1
+
+    v4 = copy(v1)
+    @test v4 == v1
+    @test v4 !== v1
+    @test deepcopy([v1, v1])[1] == v1
+
+    @test isless(v3, v1)
+    @test sort([v1, v3]) == [v3, v1]
+end
+
+@testset "Serialization" begin
+    v = JuliaModuleTest.Version(UInt32(1), UInt32(2), UInt32(3))
+    io = IOBuffer()
+    serialize(io, [v, v])
+    seekstart(io)
+    vs = deserialize(io)
+    @test vs isa Vector{JuliaModuleTest.Version}
+    @test vs[1] == v
+    @test vs[1] === vs[2]
+end
+
+@testset "Generated helpers" begin
+    helpers = filter(name -> startswith(string(name), "#jlrs_"), names(JuliaModuleTest; all=true))
+    @test Symbol("#jlrs_iterator_next_Countdown") in helpers
+    @test Symbol("#jlrs_array_getindex_Grid") in helpers
+    @test Symbol("#jlrs_serialize_Version") in helpers
+    @test !any(name -> startswith(string(name), "__jlrs"), names(JuliaModuleTest; all=true))
+end
+
+@testset "Trait objects" begin
+    @test isabstracttype(JuliaModuleTest.Shape)
+    @test JuliaModuleTest.Circle <: JuliaModuleTest.Shape
+    @test JuliaModuleTest.Square <: JuliaModuleTest.Shape
+
+    shapes = JuliaModuleTest.Shape[JuliaModuleTest.Circle(1.0), JuliaModuleTest.Square(2.0)]
+    @test JuliaModuleTest.area(shapes[1]) ≈ π
+    @test JuliaModuleTest.area(shapes[2]) == 4.0
+
+    foreach(s -> JuliaModuleTest.scale!(s, 2.0), shapes)
+    @test JuliaModuleTest.area(shapes[1]) ≈ 4π
+    @test JuliaModuleTest.area(shapes[2]) == 16.0
+end
+
 @testset "Associated function" begin
     @test JuliaModuleTest.assoc_func() == 1
     @inferred JuliaModuleTest.assoc_func()
@@ -106,6 +255,15 @@ end
     @test_throws JlrsCore.JlrsError JuliaModuleTest.async_callback_callback_err()
 end
 
+@testset "Async future" begin
+    task = JuliaModuleTest.async_future(1, 2)
+    @test task isa Task
+    @test fetch(task) == 3
+
+    @test_throws MethodError JuliaModuleTest.async_future(1.0, 2)
+    @test_throws TaskFailedException fetch(JuliaModuleTest.async_future_err())
+end
+
 @testset "Constants and globals" begin
     @test JuliaModuleTest.CONST_U8 == 0x1
     @test isconst(JuliaModuleTest, :CONST_U8)
@@ -120,6 +278,12 @@ end
     @test !isconst(JuliaModuleTest, :STATIC_U8)
 end
 
+@testset "Precompiled package" begin
+    script = joinpath(@__DIR__, "precompiled_test.jl")
+    cmd = `$(Base.julia_cmd()) --startup-file=no $script`
+    @test success(pipeline(cmd; stdout=stdout, stderr=stderr))
+end
+
 # using BenchmarkTools
 #
 # v = Vector{UInt32}()
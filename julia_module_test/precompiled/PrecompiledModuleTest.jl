@@ -0,0 +1,9 @@
+module PrecompiledModuleTest
+using JlrsCore.Wrap
+
+@wrapmodule("./libjulia_module_test", :julia_module_tests_init_fn)
+
+function __init__()
+    @initjlrs
+end
+end
@@ -0,0 +1,42 @@
+# Loads the library as a precompiled package. The methods generated for exported types are
+# defined while the package is precompiled, these tests check that they're available after the
+# package has been loaded from the cache. This script is run in a separate process by
+# JuliaModuleTest.jl because the library can only be initialized once per process.
+
+cd(@__DIR__)
+pushfirst!(LOAD_PATH, joinpath(@__DIR__, "precompiled"))
+
+using Serialization
+using Test
+
+Base.compilecache(Base.identify_package("PrecompiledModuleTest"))
+using PrecompiledModuleTest
+
+const M = PrecompiledModuleTest
+
+@testset "Precompiled module" begin
+    @test Base.IteratorSize(M.Countdown) == Base.HasLength()
+    @test eltype(M.Countdown) == UInt32
+    @test collect(M.Countdown(UInt32(3))) == UInt32[2, 1, 0]
+
+    grid = M.Grid(UInt(2), UInt(3))
+    @test size(grid) == (2, 3)
+    @test grid[2, 3] == 5.0
+    grid[1, 2] = 7
+    @test grid[1, 2] == 7.0
+    @test strides(grid) == (1, 2)
+
+    v1 = M.Version(UInt32(1), UInt32(2), UInt32(3))
+    v2 = M.Version(UInt32(0), UInt32(9), UInt32(0))
+    @test string(v1) == "v1.2.3"
+    @test v1 == copy(v1)
+    @test hash(v1) == hash(copy(v1))
+    @test isless(v2, v1)
+
+    io = IOBuffer()
+    serialize(io, [v1, v1])
+    seekstart(io)
+    vs = deserialize(io)
+    @test vs[1] == v1
+    @test vs[1] === vs[2]
+end
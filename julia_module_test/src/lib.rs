@@ -1,19 +1,28 @@
 use jlrs::{
-    ccall::AsyncCallback,
+    ccall::{AsyncCallback, DefaultExecutor, RustFuture},
+    convert::into_julia::IntoJulia,
     data::{
         managed::{
             array::{ArrayRet, TypedArrayUnbound},
             ccall_ref::CCallRef,
+            datatype::DataTypeData,
             rust_result::{RustResult, RustResultRet},
             value::typed::{TypedValue, TypedValueRef, TypedValueRet},
         },
         types::{
             abstract_types::{AnyType, Number},
-            foreign_type::{ForeignType, OpaqueType},
+            construct_type::ConstructType,
+            foreign_array::{abstract_array_super_type, ForeignArray},
+            foreign_serialization::SerializableForeign,
+            foreign_type::{ForeignType, OpaqueType, ParametricBase, ParametricVariant},
+            trait_object::trait_abstract_type,
         },
     },
     error::JlrsError,
-    memory::gc::{mark_queue_obj, write_barrier},
+    memory::{
+        gc::{mark_queue_obj, write_barrier},
+        target::frame::GcFrame,
+    },
     prelude::*,
 };
 
@@ -87,6 +96,22 @@ unsafe extern "C" fn freestanding_func_ret_rust_result(throw_err: Bool) -> RustR
     })
 }
 
+unsafe extern "C" fn freestanding_func_kwargs(a: isize, b: isize, c: isize, d: f64) -> f64 {
+    (100 * a + 10 * b + c) as f64 + d
+}
+
+unsafe extern "C" fn freestanding_func_typed_defaults(a: u8, b: i32, c: f32) -> f64 {
+    (10 * a as i32 + b) as f64 + c as f64
+}
+
+unsafe extern "C" fn scale_i32(a: i32, by: i32) -> i32 {
+    a * by
+}
+
+unsafe extern "C" fn scale_f64(a: f64, by: f64) -> f64 {
+    a * by
+}
+
 #[derive(Clone, Debug)]
 struct OpaqueInt {
     a: i32,
@@ -121,6 +146,30 @@ impl OpaqueInt {
         }
     }
 
+    fn add(&self, incr: i32) -> RustResultRet<i32> {
+        unsafe {
+            CCall::invoke(|mut frame| {
+                let data = TypedValue::new(&mut frame, self.a + incr);
+                RustResult::ok(frame.as_extended_target(), data).leak()
+            })
+        }
+    }
+
+    fn value(&self) -> RustResultRet<i32> {
+        self.get()
+    }
+
+    fn set_value(&mut self, value: i32) -> RustResultRet<Nothing> {
+        self.a = value;
+
+        unsafe {
+            CCall::invoke(|mut frame| {
+                let nothing = Value::nothing(&frame).as_typed_unchecked::<Nothing>();
+                RustResult::ok(frame.as_extended_target(), nothing).leak()
+            })
+        }
+    }
+
     fn get_cloned(self) -> RustResultRet<i32> {
         unsafe {
             CCall::invoke(|mut frame| {
@@ -179,6 +228,311 @@ impl ForeignThing {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct POpaque<T> {
+    value: T,
+}
+
+impl<T> POpaque<T>
+where
+    T: 'static + Send + ConstructType + IntoJulia + Copy,
+{
+    fn new(value: T) -> TypedValueRet<POpaque<T>> {
+        unsafe { CCall::invoke(|mut frame| TypedValue::new(&mut frame, POpaque { value }).leak()) }
+    }
+
+    fn popaque_get(&self) -> RustResultRet<T> {
+        unsafe {
+            CCall::invoke(|mut frame| {
+                let data = TypedValue::new(&mut frame, self.value);
+                RustResult::ok(frame.as_extended_target(), data).leak()
+            })
+        }
+    }
+}
+
+unsafe impl<T: 'static + Send> OpaqueType for POpaque<T> {}
+
+unsafe impl<T: 'static + Send> ParametricBase for POpaque<T> {
+    type Key = POpaque<()>;
+
+    fn type_parameters() -> &'static [&'static str] {
+        &["T"]
+    }
+}
+
+unsafe impl<T: 'static + Send + ConstructType> ParametricVariant for POpaque<T> {
+    fn variant_parameters<'target>(frame: &mut GcFrame<'target>) -> Vec<Value<'target, 'static>> {
+        vec![T::construct_type(frame.as_extended_target())]
+    }
+}
+
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Enum)]
+enum Color {
+    Red = 0,
+    Green = 1,
+    Blue = 2,
+}
+
+unsafe extern "C" fn next_color(color: Color) -> Color {
+    match color {
+        Color::Red => Color::Green,
+        Color::Green => Color::Blue,
+        Color::Blue => Color::Red,
+    }
+}
+
+pub struct Countdown {
+    remaining: u32,
+}
+
+impl Countdown {
+    fn new(from: u32) -> TypedValueRet<Countdown> {
+        unsafe {
+            CCall::invoke(|mut frame| {
+                TypedValue::new(&mut frame, Countdown { remaining: from }).leak()
+            })
+        }
+    }
+}
+
+impl Iterator for Countdown {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.remaining == 0 {
+            None
+        } else {
+            self.remaining -= 1;
+            Some(self.remaining)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl ExactSizeIterator for Countdown {}
+
+unsafe impl OpaqueType for Countdown {}
+
+pub struct Fibonacci {
+    current: u64,
+    next: u64,
+}
+
+impl Fibonacci {
+    fn new() -> TypedValueRet<Fibonacci> {
+        unsafe {
+            CCall::invoke(|mut frame| {
+                TypedValue::new(
+                    &mut frame,
+                    Fibonacci {
+                        current: 0,
+                        next: 1,
+                    },
+                )
+                .leak()
+            })
+        }
+    }
+}
+
+impl Iterator for Fibonacci {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let current = self.current;
+        self.current = self.next;
+        self.next = self.next.checked_add(current)?;
+        Some(current)
+    }
+}
+
+unsafe impl OpaqueType for Fibonacci {}
+
+pub struct Grid {
+    data: Vec<f64>,
+    n_rows: usize,
+    n_cols: usize,
+}
+
+impl Grid {
+    fn new(n_rows: usize, n_cols: usize) -> TypedValueRet<Grid> {
+        unsafe {
+            CCall::invoke(|mut frame| {
+                let data = (0..n_rows * n_cols).map(|i| i as f64).collect();
+                TypedValue::new(
+                    &mut frame,
+                    Grid {
+                        data,
+                        n_rows,
+                        n_cols,
+                    },
+                )
+                .leak()
+            })
+        }
+    }
+}
+
+unsafe impl OpaqueType for Grid {
+    fn super_type<'target, Tgt>(target: Tgt) -> DataTypeData<'target, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        abstract_array_super_type::<Self, Tgt>(target)
+    }
+}
+
+impl ForeignArray for Grid {
+    type Elem = f64;
+    const RANK: usize = 2;
+
+    fn size(&self) -> Vec<usize> {
+        vec![self.n_rows, self.n_cols]
+    }
+
+    fn get(&self, index: &[usize]) -> f64 {
+        self.data[index[0] + index[1] * self.n_rows]
+    }
+
+    fn set(&mut self, index: &[usize], value: f64) -> JlrsResult<()> {
+        self.data[index[0] + index[1] * self.n_rows] = value;
+        Ok(())
+    }
+
+    fn strides(&self) -> Option<Vec<usize>> {
+        Some(vec![1, self.n_rows])
+    }
+
+    fn data_ptr(&self) -> *mut f64 {
+        self.data.as_ptr() as *mut f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl Version {
+    fn new(major: u32, minor: u32, patch: u32) -> TypedValueRet<Version> {
+        unsafe {
+            CCall::invoke(|mut frame| {
+                TypedValue::new(
+                    &mut frame,
+                    Version {
+                        major,
+                        minor,
+                        patch,
+                    },
+                )
+                .leak()
+            })
+        }
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+unsafe impl OpaqueType for Version {}
+
+impl SerializableForeign for Version {
+    fn to_bytes(&self) -> JlrsResult<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(12);
+        bytes.extend_from_slice(&self.major.to_le_bytes());
+        bytes.extend_from_slice(&self.minor.to_le_bytes());
+        bytes.extend_from_slice(&self.patch.to_le_bytes());
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> JlrsResult<Self> {
+        if bytes.len() != 12 {
+            Err(JlrsError::exception("invalid number of bytes"))?
+        }
+
+        let read =
+            |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Ok(Version {
+            major: read(0),
+            minor: read(4),
+            patch: read(8),
+        })
+    }
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn scale(&mut self, factor: f64);
+}
+
+pub struct Circle {
+    radius: f64,
+}
+
+impl Circle {
+    fn new(radius: f64) -> TypedValueRet<Circle> {
+        unsafe { CCall::invoke(|mut frame| TypedValue::new(&mut frame, Circle { radius }).leak()) }
+    }
+}
+
+unsafe impl OpaqueType for Circle {
+    fn super_type<'target, Tgt>(target: Tgt) -> DataTypeData<'target, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        trait_abstract_type::<dyn Shape, Tgt>(target)
+    }
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.radius *= factor;
+    }
+}
+
+pub struct Square {
+    side: f64,
+}
+
+impl Square {
+    fn new(side: f64) -> TypedValueRet<Square> {
+        unsafe { CCall::invoke(|mut frame| TypedValue::new(&mut frame, Square { side }).leak()) }
+    }
+}
+
+unsafe impl OpaqueType for Square {
+    fn super_type<'target, Tgt>(target: Tgt) -> DataTypeData<'target, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        trait_abstract_type::<dyn Shape, Tgt>(target)
+    }
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.side *= factor;
+    }
+}
+
 struct UnexportedType;
 
 impl UnexportedType {
@@ -201,11 +555,23 @@ fn async_callback_callback_err() -> JlrsResult<impl AsyncCallback<isize>> {
     Ok(move || Err(JlrsError::exception("Err"))?)
 }
 
+fn async_future(a: isize, b: isize) -> JlrsResult<impl RustFuture<isize>> {
+    Ok(async move { Ok(a + b) })
+}
+
+fn async_future_err() -> JlrsResult<impl RustFuture<isize>> {
+    Ok(async move { Err(JlrsError::exception("Err"))? })
+}
+
+fn julia_module_tests_executor() -> DefaultExecutor {
+    DefaultExecutor::new()
+}
+
 const CONST_U8: u8 = 1;
 static STATIC_U8: u8 = 2;
 
 julia_module! {
-    become julia_module_tests_init_fn;
+    become julia_module_tests_init_fn with julia_module_tests_executor;
 
     fn freestanding_func_trivial();
     fn freestanding_func_noargs() -> usize;
@@ -218,18 +584,68 @@ julia_module! {
     fn freestanding_func_typevaluearg(a: TypedValue<usize>) -> usize;
     fn freestanding_func_ret_array(dt: DataType) -> ArrayRet;
     fn freestanding_func_ret_rust_result(throw_err: Bool) -> RustResultRet<i32>;
+    fn freestanding_func_kwargs(a: isize, #[kw] b: isize, c: isize = 3, #[kw] d: f64 = 0.5) -> f64;
+    fn freestanding_func_typed_defaults(a: u8 = 1, #[kw] b: i32 = 2, #[kw] c: f32 = 0.5) -> f64;
+    fn scale_i32(a: i32, #[kw] by: i32 = 2) -> i32 as scale;
+    fn scale_f64(a: f64, #[kw] by: f64 = 0.5) -> f64 as scale;
 
     struct OpaqueInt;
     in OpaqueInt fn new(value: i32) -> TypedValueRet<OpaqueInt> as OpaqueInt;
     in OpaqueInt fn increment(&mut self) -> RustResultRet<Nothing> as increment!;
     in OpaqueInt fn get(&self) -> RustResultRet<i32> as unbox_opaque;
     in OpaqueInt fn get_cloned(self) -> RustResultRet<i32>;
+    in OpaqueInt fn add(&self, #[kw] incr: i32 = 1i32) -> RustResultRet<i32> as add_opaque;
+
+    #[jlrs(property)]
+    in OpaqueInt fn value(&self) -> RustResultRet<i32>;
+    #[jlrs(property)]
+    #[doc = "    set_value(opaque_int::OpaqueInt, value::Int32)"]
+    in OpaqueInt fn set_value(&mut self, value: i32) -> RustResultRet<Nothing>;
 
     struct ForeignThing;
     in ForeignThing fn new(value: Value) -> TypedValueRet<ForeignThing> as ForeignThing;
     in ForeignThing fn get(&self) -> RustResultRet<AnyType> as extract_inner;
     in ForeignThing fn set(&mut self, value: Value) -> RustResultRet<Nothing> as set_inner!;
 
+    for T in [f32, f64] {
+        struct POpaque<T>;
+        in POpaque<T> fn new(value: T) -> TypedValueRet<POpaque<T>> as POpaque;
+        in POpaque<T> fn popaque_get(&self) -> RustResultRet<T>;
+    };
+
+    enum Color;
+    fn next_color(color: Color) -> Color;
+
+    #[jlrs(iterator, exact_size)]
+    struct Countdown;
+    in Countdown fn new(from: u32) -> TypedValueRet<Countdown> as Countdown;
+
+    #[jlrs(iterator)]
+    struct Fibonacci;
+    in Fibonacci fn new() -> TypedValueRet<Fibonacci> as Fibonacci;
+
+    #[jlrs(array, strided)]
+    struct Grid;
+    in Grid fn new(n_rows: usize, n_cols: usize) -> TypedValueRet<Grid> as Grid;
+
+    #[jlrs(derive(Debug, Display, PartialEq, Hash, Clone, Ord))]
+    #[jlrs(serializable)]
+    struct Version;
+    in Version fn new(major: u32, minor: u32, patch: u32) -> TypedValueRet<Version> as Version;
+
+    trait Shape {
+        fn area(&self) -> f64;
+        fn scale(&mut self, factor: f64) as scale!;
+    }
+
+    #[jlrs(implements(Shape))]
+    struct Circle;
+    in Circle fn new(radius: f64) -> TypedValueRet<Circle> as Circle;
+
+    #[jlrs(implements(Shape))]
+    struct Square;
+    in Square fn new(side: f64) -> TypedValueRet<Square> as Square;
+
     in UnexportedType fn assoc_func() -> isize;
 
     #[doc = "    async_callback(array::Array{Int})::Int"]
@@ -239,6 +655,9 @@ julia_module! {
     async fn async_callback_init_err() -> JlrsResult<impl AsyncCallback<isize>>;
     async fn async_callback_callback_err() -> JlrsResult<impl AsyncCallback<isize>>;
 
+    async fn async_future(a: isize, b: isize) -> JlrsResult<impl RustFuture<isize>>;
+    async fn async_future_err() -> JlrsResult<impl RustFuture<isize>>;
+
     const CONST_U8: u8;
     static CONST_U8: u8 as STATIC_CONST_U8;
     const STATIC_U8: u8 as CONST_STATIC_U8;